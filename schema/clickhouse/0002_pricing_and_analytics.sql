@@ -0,0 +1,177 @@
+-- Transaction sender, MEV role and price provenance on events
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS tx_from String DEFAULT '' CODEC(ZSTD(1)) AFTER owner;
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS mev_role LowCardinality(String) DEFAULT '' AFTER is_suspicious;
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS price_confidence Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)) AFTER mev_role;
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS price_source LowCardinality(String) DEFAULT '' AFTER price_confidence;
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS price_method LowCardinality(String) DEFAULT '' AFTER price_source;
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS price_source_pools Array(String) DEFAULT [] CODEC(ZSTD(1)) AFTER price_method;
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS price_hops UInt8 DEFAULT 0 CODEC(T64, LZ4) AFTER price_source_pools;
+ALTER TABLE indexer.events ADD COLUMN IF NOT EXISTS price_min_liquidity_usd Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)) AFTER price_hops;
+-- Index for MEV filtering (sandwich legs and victims)
+ALTER TABLE indexer.events ADD INDEX IF NOT EXISTS idx_mev_role mev_role TYPE set(0) GRANULARITY 4;
+-- Index for filtering out low-confidence prices
+ALTER TABLE indexer.events ADD INDEX IF NOT EXISTS idx_price_confidence price_confidence TYPE minmax GRANULARITY 4;
+
+-- Fee revenue on pool snapshots
+ALTER TABLE indexer.pool_snapshots ADD COLUMN IF NOT EXISTS fees_7d Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)) AFTER fees_24h;
+ALTER TABLE indexer.pool_snapshots ADD COLUMN IF NOT EXISTS fee_apr Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)) AFTER fees_7d;
+
+-- Trader Positions (per wallet and token, maintained by the update_trader_positions cron job)
+-- Each refresh inserts a new row version, read with FINAL.
+CREATE TABLE IF NOT EXISTS indexer.trader_positions (
+    chain_id            UInt64 CODEC(Delta, LZ4),
+    wallet              String CODEC(ZSTD(1)),
+    token_address       String CODEC(ZSTD(1)),
+    quantity            Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    cost_basis_usd      Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    realized_pnl_usd    Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    unrealized_pnl_usd  Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    mark_price_usd      Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    total_bought_usd    Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    total_sold_usd      Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    buys                UInt64 DEFAULT 0 CODEC(T64, LZ4),
+    sells               UInt64 DEFAULT 0 CODEC(T64, LZ4),
+    first_trade         DateTime CODEC(DoubleDelta, ZSTD(1)),
+    last_trade          DateTime CODEC(DoubleDelta, ZSTD(1)),
+    last_block_number   UInt64 CODEC(Delta, ZSTD(1)),
+    updated_at          DateTime CODEC(DoubleDelta, ZSTD(1)),
+
+    PROJECTION by_token (
+        SELECT * ORDER BY (chain_id, token_address, wallet)
+    )
+) ENGINE = ReplacingMergeTree(updated_at)
+ORDER BY (chain_id, wallet, token_address)
+SETTINGS deduplicate_merge_projection_mode = 'rebuild';
+
+-- Wallet PnL totals for leaderboards
+CREATE VIEW IF NOT EXISTS indexer.trader_pnl AS
+SELECT
+    chain_id,
+    wallet,
+    sum(realized_pnl_usd) AS realized_pnl_usd,
+    sum(unrealized_pnl_usd) AS unrealized_pnl_usd,
+    sum(realized_pnl_usd + unrealized_pnl_usd) AS total_pnl_usd,
+    sum(total_bought_usd + total_sold_usd) AS volume_usd,
+    countIf(quantity > 0) AS open_positions,
+    max(last_trade) AS last_trade
+FROM indexer.trader_positions FINAL
+GROUP BY chain_id, wallet;
+
+-- Block range pruning for partial purges
+ALTER TABLE indexer.supply_events ADD INDEX IF NOT EXISTS idx_block_number block_number TYPE minmax GRANULARITY 4;
+
+-- MEV Sandwiches (one row per front-run, victim, back-run triple)
+CREATE TABLE IF NOT EXISTS indexer.mev_sandwiches (
+    chain_id            UInt64 CODEC(Delta, LZ4),
+    block_number        UInt64 CODEC(Delta, ZSTD(1)),
+    timestamp           DateTime CODEC(DoubleDelta, ZSTD(1)),
+    pool_address        String CODEC(ZSTD(1)),
+    attacker            String CODEC(ZSTD(1)),
+    victim              String CODEC(ZSTD(1)),
+    frontrun_tx_hash    String CODEC(ZSTD(1)),
+    frontrun_log_index  UInt32 CODEC(Delta, LZ4),
+    victim_tx_hash      String CODEC(ZSTD(1)),
+    victim_log_index    UInt32 CODEC(Delta, LZ4),
+    backrun_tx_hash     String CODEC(ZSTD(1)),
+    backrun_log_index   UInt32 CODEC(Delta, LZ4),
+    token_bought        String CODEC(ZSTD(1)),
+    profit_usd          Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    victim_loss_usd     Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+
+    INDEX idx_attacker attacker TYPE bloom_filter GRANULARITY 4,
+    INDEX idx_victim victim TYPE bloom_filter GRANULARITY 4
+) ENGINE = ReplacingMergeTree()
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (chain_id, pool_address, timestamp, frontrun_tx_hash, victim_log_index);
+
+-- Atomic Arbitrages (closed token cycles within one transaction)
+CREATE TABLE IF NOT EXISTS indexer.arbitrages (
+    chain_id            UInt64 CODEC(Delta, LZ4),
+    block_number        UInt64 CODEC(Delta, ZSTD(1)),
+    timestamp           DateTime CODEC(DoubleDelta, ZSTD(1)),
+    tx_hash             String CODEC(ZSTD(1)),
+    first_log_index     UInt32 CODEC(Delta, LZ4),
+    last_log_index      UInt32 CODEC(Delta, LZ4),
+    trader              String CODEC(ZSTD(1)),
+    token               String CODEC(ZSTD(1)),
+    path                Array(String) CODEC(ZSTD(1)),
+    pools               Array(String) CODEC(ZSTD(1)),
+    hops                UInt8 CODEC(T64, LZ4),
+    amount_in           Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    amount_out          Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    profit_usd          Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    volume_usd          Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+
+    INDEX idx_trader trader TYPE bloom_filter GRANULARITY 4,
+    INDEX idx_pools pools TYPE bloom_filter GRANULARITY 4
+) ENGINE = ReplacingMergeTree()
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (chain_id, timestamp, tx_hash, first_log_index);
+
+-- Trades (swap hops of one transaction chained into user trades)
+CREATE TABLE IF NOT EXISTS indexer.trades (
+    chain_id            UInt64 CODEC(Delta, LZ4),
+    block_number        UInt64 CODEC(Delta, ZSTD(1)),
+    timestamp           DateTime CODEC(DoubleDelta, ZSTD(1)),
+    tx_hash             String CODEC(ZSTD(1)),
+    first_log_index     UInt32 CODEC(Delta, LZ4),
+    last_log_index      UInt32 CODEC(Delta, LZ4),
+    trader              String CODEC(ZSTD(1)),
+    token_in            String CODEC(ZSTD(1)),
+    token_out           String CODEC(ZSTD(1)),
+    amount_in           Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    amount_out          Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    route               Array(String) CODEC(ZSTD(1)),
+    path                Array(String) CODEC(ZSTD(1)),
+    hops                UInt8 CODEC(T64, LZ4),
+    amount_in_usd       Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    amount_out_usd      Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    volume_usd          Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+
+    PROJECTION by_trader (
+        SELECT * ORDER BY (chain_id, trader, timestamp, tx_hash, first_log_index)
+    ),
+
+    INDEX idx_token_in token_in TYPE bloom_filter GRANULARITY 4,
+    INDEX idx_token_out token_out TYPE bloom_filter GRANULARITY 4
+) ENGINE = ReplacingMergeTree()
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (chain_id, timestamp, tx_hash, first_log_index);
+
+-- Trade counts and volume counted once per trade, next to the per-hop swap stats.
+-- Plain views over FINAL: an insert-time view would count re-inserted trades twice.
+CREATE VIEW IF NOT EXISTS indexer.hourly_trade_stats AS
+SELECT
+    chain_id,
+    toStartOfHour(timestamp) AS hour,
+    count() AS trade_count,
+    sum(volume_usd) AS trade_volume_usd
+FROM indexer.trades FINAL
+GROUP BY chain_id, hour;
+
+CREATE VIEW IF NOT EXISTS indexer.global_trade_stats AS
+SELECT
+    chain_id,
+    count() AS total_trades,
+    sum(volume_usd) AS total_trade_volume_usd
+FROM indexer.trades FINAL
+GROUP BY chain_id;
+
+-- Wash Trades (events flagged by the detect_wash_trading cron job)
+-- Events are immutable, so flags live here and are joined on (chain_id, tx_hash, log_index).
+-- An event can be flagged for several reasons, one row each.
+CREATE TABLE IF NOT EXISTS indexer.wash_trades (
+    chain_id            UInt64 CODEC(Delta, LZ4),
+    pool_address        String CODEC(ZSTD(1)),
+    tx_hash             String CODEC(ZSTD(1)),
+    log_index           UInt32 CODEC(Delta, LZ4),
+    timestamp           DateTime CODEC(DoubleDelta, ZSTD(1)),
+    wallet              String CODEC(ZSTD(1)),
+    reason              LowCardinality(String),
+    volume_usd          Float64 DEFAULT 0 CODEC(Gorilla, ZSTD(1)),
+    detected_at         DateTime DEFAULT now() CODEC(DoubleDelta, ZSTD(1)),
+
+    INDEX idx_wallet wallet TYPE bloom_filter GRANULARITY 4
+) ENGINE = ReplacingMergeTree(detected_at)
+PARTITION BY toYYYYMM(timestamp)
+ORDER BY (chain_id, pool_address, tx_hash, log_index, reason);
@@ -0,0 +1,66 @@
+-- Chain configuration: supply exclusions, reference pools and pricing strategy
+ALTER TABLE indexer.chains ADD COLUMN IF NOT EXISTS reference_pools TEXT[] DEFAULT '{}';
+ALTER TABLE indexer.chains ADD COLUMN IF NOT EXISTS supply_excluded_addresses TEXT[] DEFAULT '{}';
+ALTER TABLE indexer.chains ADD COLUMN IF NOT EXISTS pricing_strategy TEXT DEFAULT 'default';
+ALTER TABLE indexer.chains ADD COLUMN IF NOT EXISTS min_pool_tvl_usd DOUBLE PRECISION;
+ALTER TABLE indexer.chains ADD COLUMN IF NOT EXISTS min_liquidity_usd DOUBLE PRECISION;
+ALTER TABLE indexer.chains ADD COLUMN IF NOT EXISTS max_price_divergence DOUBLE PRECISION;
+
+-- Price provenance and volume quality on tokens
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS price_confidence DOUBLE PRECISION;
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS price_source TEXT;
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS price_method TEXT;
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS price_source_pools TEXT[];
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS price_hops SMALLINT;
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS price_min_liquidity_usd DOUBLE PRECISION;
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS organic_volume_24h DOUBLE PRECISION;
+ALTER TABLE indexer.tokens ADD COLUMN IF NOT EXISTS adjusted_volume_24h DOUBLE PRECISION;
+
+-- Exact reserves, price provenance, volume quality and fee revenue on pools
+ALTER TABLE indexer.pools ALTER COLUMN reserve0_adjusted TYPE NUMERIC USING reserve0_adjusted::NUMERIC;
+ALTER TABLE indexer.pools ALTER COLUMN reserve1_adjusted TYPE NUMERIC USING reserve1_adjusted::NUMERIC;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS price_confidence DOUBLE PRECISION;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS price_source TEXT;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS price_method TEXT;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS price_source_pools TEXT[];
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS price_hops SMALLINT;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS price_min_liquidity_usd DOUBLE PRECISION;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS organic_volume_24h DOUBLE PRECISION;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS adjusted_volume_24h DOUBLE PRECISION;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS fees_24h DOUBLE PRECISION;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS fees_7d DOUBLE PRECISION;
+ALTER TABLE indexer.pools ADD COLUMN IF NOT EXISTS fee_apr DOUBLE PRECISION;
+
+-- Index for "ORDER BY fee_apr" (pool comparison for LPs)
+CREATE INDEX IF NOT EXISTS idx_pools_fee_apr ON indexer.pools (chain_id, fee_apr DESC NULLS LAST);
+
+-- Native price provenance
+ALTER TABLE indexer.native_token_prices ADD COLUMN IF NOT EXISTS source TEXT;
+ALTER TABLE indexer.native_token_prices ADD COLUMN IF NOT EXISTS source_pools TEXT[];
+ALTER TABLE indexer.native_token_prices ADD COLUMN IF NOT EXISTS confidence DOUBLE PRECISION;
+
+-- Chainlink aggregators used as authoritative token prices
+CREATE TABLE IF NOT EXISTS indexer.price_feeds (
+    chain_id            BIGINT NOT NULL,
+    aggregator_address  TEXT NOT NULL,
+    token_address       TEXT NOT NULL,
+    decimals            SMALLINT NOT NULL DEFAULT 8,
+    denomination        TEXT NOT NULL DEFAULT 'usd',
+    heartbeat_secs      BIGINT NOT NULL DEFAULT 86400,
+    description         TEXT,
+    enabled             BOOLEAN NOT NULL DEFAULT TRUE,
+    PRIMARY KEY (chain_id, aggregator_address)
+);
+
+-- Latest answer per price feed, persisted across worker restarts
+CREATE TABLE IF NOT EXISTS indexer.oracle_prices (
+    chain_id            BIGINT NOT NULL,
+    aggregator_address  TEXT NOT NULL,
+    token_address       TEXT NOT NULL,
+    price               DOUBLE PRECISION,
+    round_id            TEXT,
+    block_number        BIGINT,
+    answered_at         BIGINT,
+    updated_at          TIMESTAMPTZ,
+    PRIMARY KEY (chain_id, aggregator_address)
+);
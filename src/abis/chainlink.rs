@@ -0,0 +1,8 @@
+use alloy::sol;
+
+sol! {
+    // Emitted by every Chainlink aggregator when a round is answered
+    event AnswerUpdated(int256 indexed current, uint256 indexed roundId, uint256 updatedAt);
+    // Emitted by OCR2 aggregators alongside AnswerUpdated
+    event NewTransmission(uint32 indexed aggregatorRoundId, int192 answer, address transmitter, uint32 observationsTimestamp, int192[] observations, bytes observers, int192 juelsPerFeeCoin, bytes32 configDigest, uint40 epochAndRound);
+}
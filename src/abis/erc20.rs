@@ -6,6 +6,8 @@ sol! {
         function name() external view returns (string);
         function symbol() external view returns (string);
         function decimals() external view returns (uint8);
+        function totalSupply() external view returns (uint256);
+        function balanceOf(address account) external view returns (uint256);
     }
 
     event Transfer(address indexed from, address indexed to, uint256 value);
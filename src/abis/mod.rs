@@ -1,12 +1,14 @@
+pub mod chainlink;
 pub mod erc20;
 pub mod multicall;
 pub mod v2;
 pub mod v3;
 pub mod v4;
 
+pub use chainlink::{AnswerUpdated, NewTransmission};
 pub use erc20::IERC20;
 pub use multicall::{Call3, IMulticall3, McResult};
-pub use v2::{Burn as V2Burn, Mint as V2Mint, PairCreated, Swap as V2Swap, Sync};
+pub use v2::{Burn as V2Burn, IPair, Mint as V2Mint, PairCreated, Swap as V2Swap, Sync};
 pub use v3::{
     Burn as V3Burn, Collect, Initialize as V3Initialize, Mint as V3Mint, PoolCreated,
     Swap as V3Swap,
@@ -15,5 +15,6 @@ sol! {
     #[sol(rpc)]
     interface IMulticall3 {
         function aggregate3(Call3[] calldata calls) external payable returns (McResult[] memory returnData);
+        function getEthBalance(address addr) external view returns (uint256 balance);
     }
 }
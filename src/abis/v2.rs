@@ -1,6 +1,13 @@
 use alloy::sol;
 
 sol! {
+    /// Token getters of a V2 pair (V3 pools expose the same)
+    #[sol(rpc)]
+    interface IPair {
+        function token0() external view returns (address);
+        function token1() external view returns (address);
+    }
+
     event PairCreated(address indexed token0, address indexed token1, address pair, uint256);
     event Mint(address indexed sender, uint256 amount0, uint256 amount1);
     event Burn(address indexed sender, uint256 amount0, uint256 amount1, address indexed to);
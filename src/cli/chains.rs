@@ -0,0 +1,444 @@
+//! `runic chains`: manage the chains stored in `indexer.chains`.
+//!
+//! `add` and `update` read a `DatabaseChain` from a YAML or JSON file (the output
+//! of `show` is a valid input). Before a chain is written, its configuration is
+//! checked against the network (see `checks`): RPC and HyperSync answer for the
+//! right chain, Multicall3 is deployed, the native and stable tokens decode, the
+//! stable pool pairs them, and every factory has code.
+//!
+//! `reset` stops the chain's worker, deletes its data from both stores and lets
+//! the worker sync it again, either entirely or from a block.
+
+use std::time::Duration;
+
+use anyhow::{ensure, Context};
+use chrono::Utc;
+use config::{Config, File};
+use log::{info, warn};
+
+use crate::{
+    cli::{checks::validate_chain, ChainsCommand, Runtime},
+    db::models::DatabaseChain,
+};
+
+/// How long a disabled chain's checkpoint must stay untouched before its worker is
+/// considered stopped: the chain refresh (30s), the worker stop timeout (10s) and a
+/// flush of the historical ingestor
+const WORKER_STOP_GRACE: Duration = Duration::from_secs(60);
+
+/// How often `reset` checks whether the worker stopped
+const WORKER_STOP_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Longest `reset` waits for the worker to stop
+const WORKER_STOP_TIMEOUT: Duration = Duration::from_secs(600);
+
+pub(crate) async fn execute(runtime: &Runtime, command: ChainsCommand) -> anyhow::Result<()> {
+    match command {
+        ChainsCommand::List => list(runtime).await,
+        ChainsCommand::Show {
+            chain_id,
+        } => {
+            let chain = get_chain(runtime, chain_id).await?;
+            println!("{}", serde_json::to_string_pretty(&chain)?);
+            Ok(())
+        },
+        ChainsCommand::Add {
+            file,
+            skip_checks,
+        } => {
+            let chain = read_chain(&file)?;
+            ensure!(
+                find_chain(runtime, chain.chain_id).await?.is_none(),
+                "Chain {} already exists, use `runic chains update`",
+                chain.chain_id
+            );
+            write_chain(runtime, chain, skip_checks).await
+        },
+        ChainsCommand::Update {
+            file,
+            skip_checks,
+        } => {
+            let chain = read_chain(&file)?;
+            get_chain(runtime, chain.chain_id).await?;
+            write_chain(runtime, chain, skip_checks).await
+        },
+        ChainsCommand::Enable {
+            chain_id,
+            skip_checks,
+        } => {
+            let chain = get_chain(runtime, chain_id).await?;
+            write_chain(
+                runtime,
+                DatabaseChain {
+                    enabled: true,
+                    ..chain
+                },
+                skip_checks,
+            )
+            .await
+        },
+        ChainsCommand::Disable {
+            chain_id,
+        } => {
+            let chain = get_chain(runtime, chain_id).await?;
+            write_chain(
+                runtime,
+                DatabaseChain {
+                    enabled: false,
+                    ..chain
+                },
+                true,
+            )
+            .await
+        },
+        ChainsCommand::Sync => sync_chains(runtime).await,
+        ChainsCommand::Reset {
+            chain_id,
+            from_block,
+            yes,
+        } => reset(runtime, chain_id, from_block, yes).await,
+    }
+}
+
+/// Prints the chains stored in PostgreSQL.
+async fn list(runtime: &Runtime) -> anyhow::Result<()> {
+    let chains = runtime.db.postgres.get_chains().await?;
+
+    print_row("CHAIN ID", "NAME", "ENABLED", "FACTORIES", "HYPERSYNC");
+    for chain in chains {
+        print_row(
+            &chain.chain_id.to_string(),
+            &chain.name,
+            &chain.enabled.to_string(),
+            &chain.factories.len().to_string(),
+            &chain.hypersync_url,
+        );
+    }
+
+    Ok(())
+}
+
+fn print_row(chain_id: &str, name: &str, enabled: &str, factories: &str, hypersync: &str) {
+    println!("{chain_id:>10}  {name:<20}  {enabled:<8}  {factories:<10}  {hypersync}");
+}
+
+pub(crate) async fn find_chain(
+    runtime: &Runtime,
+    chain_id: u64,
+) -> anyhow::Result<Option<DatabaseChain>> {
+    Ok(runtime
+        .db
+        .postgres
+        .get_chains()
+        .await?
+        .into_iter()
+        .find(|chain| chain.chain_id == chain_id))
+}
+
+pub(crate) async fn get_chain(runtime: &Runtime, chain_id: u64) -> anyhow::Result<DatabaseChain> {
+    find_chain(runtime, chain_id)
+        .await?
+        .with_context(|| format!("Chain {} not found", chain_id))
+}
+
+/// Reads a chain from a YAML or JSON file, with addresses lowercased.
+fn read_chain(path: &str) -> anyhow::Result<DatabaseChain> {
+    let mut chain: DatabaseChain = Config::builder()
+        .add_source(File::with_name(path))
+        .build()
+        .and_then(Config::try_deserialize)
+        .with_context(|| format!("Invalid chain file {}", path))?;
+
+    chain.normalize();
+    Ok(chain)
+}
+
+/// Reconciles `indexer.chains` with the `chains` section of the settings.
+///
+/// Listed chains are created, or updated when they differ from their row: an
+/// unchanged row keeps its `updated_at`, so running workers aren't restarted, and
+/// feeds removed from a definition are disabled by `set_chain`. With
+/// `disable_unlisted`, the other enabled chains are disabled. A chain that fails
+/// its checks is logged and skipped, the others are still synced.
+pub(crate) async fn sync_chains(runtime: &Runtime) -> anyhow::Result<()> {
+    let Some(section) = &runtime.settings.chains else {
+        return Ok(());
+    };
+    let existing = runtime.db.postgres.get_chains().await?;
+
+    let (mut created, mut updated, mut disabled, mut skipped) = (0, 0, 0, 0);
+    for definition in &section.definitions {
+        let mut chain = definition.clone();
+        chain.normalize();
+
+        let current = existing.iter().find(|c| c.chain_id == chain.chain_id);
+        if current.is_some_and(|current| same_definition(current, &chain)) {
+            continue;
+        }
+        if let Err(e) = write_chain(runtime, chain, section.skip_checks).await {
+            warn!(
+                "Chain {}: not synced from the configuration: {:#}",
+                definition.chain_id, e
+            );
+            skipped += 1;
+            continue;
+        }
+        match current {
+            Some(_) => updated += 1,
+            None => created += 1,
+        }
+    }
+
+    if section.disable_unlisted {
+        for chain in existing.into_iter().filter(|chain| {
+            chain.enabled
+                && !section
+                    .definitions
+                    .iter()
+                    .any(|d| d.chain_id == chain.chain_id)
+        }) {
+            write_chain(
+                runtime,
+                DatabaseChain {
+                    enabled: false,
+                    ..chain
+                },
+                true,
+            )
+            .await?;
+            disabled += 1;
+        }
+    }
+
+    info!(
+        "Chains synced from the configuration: {} created, {} updated, {} disabled, {} skipped",
+        created, updated, disabled, skipped
+    );
+    Ok(())
+}
+
+/// Whether writing `definition` would leave `current` unchanged. The price feeds
+/// must match exactly (in any order), since `set_chain` disables the unlisted ones.
+fn same_definition(current: &DatabaseChain, definition: &DatabaseChain) -> bool {
+    let comparable = |chain: &DatabaseChain| {
+        let mut price_feeds = chain.price_feeds.clone();
+        price_feeds.sort_by(|a, b| a.aggregator_address.cmp(&b.aggregator_address));
+        DatabaseChain {
+            updated_at: None,
+            price_feeds,
+            ..chain.clone()
+        }
+    };
+
+    comparable(current) == comparable(definition)
+}
+
+/// Validates `chain` (unless `skip_checks`) and upserts it.
+///
+/// Running workers pick the change up at their next chain refresh.
+async fn write_chain(
+    runtime: &Runtime,
+    mut chain: DatabaseChain,
+    skip_checks: bool,
+) -> anyhow::Result<()> {
+    if skip_checks {
+        warn!("Chain {}: skipping network checks", chain.chain_id);
+    } else {
+        validate_chain(
+            &runtime.db,
+            &chain,
+            &runtime.settings.indexer.hypersync_bearer_token,
+        )
+        .await?;
+    }
+
+    chain.updated_at = Some(Utc::now());
+    runtime.db.postgres.set_chain(&chain).await?;
+
+    info!(
+        "Chain {} ({}) saved, enabled: {}",
+        chain.name, chain.chain_id, chain.enabled
+    );
+    Ok(())
+}
+
+/// Deletes the data of a chain, or the data from `from_block` on, and resumes its sync.
+///
+/// An enabled chain is disabled first and re-enabled once the data is gone, so the
+/// `run` processes stop its worker and then start it again from the new checkpoint.
+async fn reset(
+    runtime: &Runtime,
+    chain_id: u64,
+    from_block: Option<u64>,
+    yes: bool,
+) -> anyhow::Result<()> {
+    let chain = get_chain(runtime, chain_id).await?;
+    let scope = match from_block {
+        Some(block) => format!("from block {}", block),
+        None => "entirely".to_string(),
+    };
+    ensure!(
+        yes,
+        "This deletes the data of chain {} ({}) {} from ClickHouse and PostgreSQL \
+         and rebuilds its aggregates, pass --yes to confirm",
+        chain.chain_id,
+        chain.name,
+        scope
+    );
+
+    let was_enabled = chain.enabled;
+    if was_enabled {
+        write_chain(
+            runtime,
+            DatabaseChain {
+                enabled: false,
+                ..chain.clone()
+            },
+            true,
+        )
+        .await?;
+    }
+    wait_for_worker_stop(runtime, chain_id).await?;
+
+    // Pool changes of the purged blocks, read before ClickHouse drops them
+    let purged = match from_block {
+        Some(block) => Some(runtime.db.clickhouse.purged_pools(chain_id, block).await?),
+        None => None,
+    };
+
+    info!("Chain {}: deleting ClickHouse data {}", chain_id, scope);
+    runtime
+        .db
+        .clickhouse
+        .purge_chain(chain_id, from_block)
+        .await?;
+    info!("Chain {}: deleting PostgreSQL data {}", chain_id, scope);
+    runtime
+        .db
+        .postgres
+        .purge_chain(chain_id, from_block.zip(purged.as_ref()))
+        .await?;
+
+    if was_enabled {
+        let chain = get_chain(runtime, chain_id).await?;
+        write_chain(
+            runtime,
+            DatabaseChain {
+                enabled: true,
+                ..chain
+            },
+            true,
+        )
+        .await?;
+        info!("Chain {}: reset {}, indexing resumes", chain_id, scope);
+    } else {
+        info!(
+            "Chain {}: reset {}, enable it to resume indexing",
+            chain_id, scope
+        );
+    }
+
+    Ok(())
+}
+
+/// Waits until the chain's checkpoint has been idle for `WORKER_STOP_GRACE`.
+async fn wait_for_worker_stop(runtime: &Runtime, chain_id: u64) -> anyhow::Result<()> {
+    let deadline = tokio::time::Instant::now() + WORKER_STOP_TIMEOUT;
+
+    loop {
+        let Some(checkpoint) = runtime.db.postgres.get_sync_checkpoint(chain_id).await? else {
+            return Ok(());
+        };
+        let idle = (Utc::now() - checkpoint.updated_at)
+            .to_std()
+            .unwrap_or_default();
+        if idle >= WORKER_STOP_GRACE {
+            return Ok(());
+        }
+
+        ensure!(
+            tokio::time::Instant::now() < deadline,
+            "Chain {}: the checkpoint is still moving, is a worker running elsewhere?",
+            chain_id
+        );
+        info!(
+            "Chain {}: waiting for the worker to stop (checkpoint updated {}s ago)",
+            chain_id,
+            idle.as_secs()
+        );
+        tokio::time::sleep(WORKER_STOP_POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain() -> DatabaseChain {
+        let mut chain: DatabaseChain = serde_json::from_value(serde_json::json!({
+            "chain_id": 8453,
+            "name": "base",
+            "rpc_url": "https://mainnet.base.org",
+            "hypersync_url": "https://base.hypersync.xyz",
+            "enabled": true,
+            "native_token_address": "0x4200000000000000000000000000000000000006",
+            "native_token_decimals": 18,
+            "native_token_name": "Ether",
+            "native_token_symbol": "ETH",
+            "stable_token_address": "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+            "stable_token_decimals": 6,
+            "stable_pool_address": "0xd0b53D9277642d899DF5C87A3966A349A798F224",
+            "major_tokens": [],
+            "stablecoins": [],
+            "price_feeds": [
+                {
+                    "chain_id": 0,
+                    "aggregator_address": "0x71041dddad3595F9CEd3DcCFBe3D1F4b0a16Bb70",
+                    "token_address": "0x4200000000000000000000000000000000000006",
+                    "decimals": 8
+                },
+                {
+                    "chain_id": 0,
+                    "aggregator_address": "0x591e79239a7d679378eC8c847e5038150364C78F",
+                    "token_address": "0x833589fCD6eDb6E08f4c7C32D4f71b54bdA02913",
+                    "decimals": 8
+                }
+            ],
+        }))
+        .unwrap();
+        chain.normalize();
+        chain
+    }
+
+    #[test]
+    fn test_same_definition_ignores_updated_at_and_feed_order() {
+        let definition = chain();
+        let mut current = chain();
+        current.updated_at = Some(Utc::now());
+        current.price_feeds.reverse();
+
+        assert!(same_definition(&current, &definition));
+    }
+
+    #[test]
+    fn test_same_definition_detects_changes() {
+        let definition = chain();
+
+        let mut renamed = chain();
+        renamed.rpc_url = "https://base.llamarpc.com".to_string();
+        assert!(!same_definition(&renamed, &definition));
+
+        // A feed removed from the definition must be disabled by a write
+        let mut removed = chain();
+        removed.price_feeds.pop();
+        assert!(!same_definition(&chain(), &removed));
+
+        let mut added = chain();
+        added.price_feeds.pop();
+        assert!(!same_definition(&added, &definition));
+
+        let mut stale = chain();
+        stale.price_feeds[0].heartbeat_secs += 1;
+        assert!(!same_definition(&stale, &definition));
+    }
+}
@@ -0,0 +1,239 @@
+//! Network checks of a chain configuration, shared by `runic chains` (before a
+//! chain is written) and `runic verify`.
+
+use std::{future::Future, sync::Arc, time::Duration};
+
+use alloy::{
+    primitives::{Address, B256},
+    providers::{DynProvider, Provider, ProviderBuilder, MULTICALL3_ADDRESS},
+};
+use anyhow::{anyhow, ensure, Context};
+
+use crate::{
+    abis::IPair,
+    db::models::DatabaseChain,
+    worker::{hypersync_client, pricing_strategy::pricing_strategy, token_fetcher::TokenFetcher},
+    Database,
+};
+
+/// Timeout of each check
+const CHECK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Outcome of one check: a short detail when it passed.
+pub(crate) struct CheckResult {
+    pub name: &'static str,
+    pub outcome: anyhow::Result<String>,
+}
+
+async fn run_check(
+    name: &'static str,
+    check: impl Future<Output = anyhow::Result<String>>,
+) -> CheckResult {
+    let outcome = match tokio::time::timeout(CHECK_TIMEOUT, check).await {
+        Ok(outcome) => outcome,
+        Err(_) => Err(anyhow!("timed out after {}s", CHECK_TIMEOUT.as_secs())),
+    };
+    CheckResult {
+        name,
+        outcome,
+    }
+}
+
+/// Runs every check of `chain`, including the failing ones' successors.
+pub(crate) async fn check_chain(
+    db: &Arc<Database>,
+    chain: &DatabaseChain,
+    hypersync_token: &str,
+) -> Vec<CheckResult> {
+    let mut results = vec![
+        run_check("config", async {
+            ensure!(!chain.name.is_empty(), "name is empty");
+            pricing_strategy(&chain.pricing_strategy, chain.pricing_thresholds)?;
+            Ok(chain.pricing_strategy.clone())
+        })
+        .await,
+    ];
+
+    let provider = match chain.rpc_url.parse() {
+        Ok(url) => DynProvider::new(ProviderBuilder::new().connect_http(url)),
+        Err(e) => {
+            results.push(CheckResult {
+                name: "rpc",
+                outcome: Err(anyhow!("invalid rpc_url: {}", e)),
+            });
+            return results;
+        },
+    };
+
+    results.push(
+        run_check("rpc", async {
+            let rpc_chain_id = provider
+                .get_chain_id()
+                .await
+                .with_context(|| format!("{} is unreachable", chain.rpc_url))?;
+            ensure!(
+                rpc_chain_id == chain.chain_id,
+                "rpc_url serves chain {} instead of {}",
+                rpc_chain_id,
+                chain.chain_id
+            );
+            Ok(format!("chain {}", rpc_chain_id))
+        })
+        .await,
+    );
+
+    results.push(
+        run_check("multicall3", async {
+            let code = provider.get_code_at(MULTICALL3_ADDRESS).await?;
+            ensure!(!code.is_empty(), "no contract at {}", MULTICALL3_ADDRESS);
+            Ok(MULTICALL3_ADDRESS.to_string())
+        })
+        .await,
+    );
+
+    results.push(
+        run_check("hypersync", async {
+            let client = hypersync_client(&chain.hypersync_url, hypersync_token.to_string())?;
+            let hypersync_chain_id = client
+                .get_chain_id()
+                .await
+                .with_context(|| format!("{} is unreachable", chain.hypersync_url))?;
+            ensure!(
+                hypersync_chain_id == chain.chain_id,
+                "hypersync_url serves chain {} instead of {}",
+                hypersync_chain_id,
+                chain.chain_id
+            );
+            let height = client.get_height().await?;
+            ensure!(height > 0, "height is 0");
+            Ok(format!("height {}", height))
+        })
+        .await,
+    );
+
+    results.push(run_check("tokens", check_tokens(db, chain)).await);
+    results.push(run_check("stable pool", check_stable_pool(db, &provider, chain)).await);
+
+    results.push(
+        run_check("factories", async {
+            for factory in &chain.factories {
+                let address: Address = factory
+                    .parse()
+                    .with_context(|| format!("invalid factory address {}", factory))?;
+                let code = provider.get_code_at(address).await?;
+                ensure!(!code.is_empty(), "factory {} has no code", factory);
+            }
+            Ok(format!("{} with code", chain.factories.len()))
+        })
+        .await,
+    );
+
+    results
+}
+
+/// Fails with every failed check of `chain`.
+pub(crate) async fn validate_chain(
+    db: &Arc<Database>,
+    chain: &DatabaseChain,
+    hypersync_token: &str,
+) -> anyhow::Result<()> {
+    let failures: Vec<String> = check_chain(db, chain, hypersync_token)
+        .await
+        .into_iter()
+        .filter_map(|check| {
+            check
+                .outcome
+                .err()
+                .map(|e| format!("{}: {:#}", check.name, e))
+        })
+        .collect();
+
+    ensure!(
+        failures.is_empty(),
+        "Chain {} failed its checks: {}",
+        chain.chain_id,
+        failures.join(", ")
+    );
+    Ok(())
+}
+
+/// The wrapped native and stable tokens must decode as ERC-20s, and the native
+/// decimals match the configuration.
+async fn check_tokens(db: &Arc<Database>, chain: &DatabaseChain) -> anyhow::Result<String> {
+    let fetcher = TokenFetcher::new(chain.rpc_url.clone(), chain.chain_id as i64, db.clone());
+    let addresses =
+        [chain.native_token_address.to_lowercase(), chain.stable_token_address.to_lowercase()];
+    let tokens = fetcher.fetch_metadata_batch(&addresses).await;
+
+    let [Some(native), Some(stable)] = [&tokens[0], &tokens[1]] else {
+        let failed: Vec<&str> = addresses
+            .iter()
+            .zip(&tokens)
+            .filter(|(_, token)| token.is_none())
+            .map(|(address, _)| address.as_str())
+            .collect();
+        return Err(anyhow!("{} did not decode", failed.join(" and ")));
+    };
+    ensure!(
+        native.decimals == chain.native_token_decimals,
+        "{} has {} decimals, native_token_decimals is {}",
+        native.symbol,
+        native.decimals,
+        chain.native_token_decimals
+    );
+
+    Ok(format!(
+        "{} ({}), {} ({})",
+        native.symbol, native.decimals, stable.symbol, stable.decimals
+    ))
+}
+
+/// The stable pool must pair the native and stable tokens.
+///
+/// V4 pools are identified by a 32-byte id and have no contract to query: their
+/// tokens are read from the pools table, so the pool must be indexed already.
+async fn check_stable_pool(
+    db: &Arc<Database>,
+    provider: &DynProvider,
+    chain: &DatabaseChain,
+) -> anyhow::Result<String> {
+    let (token0, token1) = if chain.stable_pool_address.parse::<B256>().is_ok() {
+        let pool_id = chain.stable_pool_address.to_lowercase();
+        let pools = db
+            .postgres
+            .get_pools(chain.chain_id as i64, std::slice::from_ref(&pool_id))
+            .await?;
+        let pool = pools.first().with_context(|| {
+            format!(
+                "V4 pool {} is not indexed, its tokens can't be checked (use --skip-checks)",
+                chain.stable_pool_address
+            )
+        })?;
+        (pool.token0.clone(), pool.token1.clone())
+    } else {
+        let address = chain
+            .stable_pool_address
+            .parse::<Address>()
+            .with_context(|| format!("{} is not an address", chain.stable_pool_address))?;
+        let pool = IPair::new(address, provider);
+        let (token0_call, token1_call) = (pool.token0(), pool.token1());
+        let (token0, token1) = tokio::try_join!(token0_call.call(), token1_call.call())
+            .with_context(|| format!("{} is not a pool", chain.stable_pool_address))?;
+        (token0.to_string(), token1.to_string())
+    };
+
+    let mut tokens = [token0.to_lowercase(), token1.to_lowercase()];
+    let mut expected =
+        [chain.native_token_address.to_lowercase(), chain.stable_token_address.to_lowercase()];
+    tokens.sort();
+    expected.sort();
+    ensure!(
+        tokens == expected,
+        "{} pairs {} and {}, not the native and stable tokens",
+        chain.stable_pool_address,
+        tokens[0],
+        tokens[1]
+    );
+
+    Ok(chain.stable_pool_address.clone())
+}
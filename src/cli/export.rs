@@ -0,0 +1,250 @@
+//! `runic export`: bulk export of ClickHouse rows for offline analysis.
+//!
+//! ClickHouse encodes the files itself (`Parquet`, `CSVWithNames`), and each
+//! partition is a separate query streamed to disk, so memory stays flat whatever
+//! the range.
+
+use anyhow::{ensure, Context};
+use chrono::{DateTime, NaiveDate, Utc};
+use log::info;
+
+use crate::cli::{ExportArgs, ExportFormat, ExportPartition, ExportTable, Runtime};
+
+impl ExportTable {
+    fn name(&self) -> &'static str {
+        match self {
+            ExportTable::Events => "events",
+            ExportTable::Candles1m => "candles_1m",
+            ExportTable::Candles5m => "candles_5m",
+            ExportTable::Candles15m => "candles_15m",
+            ExportTable::Candles1h => "candles_1h",
+            ExportTable::Candles4h => "candles_4h",
+            ExportTable::Candles1d => "candles_1d",
+            ExportTable::PoolSnapshots => "pool_snapshots",
+            ExportTable::SupplyEvents => "supply_events",
+        }
+    }
+
+    fn time_column(&self) -> &'static str {
+        match self {
+            ExportTable::Events | ExportTable::SupplyEvents => "timestamp",
+            _ => "time",
+        }
+    }
+
+    fn has_block_number(&self) -> bool {
+        matches!(self, ExportTable::Events | ExportTable::SupplyEvents)
+    }
+
+    /// Sorting key of the exported rows
+    fn order_by(&self) -> &'static str {
+        match self {
+            ExportTable::Events | ExportTable::SupplyEvents => "block_number, log_index",
+            _ => "time, pool_address",
+        }
+    }
+
+    /// Start of the candle containing `timestamp`, as computed by the candle views.
+    /// `None` for the tables that aren't candles.
+    fn candle_time(&self) -> Option<&'static str> {
+        match self {
+            ExportTable::Candles1m => Some("toStartOfMinute(timestamp)"),
+            ExportTable::Candles5m => Some("toStartOfFiveMinutes(timestamp)"),
+            ExportTable::Candles15m => Some("toStartOfFifteenMinutes(timestamp)"),
+            ExportTable::Candles1h => Some("toStartOfHour(timestamp)"),
+            ExportTable::Candles4h => Some("toStartOfInterval(timestamp, INTERVAL 4 HOUR)"),
+            ExportTable::Candles1d => Some("toStartOfDay(timestamp)"),
+            ExportTable::Events | ExportTable::PoolSnapshots | ExportTable::SupplyEvents => None,
+        }
+    }
+}
+
+/// Query of the `table` rows matching `filter`.
+///
+/// The candle tables are AggregatingMergeTree tables of plain columns: rows of
+/// unmerged parts are partial candles, and merging them keeps one of them instead
+/// of combining them. Candles are therefore rebuilt from the priced swaps of
+/// `events`, the way `mv_candles_1m` aggregates them, one row per candle.
+fn export_query(table: ExportTable, filter: &str) -> String {
+    let Some(candle_time) = table.candle_time() else {
+        return format!(
+            "SELECT * FROM indexer.{} WHERE {} ORDER BY {}",
+            table.name(),
+            filter,
+            table.order_by()
+        );
+    };
+
+    format!(
+        "SELECT * FROM (\
+         SELECT chain_id, pool_address, {} AS time, argMin(price_usd, timestamp) AS open, \
+         max(price_usd) AS high, min(price_usd) AS low, argMax(price_usd, timestamp) AS close, \
+         sum(volume_usd) AS volume_usd, sum(fees_usd) AS fees_usd, count() AS tx_count \
+         FROM indexer.events WHERE event_type = 'swap' AND price_usd > 0 \
+         GROUP BY chain_id, pool_address, time\
+         ) WHERE {} ORDER BY {}",
+        candle_time,
+        filter,
+        table.order_by()
+    )
+}
+
+impl ExportFormat {
+    fn clickhouse_format(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "Parquet",
+            ExportFormat::Csv => "CSVWithNames",
+        }
+    }
+
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Parses `YYYY-MM-DD` (midnight UTC) or an RFC 3339 timestamp.
+pub(crate) fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc());
+    }
+    DateTime::parse_from_rfc3339(value)
+        .map(|time| time.with_timezone(&Utc))
+        .map_err(|_| format!("invalid time {}, expected YYYY-MM-DD or RFC 3339", value))
+}
+
+/// SQL condition selecting the chain's rows within the requested ranges.
+fn row_filter(args: &ExportArgs) -> anyhow::Result<String> {
+    let table = args.table;
+    ensure!(
+        table.has_block_number() || (args.from_block.is_none() && args.to_block.is_none()),
+        "{} has no block_number, use --from-time/--to-time",
+        table.name()
+    );
+
+    let time = table.time_column();
+    let mut conditions = vec![format!("chain_id = {}", args.chain_id)];
+    if let Some(from) = args.from_time {
+        conditions.push(format!("{} >= toDateTime({})", time, from.timestamp()));
+    }
+    if let Some(to) = args.to_time {
+        conditions.push(format!("{} < toDateTime({})", time, to.timestamp()));
+    }
+    if let Some(from) = args.from_block {
+        conditions.push(format!("block_number >= {}", from));
+    }
+    if let Some(to) = args.to_block {
+        conditions.push(format!("block_number < {}", to));
+    }
+    Ok(conditions.join(" AND "))
+}
+
+pub(crate) async fn export(runtime: &Runtime, args: ExportArgs) -> anyhow::Result<()> {
+    let filter = row_filter(&args)?;
+    let table = args.table.name();
+    let time = args.table.time_column();
+
+    let dir = args
+        .output
+        .join(table)
+        .join(format!("chain_id={}", args.chain_id));
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .with_context(|| format!("Failed to create {}", dir.display()))?;
+
+    // Partition key expression and the file name of each of its values
+    let partitions: Vec<(String, String)> = match args.partition {
+        ExportPartition::None => vec![(filter.clone(), "all".to_string())],
+        ExportPartition::Day | ExportPartition::Month => {
+            let expr = match args.partition {
+                ExportPartition::Month => format!("toStartOfMonth({})", time),
+                _ => format!("toDate({})", time),
+            };
+            runtime
+                .db
+                .clickhouse
+                .export_partitions(table, &expr, &filter)
+                .await?
+                .into_iter()
+                .map(|date| {
+                    let label = match args.partition {
+                        ExportPartition::Month => date[..7].to_string(),
+                        _ => date.clone(),
+                    };
+                    (
+                        format!("{} AND {} = toDate('{}')", filter, expr, date),
+                        label,
+                    )
+                })
+                .collect()
+        },
+    };
+
+    let mut total = 0;
+    for (partition_filter, label) in &partitions {
+        let path = dir.join(format!("{}.{}", label, args.format.extension()));
+        let bytes = runtime
+            .db
+            .clickhouse
+            .export_to_file(
+                &export_query(args.table, partition_filter),
+                args.format.clickhouse_format(),
+                &path,
+            )
+            .await?;
+        total += bytes;
+        info!("Exported {} ({} bytes)", path.display(), bytes);
+    }
+
+    info!(
+        "Chain {}: exported {} to {} file(s) in {} ({} bytes)",
+        args.chain_id,
+        table,
+        partitions.len(),
+        dir.display(),
+        total
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("2024-05-01").unwrap().timestamp(), 1_714_521_600);
+        assert_eq!(
+            parse_time("2024-05-01T12:00:00+02:00").unwrap().timestamp(),
+            1_714_557_600
+        );
+        assert!(parse_time("01/05/2024").is_err());
+    }
+
+    #[test]
+    fn test_export_query_reads_raw_rows() {
+        assert_eq!(
+            export_query(ExportTable::Events, "chain_id = 1"),
+            "SELECT * FROM indexer.events WHERE chain_id = 1 ORDER BY block_number, log_index"
+        );
+    }
+
+    #[test]
+    fn test_export_query_rebuilds_candles_from_events() {
+        let query = export_query(ExportTable::Candles4h, "chain_id = 1");
+
+        assert!(query.starts_with(
+            "SELECT * FROM (SELECT chain_id, pool_address, \
+             toStartOfInterval(timestamp, INTERVAL 4 HOUR) AS time, \
+             argMin(price_usd, timestamp) AS open"
+        ));
+        assert!(query.contains(
+            "FROM indexer.events WHERE event_type = 'swap' AND price_usd > 0 \
+             GROUP BY chain_id, pool_address, time)"
+        ));
+        assert!(query.ends_with(") WHERE chain_id = 1 ORDER BY time, pool_address"));
+        assert!(!query.contains("candles_4h"));
+    }
+}
@@ -0,0 +1,129 @@
+//! One-off operations on a single chain: backfill, re-index and audit.
+
+use std::time::Duration;
+
+use anyhow::Context;
+use log::{info, warn};
+
+use crate::{
+    cli::{chains, run::wait_for_shutdown_signal, RangeArgs, Runtime},
+    ChainWorker,
+};
+
+/// How often `backfill` compares the checkpoint with the chain height
+const BACKFILL_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+async fn chain_worker(runtime: &Runtime, chain_id: u64) -> anyhow::Result<ChainWorker> {
+    let chain = chains::get_chain(runtime, chain_id).await?;
+
+    ChainWorker::new(
+        &chain,
+        runtime.historical_tx.clone(),
+        runtime.live_tx.clone(),
+        runtime.settings.indexer.hypersync_bearer_token.clone(),
+        runtime.db.clone(),
+        runtime.settings.indexer.tip_poll_interval_milliseconds,
+    )
+    .await
+}
+
+/// Runs the chain's worker from its checkpoint until it reaches the chain height.
+///
+/// This is the regular sync (it moves the checkpoint), without the cron jobs, so
+/// don't run it for a chain a `run` process is already indexing.
+pub(crate) async fn backfill(runtime: &Runtime, chain_id: u64) -> anyhow::Result<()> {
+    let worker = chain_worker(runtime, chain_id).await?;
+    let target = worker.chain_height().await?;
+    info!("Chain {}: backfilling up to block {}", chain_id, target);
+
+    let worker_token = runtime.cancellation_token.child_token();
+    let mut handle = {
+        let worker_token = worker_token.clone();
+        tokio::spawn(async move { worker.run(worker_token).await })
+    };
+
+    let shutdown = wait_for_shutdown_signal();
+    tokio::pin!(shutdown);
+
+    loop {
+        tokio::select! {
+            result = &mut handle => {
+                return result.context("Worker task panicked")?;
+            },
+            _ = &mut shutdown => {
+                warn!("Chain {}: backfill interrupted", chain_id);
+                break;
+            },
+            _ = tokio::time::sleep(BACKFILL_POLL_INTERVAL) => {
+                let checkpoint = runtime.db.postgres.get_sync_checkpoint(chain_id).await?;
+                let synced = checkpoint.map(|c| c.last_indexed_block).unwrap_or(0);
+                if synced >= target {
+                    info!("Chain {}: backfilled to block {}", chain_id, synced);
+                    break;
+                }
+            },
+        }
+    }
+
+    // The worker stops once its current stream ends
+    worker_token.cancel();
+    handle.await.context("Worker task panicked")?
+}
+
+pub(crate) async fn reindex(runtime: &Runtime, range: RangeArgs) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        range.from_block < range.to_block,
+        "from_block must be below to_block"
+    );
+
+    chain_worker(runtime, range.chain_id)
+        .await?
+        .reindex_range(range.from_block, range.to_block)
+        .await?;
+
+    Ok(())
+}
+
+pub(crate) async fn audit(
+    runtime: &Runtime,
+    range: RangeArgs,
+    bucket_size: u64,
+) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        range.from_block < range.to_block,
+        "from_block must be below to_block"
+    );
+
+    let report = chain_worker(runtime, range.chain_id)
+        .await?
+        .audit_range(range.from_block, range.to_block, bucket_size)
+        .await?;
+
+    let incomplete: Vec<_> = report.iter().filter(|r| !r.is_complete()).collect();
+    for r in &incomplete {
+        println!(
+            "{}..{}  events {}/{}  supply events {}/{}",
+            r.from_block,
+            r.to_block,
+            r.indexed_events,
+            r.expected_events,
+            r.indexed_supply_events,
+            r.expected_supply_events
+        );
+    }
+    println!(
+        "Chain {}: {} of {} buckets incomplete in blocks {}..{}",
+        range.chain_id,
+        incomplete.len(),
+        report.len(),
+        range.from_block,
+        range.to_block
+    );
+
+    anyhow::ensure!(
+        incomplete.is_empty(),
+        "{} incomplete buckets, re-index them with `runic reindex`",
+        incomplete.len()
+    );
+    Ok(())
+}
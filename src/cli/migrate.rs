@@ -0,0 +1,36 @@
+use crate::{cli::Runtime, db::migrations::MigrationStatus};
+
+/// Applies the pending migrations, or with `status` only lists them.
+pub(crate) async fn migrate(runtime: &Runtime, status: bool) -> anyhow::Result<()> {
+    if !status {
+        return runtime.db.migrate().await;
+    }
+
+    let clickhouse = runtime.db.clickhouse.migration_status().await?;
+    let postgres = runtime.db.postgres.migration_status().await?;
+
+    print_row(["STORE", "VERSION", "NAME", "STATE", "APPLIED AT"]);
+    for (store, statuses) in [("clickhouse", clickhouse), ("postgres", postgres)] {
+        for MigrationStatus {
+            version,
+            name,
+            state,
+            applied_at,
+        } in statuses
+        {
+            print_row([
+                store,
+                &format!("{:04}", version),
+                &name,
+                state.as_str(),
+                &applied_at.map_or("-".to_string(), |at| at.to_rfc3339()),
+            ]);
+        }
+    }
+
+    Ok(())
+}
+
+fn print_row([store, version, name, state, applied_at]: [&str; 5]) {
+    println!("{store:<10}  {version:>7}  {name:<30}  {state:<8}  {applied_at}");
+}
@@ -0,0 +1,317 @@
+//! Command-line interface of the `runic` binary.
+//!
+//! Every command loads the same configuration file (`--config`). `run` (the default
+//! when no command is given) starts the indexer, optionally restricted to some
+//! components so the binary can be deployed in several roles, e.g. one process for
+//! the chain workers and one for the cron jobs.
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use clap::{Args, Parser, Subcommand, ValueEnum};
+use log::info;
+use tokio::{sync::mpsc, task::JoinHandle};
+use tokio_util::sync::CancellationToken;
+
+use crate::{cron::CronJob, db::IngestMessage, Database, Settings};
+
+mod chains;
+mod checks;
+mod export;
+mod maintenance;
+mod migrate;
+mod run;
+mod status;
+mod verify;
+
+#[derive(Debug, Parser)]
+#[command(name = "runic", version, about = "Multi-chain DEX indexer")]
+pub struct Cli {
+    /// Configuration file (the extension may be omitted)
+    #[arg(long, short, global = true, default_value = "config")]
+    pub config: String,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Run the indexer (default)
+    Run(RunArgs),
+    /// Apply the pending ClickHouse and PostgreSQL migrations, then exit
+    Migrate {
+        /// List applied and pending migrations without applying them
+        #[arg(long)]
+        status: bool,
+    },
+    /// Manage indexed chains
+    #[command(subcommand)]
+    Chains(ChainsCommand),
+    /// Sync one chain up to the chain tip, then exit
+    Backfill {
+        chain_id: u64,
+    },
+    /// Re-index a block range of one chain, inserting only missing rows
+    Reindex(RangeArgs),
+    /// Compare indexed rows with the source logs of a block range, failing when a
+    /// bucket is incomplete
+    Audit {
+        #[command(flatten)]
+        range: RangeArgs,
+        /// Blocks per reported bucket
+        #[arg(long, default_value_t = 10_000, value_parser = clap::value_parser!(u64).range(1..))]
+        bucket_size: u64,
+    },
+    /// Write a chain's rows of a ClickHouse table to Parquet or CSV files
+    Export(ExportArgs),
+    /// Show the sync progress of every chain
+    Status,
+    /// Check the stores and every enabled chain, failing on any problem
+    Verify,
+}
+
+#[derive(Debug, Default, Args)]
+pub struct RunArgs {
+    /// Components to run (default: all)
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub components: Vec<Component>,
+    /// Do not apply the schemas at startup
+    #[arg(long)]
+    pub skip_migrations: bool,
+    /// Cron jobs to run (default: all enabled in the configuration)
+    #[arg(long, value_delimiter = ',', value_parser = parse_cron_job)]
+    pub cron_jobs: Vec<CronJob>,
+}
+
+fn parse_cron_job(name: &str) -> Result<CronJob, String> {
+    CronJob::from_name(name).ok_or_else(|| {
+        let names: Vec<&str> = CronJob::ALL.iter().map(CronJob::name).collect();
+        format!("unknown job {}, expected one of {}", name, names.join(", "))
+    })
+}
+
+/// Part of the indexer started by `run`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Component {
+    /// Chain workers (one per enabled chain)
+    Workers,
+    /// Background jobs (stats, snapshots, reconciliation)
+    Cron,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ChainsCommand {
+    /// List the configured chains
+    List,
+    /// Print a chain's configuration as JSON
+    Show {
+        chain_id: u64,
+    },
+    /// Add a chain from a YAML or JSON file
+    Add {
+        file: String,
+        /// Don't check the RPC, HyperSync, stable pool and factories
+        #[arg(long)]
+        skip_checks: bool,
+    },
+    /// Replace a chain's configuration from a YAML or JSON file
+    Update {
+        file: String,
+        /// Don't check the RPC, HyperSync, stable pool and factories
+        #[arg(long)]
+        skip_checks: bool,
+    },
+    /// Start indexing a chain
+    Enable {
+        chain_id: u64,
+        /// Don't check the RPC, HyperSync, stable pool and factories
+        #[arg(long)]
+        skip_checks: bool,
+    },
+    /// Stop indexing a chain
+    Disable {
+        chain_id: u64,
+    },
+    /// Create, update (and optionally disable) chains from the `chains` config section
+    Sync,
+    /// Delete a chain's indexed data and sync it again
+    Reset {
+        chain_id: u64,
+        /// Only delete data from this block on (default: everything)
+        #[arg(long)]
+        from_block: Option<u64>,
+        /// Confirm the deletion
+        #[arg(long)]
+        yes: bool,
+    },
+}
+
+#[derive(Debug, Args)]
+pub struct ExportArgs {
+    #[arg(value_enum)]
+    pub table: ExportTable,
+    pub chain_id: u64,
+    /// Start time, inclusive (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = export::parse_time)]
+    pub from_time: Option<DateTime<Utc>>,
+    /// End time, exclusive (YYYY-MM-DD or RFC 3339)
+    #[arg(long, value_parser = export::parse_time)]
+    pub to_time: Option<DateTime<Utc>>,
+    /// First block, inclusive (events and supply_events only)
+    #[arg(long)]
+    pub from_block: Option<u64>,
+    /// Last block, exclusive (events and supply_events only)
+    #[arg(long)]
+    pub to_block: Option<u64>,
+    #[arg(long, value_enum, default_value_t = ExportFormat::Parquet)]
+    pub format: ExportFormat,
+    /// One file per day, per month, or a single file
+    #[arg(long, value_enum, default_value_t = ExportPartition::Day)]
+    pub partition: ExportPartition,
+    /// Directory receiving `{table}/chain_id={chain_id}/{partition}.{format}`
+    #[arg(long, short, default_value = "export")]
+    pub output: PathBuf,
+}
+
+/// ClickHouse table exported by `export`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportTable {
+    Events,
+    #[value(name = "candles_1m")]
+    Candles1m,
+    #[value(name = "candles_5m")]
+    Candles5m,
+    #[value(name = "candles_15m")]
+    Candles15m,
+    #[value(name = "candles_1h")]
+    Candles1h,
+    #[value(name = "candles_4h")]
+    Candles4h,
+    #[value(name = "candles_1d")]
+    Candles1d,
+    #[value(name = "pool_snapshots")]
+    PoolSnapshots,
+    #[value(name = "supply_events")]
+    SupplyEvents,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportFormat {
+    Parquet,
+    /// CSV with a header row
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ExportPartition {
+    Day,
+    Month,
+    None,
+}
+
+#[derive(Debug, Args)]
+pub struct RangeArgs {
+    pub chain_id: u64,
+    pub from_block: u64,
+    /// Exclusive
+    pub to_block: u64,
+}
+
+impl Cli {
+    /// Runs the selected command.
+    pub async fn execute(self) -> anyhow::Result<()> {
+        let settings = Arc::new(
+            Settings::from_file(&self.config)
+                .with_context(|| format!("Failed to load config file {}", self.config))?,
+        );
+        let command = self
+            .command
+            .unwrap_or_else(|| Command::Run(RunArgs::default()));
+
+        // Reports the stores it can't reach, so it doesn't need them all connected
+        if matches!(command, Command::Verify) {
+            return verify::verify(&settings).await;
+        }
+
+        let runtime = Runtime::new(settings).await?;
+
+        let result = match command {
+            Command::Run(args) => run::run(&runtime, args).await,
+            Command::Migrate {
+                status,
+            } => migrate::migrate(&runtime, status).await,
+            Command::Chains(command) => chains::execute(&runtime, command).await,
+            Command::Backfill {
+                chain_id,
+            } => maintenance::backfill(&runtime, chain_id).await,
+            Command::Reindex(range) => maintenance::reindex(&runtime, range).await,
+            Command::Audit {
+                range,
+                bucket_size,
+            } => maintenance::audit(&runtime, range, bucket_size).await,
+            Command::Export(args) => export::export(&runtime, args).await,
+            Command::Status => status::status(&runtime).await,
+            Command::Verify => unreachable!("verify runs without a runtime"),
+        };
+
+        runtime.shutdown().await;
+        result
+    }
+}
+
+/// Settings, database clients and ingestors shared by the commands.
+pub(crate) struct Runtime {
+    pub settings: Arc<Settings>,
+    pub db: Arc<Database>,
+    pub cancellation_token: CancellationToken,
+    // Dual channel architecture for ingestion
+    // Historical: high-throughput for chain sync (large batches, long wait)
+    // Live: low-latency for real-time data (small batches, fast flush)
+    pub historical_tx: mpsc::Sender<IngestMessage>,
+    pub live_tx: mpsc::Sender<IngestMessage>,
+    historical_handle: JoinHandle<()>,
+    live_handle: JoinHandle<()>,
+}
+
+impl Runtime {
+    async fn new(settings: Arc<Settings>) -> anyhow::Result<Self> {
+        let (historical_tx, historical_rx) = mpsc::channel::<IngestMessage>(128);
+        let (live_tx, live_rx) = mpsc::channel::<IngestMessage>(128);
+
+        let cancellation_token = CancellationToken::new();
+
+        let (db, historical_handle, live_handle) = Database::new(
+            settings.clone(),
+            historical_rx,
+            live_rx,
+            cancellation_token.clone(),
+        )
+        .await
+        .context("Failed to initialize database connection")?;
+
+        Ok(Self {
+            settings,
+            db: Arc::new(db),
+            cancellation_token,
+            historical_tx,
+            live_tx,
+            historical_handle,
+            live_handle,
+        })
+    }
+
+    /// Flushes and stops both ingestors.
+    async fn shutdown(self) {
+        info!("Shutting down batch ingestors...");
+        let _ = self.historical_tx.send(IngestMessage::Shutdown).await;
+        let _ = self.live_tx.send(IngestMessage::Shutdown).await;
+
+        // Gracefully await both ingestors
+        let _ = self.historical_handle.await;
+        let _ = self.live_handle.await;
+
+        info!("All ingestors stopped");
+    }
+}
@@ -0,0 +1,116 @@
+use anyhow::Context;
+use log::{error, info};
+
+use crate::{
+    cli::{chains, Component, RunArgs, Runtime},
+    ChainManager, CronScheduler,
+};
+
+/// Runs the selected components until Ctrl+C or SIGTERM.
+pub(crate) async fn run(runtime: &Runtime, args: RunArgs) -> anyhow::Result<()> {
+    let enabled =
+        |component: Component| args.components.is_empty() || args.components.contains(&component);
+
+    if !args.skip_migrations {
+        runtime.db.migrate().await?;
+    }
+
+    let mut handles = Vec::new();
+
+    if enabled(Component::Workers) {
+        // Apply the chains of the configuration before the manager loads them
+        chains::sync_chains(runtime).await?;
+
+        // Create chain manager - it will load chains from database and spawn workers
+        // Workers route to historical_tx or live_tx based on tip detection
+        let chain_manager = ChainManager::new(
+            runtime.db.clone(),
+            runtime.settings.indexer.hypersync_bearer_token.clone(),
+            runtime.settings.indexer.tip_poll_interval_milliseconds,
+            runtime.historical_tx.clone(),
+            runtime.live_tx.clone(),
+        );
+
+        let chain_manager_token = runtime.cancellation_token.child_token();
+        handles.push((
+            "chain manager",
+            tokio::spawn(async move {
+                if let Err(e) = chain_manager.run(chain_manager_token).await {
+                    error!("Chain manager failed: {:#}", e);
+                }
+            }),
+        ));
+
+        info!("Chain manager started - chains will be loaded from database");
+    }
+
+    if enabled(Component::Cron) {
+        // Create and spawn cron scheduler for background jobs
+        // (24h stats updates, price changes, MV refresh, snapshots)
+        let mut cron_scheduler = CronScheduler::new(
+            runtime.db.clone(),
+            runtime.live_tx.clone(),
+            runtime.settings.cron.clone(),
+        );
+        if !args.cron_jobs.is_empty() {
+            cron_scheduler = cron_scheduler.with_jobs(args.cron_jobs.clone());
+        }
+
+        let cron_token = runtime.cancellation_token.child_token();
+        handles.push((
+            "cron scheduler",
+            tokio::spawn(async move {
+                if let Err(e) = cron_scheduler.run(cron_token).await {
+                    error!("Cron scheduler failed: {:#}", e);
+                }
+            }),
+        ));
+
+        info!("Cron scheduler started - background jobs will run periodically");
+    }
+
+    // Set up graceful shutdown signal handler
+    info!("Indexer running. Press Ctrl+C to stop.");
+    wait_for_shutdown_signal().await?;
+
+    // Cancel all running tasks
+    info!("Finishing all tasks...");
+
+    runtime.cancellation_token.cancel();
+
+    for (name, handle) in handles {
+        info!("Waiting for {} to stop...", name);
+        let _ = handle.await;
+    }
+
+    Ok(())
+}
+
+/// Waits for Ctrl+C (or SIGTERM on unix).
+pub(crate) async fn wait_for_shutdown_signal() -> anyhow::Result<()> {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        let mut sigterm_stream =
+            signal(SignalKind::terminate()).context("Failed to install SIGTERM handler")?;
+
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                info!("Received shutdown signal (Ctrl+C), exiting gracefully...");
+            },
+            _ = sigterm_stream.recv() => {
+                info!("Received SIGTERM, exiting gracefully...");
+            },
+        };
+    }
+
+    #[cfg(not(unix))]
+    {
+        tokio::signal::ctrl_c()
+            .await
+            .context("Failed to listen for Ctrl+C")?;
+        info!("Received shutdown signal (Ctrl+C), exiting gracefully...");
+    }
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use chrono::Utc;
+
+use crate::{cli::Runtime, worker::hypersync_client};
+
+/// Prints the checkpoint, HyperSync height and lag of every chain.
+pub(crate) async fn status(runtime: &Runtime) -> anyhow::Result<()> {
+    let chains = runtime.db.postgres.get_chains().await?;
+
+    print_row(["CHAIN ID", "NAME", "ENABLED", "CHECKPOINT", "HEIGHT", "BEHIND", "UPDATED"]);
+    for chain in chains {
+        let checkpoint = runtime
+            .db
+            .postgres
+            .get_sync_checkpoint(chain.chain_id)
+            .await?;
+
+        // The height is informational: an unreachable HyperSync shows as "-"
+        let height = match hypersync_client(
+            &chain.hypersync_url,
+            runtime.settings.indexer.hypersync_bearer_token.clone(),
+        ) {
+            Ok(client) => client.get_height().await.ok(),
+            Err(_) => None,
+        };
+
+        let synced = checkpoint.as_ref().map(|c| c.last_indexed_block);
+        let behind = match (height, synced) {
+            (Some(height), Some(synced)) => height.saturating_sub(synced).to_string(),
+            _ => "-".to_string(),
+        };
+        let updated = checkpoint
+            .as_ref()
+            .map(|c| format!("{}s ago", (Utc::now() - c.updated_at).num_seconds()))
+            .unwrap_or_else(|| "never".to_string());
+
+        print_row([
+            &chain.chain_id.to_string(),
+            &chain.name,
+            &chain.enabled.to_string(),
+            &synced.map_or("-".to_string(), |b| b.to_string()),
+            &height.map_or("-".to_string(), |h| h.to_string()),
+            &behind,
+            &updated,
+        ]);
+    }
+
+    Ok(())
+}
+
+fn print_row([chain_id, name, enabled, checkpoint, height, behind, updated]: [&str; 7]) {
+    println!(
+        "{chain_id:>10}  {name:<20}  {enabled:<8}  {checkpoint:>12}  {height:>12}  {behind:>10}  {updated}"
+    );
+}
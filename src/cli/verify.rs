@@ -0,0 +1,103 @@
+//! `runic verify`: dry run of the configuration.
+//!
+//! Checks every store and every enabled chain without indexing anything, prints
+//! one line per check and fails if any of them failed.
+//!
+//! It runs without a `Runtime`: each store is connected on its own, so an
+//! unreachable one is reported as a failed check instead of aborting the command.
+
+use std::sync::Arc;
+
+use anyhow::{anyhow, ensure};
+use tokio::sync::mpsc;
+
+use crate::{
+    cli::checks::check_chain,
+    config::Settings,
+    db::{ClickhouseClient, Database, PostgresClient},
+    RedpandaPublisher,
+};
+
+pub(crate) async fn verify(settings: &Settings) -> anyhow::Result<()> {
+    let mut failures = 0;
+    let mut report = |name: &str, outcome: anyhow::Result<String>| match outcome {
+        Ok(detail) => print_row(name, "ok", &detail),
+        Err(e) => {
+            failures += 1;
+            print_row(name, "FAILED", &format!("{:#}", e))
+        },
+    };
+
+    // The ingestors are never started, nothing is written
+    let (_, historical_rx) = mpsc::channel(1);
+    let (_, live_rx) = mpsc::channel(1);
+    let clickhouse =
+        match ClickhouseClient::new(settings.clickhouse.clone(), historical_rx, live_rx).await {
+            Ok((client, _, _)) => {
+                report("clickhouse", Ok(String::new()));
+                Some(client)
+            },
+            Err(e) => {
+                report("clickhouse", Err(e));
+                None
+            },
+        };
+    let postgres = match PostgresClient::new(settings.postgres.clone()).await {
+        Ok(client) => {
+            report("postgres", Ok(String::new()));
+            Some(client)
+        },
+        Err(e) => {
+            report("postgres", Err(e));
+            None
+        },
+    };
+    match settings.redpanda.as_ref().filter(|r| r.enabled) {
+        None => report("redpanda", Ok("disabled".to_string())),
+        Some(settings) => {
+            let outcome = match RedpandaPublisher::new(settings) {
+                Some(publisher) => publisher
+                    .health_check()
+                    .await
+                    .map(|_| settings.brokers.clone()),
+                None => Err(anyhow::anyhow!("failed to create the producer")),
+            };
+            report("redpanda", outcome);
+        },
+    }
+
+    let (Some(clickhouse), Some(postgres)) = (clickhouse, postgres) else {
+        report(
+            "chains",
+            Err(anyhow!("not checked, both stores must be reachable")),
+        );
+        ensure!(failures == 0, "{} check(s) failed", failures);
+        return Ok(());
+    };
+    let db = Arc::new(Database {
+        clickhouse: Arc::new(clickhouse),
+        postgres: Arc::new(postgres),
+    });
+
+    let chains = match db.postgres.get_chains().await {
+        Ok(chains) => chains,
+        Err(e) => {
+            report("chains", Err(e));
+            Vec::new()
+        },
+    };
+    for chain in chains.iter().filter(|chain| chain.enabled) {
+        println!();
+        println!("Chain {} ({})", chain.chain_id, chain.name);
+        for check in check_chain(&db, chain, &settings.indexer.hypersync_bearer_token).await {
+            report(&format!("  {}", check.name), check.outcome);
+        }
+    }
+
+    ensure!(failures == 0, "{} check(s) failed", failures);
+    Ok(())
+}
+
+fn print_row(name: &str, state: &str, detail: &str) {
+    println!("{name:<16}  {state:<6}  {detail}");
+}
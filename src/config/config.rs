@@ -1,6 +1,14 @@
-use config::{Config, ConfigError, File};
+use std::collections::HashMap;
+
+use config::{builder::DefaultState, Config, ConfigBuilder, ConfigError, Environment, File};
 use serde::Deserialize;
 
+use crate::{cron::CronJob, db::models::DatabaseChain};
+
+/// Prefix of the environment overrides: `RUNIC__SECTION__KEY`, e.g.
+/// `RUNIC__POSTGRES__PASSWORD` for `postgres.password`
+const ENV_PREFIX: &str = "RUNIC";
+
 /// ClickHouse database connection and batching configuration.
 ///
 /// Controls connection settings and dual-channel batch ingestion:
@@ -10,7 +18,10 @@ use serde::Deserialize;
 pub struct ClickHouseSettings {
     pub url: String,
     pub user: String,
+    #[serde(default)]
     pub password: String,
+    /// File holding the password (e.g. a Docker secret), instead of `password`
+    pub password_file: Option<String>,
     pub database: String,
     // Historical ingestor (high-throughput sync)
     #[serde(default = "default_historical_batch_size")]
@@ -52,7 +63,10 @@ pub struct PostgresSettings {
     pub host: String,
     pub port: u16,
     pub user: String,
+    #[serde(default)]
     pub password: String,
+    /// File holding the password (e.g. a Docker secret), instead of `password`
+    pub password_file: Option<String>,
     pub database: String,
     #[serde(default = "default_pool_size")]
     pub pool_size: usize,
@@ -68,7 +82,10 @@ fn default_pool_size() -> usize {
 /// with sub-second latency for real-time indexing.
 #[derive(Debug, Deserialize, Clone)]
 pub struct IndexerSettings {
+    #[serde(default)]
     pub hypersync_bearer_token: String,
+    /// File holding the token, instead of `hypersync_bearer_token`
+    pub hypersync_bearer_token_file: Option<String>,
     #[serde(default = "default_tip_poll_interval")]
     pub tip_poll_interval_milliseconds: u64,
 }
@@ -98,9 +115,78 @@ fn default_redpanda_topic_prefix() -> String {
     "runic".to_string()
 }
 
+/// Background job configuration.
+///
+/// Jobs not listed in `jobs` run every `CronJob::default_interval`.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct CronSettings {
+    /// Per-job settings, keyed by job name (e.g. `pool_snapshots`)
+    #[serde(default)]
+    pub jobs: HashMap<String, CronJobSettings>,
+}
+
+impl CronSettings {
+    /// Settings of `job`, the defaults when not configured.
+    pub fn job(&self, job: CronJob) -> CronJobSettings {
+        self.jobs.get(job.name()).cloned().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct CronJobSettings {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// Seconds between runs (the job's default when neither this nor `schedule` is set)
+    pub interval_secs: Option<u64>,
+    /// Cron expression with seconds, in UTC (e.g. `0 0 * * * *` hourly), instead of
+    /// `interval_secs`
+    pub schedule: Option<String>,
+    /// A run taking longer is abandoned (no limit by default)
+    pub timeout_secs: Option<u64>,
+    /// Also run once when the scheduler starts
+    #[serde(default)]
+    pub run_on_start: bool,
+}
+
+impl Default for CronJobSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_secs: None,
+            schedule: None,
+            timeout_secs: None,
+            run_on_start: false,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Chains declared in the configuration, synced into `indexer.chains` at startup.
+///
+/// PostgreSQL stays the source read by the workers, so chains can still be
+/// changed at runtime, but listed chains are reset to their definition on the
+/// next sync.
+#[derive(Debug, Deserialize, Clone)]
+pub struct ChainsSettings {
+    /// Disable the enabled chains that are not listed, so chains removed from the
+    /// definitions stop being indexed
+    #[serde(default)]
+    pub disable_unlisted: bool,
+    /// Don't run the network checks on created or changed chains
+    #[serde(default)]
+    pub skip_checks: bool,
+    /// Same fields as `runic chains show`
+    #[serde(default)]
+    pub definitions: Vec<DatabaseChain>,
+}
+
 /// Root application configuration.
 ///
-/// Loaded from `config.yaml` at startup.
+/// Loaded from `config.yaml` (or the file given with `--config`) at startup, with
+/// `RUNIC__SECTION__KEY` environment variables taking precedence over the file.
 /// Contains all subsystem configurations for databases and indexer.
 #[derive(Debug, Deserialize, Clone)]
 pub struct Settings {
@@ -109,6 +195,10 @@ pub struct Settings {
     pub indexer: IndexerSettings,
     #[serde(default)]
     pub redpanda: Option<RedpandaSettings>,
+    #[serde(default)]
+    pub cron: CronSettings,
+    #[serde(default)]
+    pub chains: Option<ChainsSettings>,
 }
 
 fn default_tip_poll_interval() -> u64 {
@@ -117,12 +207,291 @@ fn default_tip_poll_interval() -> u64 {
 
 impl Settings {
     pub fn new() -> Result<Self, ConfigError> {
-        let s = Config::builder()
-            .add_source(File::with_name("config"))
-            .build()?;
+        Self::from_file("config")
+    }
+
+    /// Loads the settings from `path` (the extension may be omitted) and the
+    /// environment, then reads the secret files and validates the result.
+    pub fn from_file(path: &str) -> Result<Self, ConfigError> {
+        Self::load(
+            Config::builder()
+                .add_source(File::with_name(path))
+                .add_source(environment()),
+        )
+    }
 
-        let settings: Settings = s.try_deserialize()?;
+    fn load(builder: ConfigBuilder<DefaultState>) -> Result<Self, ConfigError> {
+        let mut settings: Settings = builder.build()?.try_deserialize()?;
 
+        read_secret(
+            "clickhouse.password",
+            &mut settings.clickhouse.password,
+            &settings.clickhouse.password_file,
+        )?;
+        read_secret(
+            "postgres.password",
+            &mut settings.postgres.password,
+            &settings.postgres.password_file,
+        )?;
+        read_secret(
+            "indexer.hypersync_bearer_token",
+            &mut settings.indexer.hypersync_bearer_token,
+            &settings.indexer.hypersync_bearer_token_file,
+        )?;
+
+        settings.validate()?;
         Ok(settings)
     }
+
+    /// Checks the values serde accepts but the indexer can't use.
+    fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+        let mut check = |valid: bool, key: &str, problem: &str| {
+            if !valid {
+                problems.push(format!("{} {}", key, problem));
+            }
+        };
+
+        let clickhouse = &self.clickhouse;
+        check(
+            clickhouse.url.starts_with("http://") || clickhouse.url.starts_with("https://"),
+            "clickhouse.url",
+            "must be an http(s) URL",
+        );
+        check(
+            !clickhouse.database.is_empty(),
+            "clickhouse.database",
+            "must not be empty",
+        );
+        check(
+            clickhouse.historical_batch_size > 0,
+            "clickhouse.historical_batch_size",
+            "must be positive",
+        );
+        check(
+            clickhouse.live_batch_size > 0,
+            "clickhouse.live_batch_size",
+            "must be positive",
+        );
+
+        let postgres = &self.postgres;
+        check(
+            !postgres.host.is_empty(),
+            "postgres.host",
+            "must not be empty",
+        );
+        check(postgres.port > 0, "postgres.port", "must be positive");
+        check(
+            !postgres.user.is_empty(),
+            "postgres.user",
+            "must not be empty",
+        );
+        check(
+            !postgres.database.is_empty(),
+            "postgres.database",
+            "must not be empty",
+        );
+        check(
+            postgres.pool_size > 0,
+            "postgres.pool_size",
+            "must be positive",
+        );
+
+        check(
+            self.indexer.tip_poll_interval_milliseconds > 0,
+            "indexer.tip_poll_interval_milliseconds",
+            "must be positive",
+        );
+
+        if let Some(redpanda) = self.redpanda.as_ref().filter(|r| r.enabled) {
+            check(
+                !redpanda.brokers.is_empty(),
+                "redpanda.brokers",
+                "must not be empty",
+            );
+        }
+
+        if let Some(chains) = &self.chains {
+            for (i, chain) in chains.definitions.iter().enumerate() {
+                check(
+                    !chains.definitions[..i]
+                        .iter()
+                        .any(|other| other.chain_id == chain.chain_id),
+                    "chains.definitions",
+                    &format!("lists chain {} twice", chain.chain_id),
+                );
+            }
+        }
+
+        for (name, job) in &self.cron.jobs {
+            let key = format!("cron.jobs.{}", name);
+            check(CronJob::from_name(name).is_some(), &key, "is not a job");
+            check(
+                job.interval_secs.is_none() || job.schedule.is_none(),
+                &key,
+                "sets both interval_secs and schedule",
+            );
+            if let Some(schedule) = &job.schedule {
+                check(
+                    CronJob::is_valid_schedule(schedule),
+                    &format!("{}.schedule", key),
+                    "is not a cron expression with seconds",
+                );
+            }
+            check(
+                job.interval_secs != Some(0),
+                &format!("{}.interval_secs", key),
+                "must be positive",
+            );
+            check(
+                job.timeout_secs != Some(0),
+                &format!("{}.timeout_secs", key),
+                "must be positive",
+            );
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Message(format!(
+                "invalid configuration: {}",
+                problems.join(", ")
+            )))
+        }
+    }
+}
+
+/// `RUNIC__SECTION__KEY` overrides (keys are lowercased, empty variables ignored).
+fn environment() -> Environment {
+    Environment::with_prefix(ENV_PREFIX)
+        .prefix_separator("__")
+        .separator("__")
+        .ignore_empty(true)
+}
+
+/// Replaces `value` with the content of `file`, when set. Setting both is an error.
+fn read_secret(key: &str, value: &mut String, file: &Option<String>) -> Result<(), ConfigError> {
+    let Some(path) = file else {
+        return Ok(());
+    };
+    if !value.is_empty() {
+        return Err(ConfigError::Message(format!(
+            "{} and {}_file are both set",
+            key, key
+        )));
+    }
+
+    let secret = std::fs::read_to_string(path)
+        .map_err(|e| ConfigError::Message(format!("{}_file: cannot read {}: {}", key, path, e)))?;
+    *value = secret.trim_end_matches(['\r', '\n']).to_string();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use config::FileFormat;
+
+    use super::*;
+
+    const CONFIG: &str = r#"
+clickhouse:
+  url: "http://localhost:8123"
+  user: "default"
+  password: ""
+  database: "indexer"
+postgres:
+  host: "localhost"
+  port: 5432
+  user: "runic"
+  password: "from_file"
+  database: "indexer"
+indexer:
+  hypersync_bearer_token: ""
+"#;
+
+    fn load(env: &[(&str, &str)]) -> Result<Settings, ConfigError> {
+        let env: HashMap<String, String> = env
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        Settings::load(
+            Config::builder()
+                .add_source(File::from_str(CONFIG, FileFormat::Yaml))
+                .add_source(environment().source(Some(env))),
+        )
+    }
+
+    #[test]
+    fn test_environment_overrides_file() {
+        let settings = load(&[
+            ("RUNIC__POSTGRES__PASSWORD", "from_env"),
+            ("RUNIC__POSTGRES__PORT", "5433"),
+            ("RUNIC__INDEXER__TIP_POLL_INTERVAL_MILLISECONDS", "500"),
+        ])
+        .unwrap();
+
+        assert_eq!(settings.postgres.password, "from_env");
+        assert_eq!(settings.postgres.port, 5433);
+        assert_eq!(settings.indexer.tip_poll_interval_milliseconds, 500);
+        assert_eq!(settings.clickhouse.database, "indexer");
+    }
+
+    #[test]
+    fn test_secret_file_and_validation_errors() {
+        let path = std::env::temp_dir().join(format!("runic-token-{}", std::process::id()));
+        std::fs::write(&path, "token\n").unwrap();
+        let settings = load(&[(
+            "RUNIC__INDEXER__HYPERSYNC_BEARER_TOKEN_FILE",
+            path.to_str().unwrap(),
+        )])
+        .unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(settings.indexer.hypersync_bearer_token, "token");
+
+        let both = load(&[("RUNIC__POSTGRES__PASSWORD_FILE", "/nonexistent")]).unwrap_err();
+        assert!(both
+            .to_string()
+            .contains("postgres.password and postgres.password_file"));
+
+        let invalid = load(&[("RUNIC__CLICKHOUSE__URL", "localhost:8123")]).unwrap_err();
+        assert!(invalid
+            .to_string()
+            .contains("clickhouse.url must be an http(s) URL"));
+
+        let unknown_job = load(&[("RUNIC__CRON__JOBS__SNAPSHOTS__ENABLED", "false")]).unwrap_err();
+        assert!(unknown_job
+            .to_string()
+            .contains("cron.jobs.snapshots is not a job"));
+    }
+
+    #[test]
+    fn test_cron_job_schedule_validation() {
+        let settings =
+            load(&[("RUNIC__CRON__JOBS__POOL_SNAPSHOTS__SCHEDULE", "0 0 * * * *")]).unwrap();
+        assert_eq!(
+            settings
+                .cron
+                .job(CronJob::PoolSnapshots)
+                .schedule
+                .as_deref(),
+            Some("0 0 * * * *")
+        );
+
+        let invalid =
+            load(&[("RUNIC__CRON__JOBS__POOL_SNAPSHOTS__SCHEDULE", "every hour")]).unwrap_err();
+        assert!(invalid
+            .to_string()
+            .contains("cron.jobs.pool_snapshots.schedule is not a cron expression"));
+
+        let both = load(&[
+            ("RUNIC__CRON__JOBS__POOL_SNAPSHOTS__SCHEDULE", "0 0 * * * *"),
+            ("RUNIC__CRON__JOBS__POOL_SNAPSHOTS__INTERVAL_SECS", "3600"),
+        ])
+        .unwrap_err();
+        assert!(both
+            .to_string()
+            .contains("cron.jobs.pool_snapshots sets both interval_secs and schedule"));
+    }
 }
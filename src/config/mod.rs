@@ -1,5 +1,6 @@
 pub mod config;
 
 pub use config::{
-    ClickHouseSettings, IndexerSettings, PostgresSettings, RedpandaSettings, Settings,
+    ChainsSettings, ClickHouseSettings, CronJobSettings, CronSettings, IndexerSettings,
+    PostgresSettings, RedpandaSettings, Settings,
 };
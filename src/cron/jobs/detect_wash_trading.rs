@@ -0,0 +1,415 @@
+//! Job to flag wash trading in ClickHouse.
+//!
+//! Scans recent swaps for three patterns and records the affected events in
+//! `indexer.wash_trades`, which `update_24h_stats` subtracts from the adjusted volume.
+//! Events are immutable in ClickHouse, so flags are a separate table joined on
+//! (chain_id, tx_hash, log_index).
+
+use std::collections::{HashMap, HashSet};
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use time::OffsetDateTime;
+
+use crate::db::Database;
+
+/// Window scanned on each run
+const WINDOW_HOURS: u32 = 24;
+/// History used for the volume baseline and to tell new wallets from known ones
+const BASELINE_DAYS: u32 = 7;
+
+/// Wallet behind a swap: the transaction sender when known, otherwise `maker`
+const WALLET: &str = "if(tx_from != '', tx_from, maker)";
+
+/// Max |net token0 flow| relative to gross token0 flow to count as "net-zero"
+const NET_ZERO_TOLERANCE: f64 = 0.05;
+/// Minimum buys and minimum sells by one wallet on one pool for a round trip
+const ROUND_TRIP_MIN_LEGS: usize = 2;
+/// A pool traded only by this many wallets or fewer is a circular-trading candidate
+const CIRCULAR_MAX_WALLETS: usize = 3;
+/// Minimum swaps in the window for a circular-trading pool
+const CIRCULAR_MIN_SWAPS: usize = 10;
+/// Hourly volume above this multiple of the baseline average is a spike
+const VOLUME_SPIKE_FACTOR: f64 = 5.0;
+
+/// Flags wash trading over the last `WINDOW_HOURS` for all enabled chains.
+///
+/// Patterns (`reason` column):
+/// - round_trip: a wallet buys and sells the same pool repeatedly and ends net-zero
+/// - circular: a pool traded only by a handful of wallets whose combined position is net-zero
+/// - volume_spike: an hour with `VOLUME_SPIKE_FACTOR`x the pool's usual volume and no new wallet
+///
+/// Re-running is idempotent: flags are deduplicated per event and reason.
+pub async fn run(db: &Database) -> Result<()> {
+    info!("Starting detect_wash_trading job...");
+
+    let start = std::time::Instant::now();
+
+    for chain in db.postgres.get_chains().await? {
+        if !chain.enabled {
+            continue;
+        }
+
+        if let Err(e) = detect_chain(db, chain.chain_id).await {
+            warn!(
+                "Failed to detect wash trading for chain {}: {:#}",
+                chain.chain_id, e
+            );
+        }
+    }
+
+    let flagged: u64 = db
+        .clickhouse
+        .client
+        .query(&format!(
+            "SELECT count() FROM indexer.wash_trades FINAL
+             WHERE timestamp >= now() - INTERVAL {WINDOW_HOURS} HOUR"
+        ))
+        .fetch_one()
+        .await?;
+
+    info!(
+        "Completed detect_wash_trading job in {:?} ({} flagged events in the last {}h)",
+        start.elapsed(),
+        flagged,
+        WINDOW_HOURS
+    );
+    Ok(())
+}
+
+/// Streams the chain's window swaps pool by pool and records the flagged ones.
+async fn detect_chain(db: &Database, chain_id: u64) -> Result<()> {
+    // Baseline: average hourly volume over the history before the window
+    let baseline_hours = BASELINE_DAYS * 24 - WINDOW_HOURS;
+
+    let mut cursor = db
+        .clickhouse
+        .client
+        .query(&format!(
+            r#"
+            SELECT e.pool_address, e.tx_hash, e.log_index, e.timestamp, e.wallet, e.volume_usd,
+                   e.amount0_adjusted, e.amount0_direction, f.first_seen,
+                   b.baseline_hourly_volume
+            FROM (
+                SELECT pool_address, tx_hash, log_index, timestamp, {WALLET} AS wallet,
+                       volume_usd, amount0_adjusted, amount0_direction
+                FROM indexer.events
+                WHERE chain_id = ?
+                  AND timestamp >= now() - INTERVAL {WINDOW_HOURS} HOUR
+                  AND event_type = 'swap'
+            ) AS e
+            LEFT JOIN (
+                SELECT pool_address, {WALLET} AS wallet, min(timestamp) AS first_seen
+                FROM indexer.events
+                WHERE chain_id = ?
+                  AND timestamp >= now() - INTERVAL {BASELINE_DAYS} DAY
+                  AND event_type = 'swap'
+                GROUP BY pool_address, wallet
+            ) AS f USING (pool_address, wallet)
+            LEFT JOIN (
+                SELECT pool_address, sum(volume_usd) / {baseline_hours} AS baseline_hourly_volume
+                FROM indexer.events
+                WHERE chain_id = ?
+                  AND timestamp >= now() - INTERVAL {BASELINE_DAYS} DAY
+                  AND timestamp < now() - INTERVAL {WINDOW_HOURS} HOUR
+                  AND event_type = 'swap'
+                GROUP BY pool_address
+            ) AS b USING (pool_address)
+            ORDER BY e.pool_address, e.timestamp, e.log_index
+            "#
+        ))
+        .bind(chain_id)
+        .bind(chain_id)
+        .bind(chain_id)
+        .fetch::<WindowSwap>()?;
+
+    let mut flags = Vec::new();
+    let mut pool_swaps: Vec<WindowSwap> = Vec::new();
+    while let Some(swap) = cursor.next().await? {
+        if pool_swaps
+            .last()
+            .is_some_and(|last| last.pool_address != swap.pool_address)
+        {
+            flags.extend(wash_trades(chain_id, &pool_swaps));
+            pool_swaps.clear();
+        }
+        pool_swaps.push(swap);
+    }
+    flags.extend(wash_trades(chain_id, &pool_swaps));
+
+    if flags.is_empty() {
+        return Ok(());
+    }
+    let mut insert = db
+        .clickhouse
+        .client
+        .insert::<WashTrade>("wash_trades")
+        .await
+        .context("Failed to start wash_trades insert")?;
+    for flag in &flags {
+        insert.write(flag).await?;
+    }
+    insert.end().await.context("Failed to write wash trades")
+}
+
+/// Rows of `wash_trades` for the flagged swaps of one pool's window.
+fn wash_trades(chain_id: u64, swaps: &[WindowSwap]) -> Vec<WashTrade> {
+    let flagged = [
+        ("round_trip", round_trips(swaps)),
+        ("circular", circular(swaps)),
+        ("volume_spike", volume_spikes(swaps)),
+    ];
+
+    flagged
+        .into_iter()
+        .flat_map(|(reason, indexes)| {
+            indexes.into_iter().map(move |i| {
+                let swap = &swaps[i];
+                WashTrade {
+                    chain_id,
+                    pool_address: swap.pool_address.clone(),
+                    tx_hash: swap.tx_hash.clone(),
+                    log_index: swap.log_index,
+                    timestamp: swap.timestamp,
+                    wallet: swap.wallet.clone(),
+                    reason: reason.to_string(),
+                    volume_usd: swap.volume_usd,
+                }
+            })
+        })
+        .collect()
+}
+
+/// Whether the net token0 flow of `swaps` is within `NET_ZERO_TOLERANCE` of their
+/// gross token0 flow.
+fn net_zero<'a>(swaps: impl Iterator<Item = &'a WindowSwap>) -> bool {
+    let (net, gross) = swaps.fold((0.0, 0.0), |(net, gross), swap| {
+        (
+            net + swap.amount0_adjusted * f64::from(swap.amount0_direction),
+            gross + swap.amount0_adjusted,
+        )
+    });
+    net.abs() <= NET_ZERO_TOLERANCE * gross
+}
+
+/// Swaps of the wallets that bought and sold the pool `ROUND_TRIP_MIN_LEGS` times
+/// each and ended net-zero.
+fn round_trips(swaps: &[WindowSwap]) -> Vec<usize> {
+    let mut by_wallet: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (i, swap) in swaps.iter().enumerate() {
+        if !swap.wallet.is_empty() {
+            by_wallet.entry(swap.wallet.as_str()).or_default().push(i);
+        }
+    }
+
+    by_wallet
+        .into_values()
+        .filter(|indexes| {
+            let legs = |direction: i8| {
+                indexes
+                    .iter()
+                    .filter(|&&i| swaps[i].amount0_direction == direction)
+                    .count()
+            };
+            legs(1) >= ROUND_TRIP_MIN_LEGS
+                && legs(-1) >= ROUND_TRIP_MIN_LEGS
+                && net_zero(indexes.iter().map(|&i| &swaps[i]))
+        })
+        .flatten()
+        .collect()
+}
+
+/// All the swaps of a busy pool traded by a few wallets only, net-zero overall.
+fn circular(swaps: &[WindowSwap]) -> Vec<usize> {
+    let wallets: HashSet<&str> = swaps.iter().map(|swap| swap.wallet.as_str()).collect();
+    let is_circular = swaps.len() >= CIRCULAR_MIN_SWAPS
+        && (2..=CIRCULAR_MAX_WALLETS).contains(&wallets.len())
+        && net_zero(swaps.iter());
+
+    if is_circular {
+        (0..swaps.len()).collect()
+    } else {
+        Vec::new()
+    }
+}
+
+/// Swaps of the hours whose volume exceeds `VOLUME_SPIKE_FACTOR` times the pool's
+/// baseline while no wallet traded the pool for the first time.
+fn volume_spikes(swaps: &[WindowSwap]) -> Vec<usize> {
+    let hour = |time: OffsetDateTime| time.unix_timestamp().div_euclid(3600);
+
+    let mut by_hour: HashMap<i64, Vec<usize>> = HashMap::new();
+    for (i, swap) in swaps.iter().enumerate() {
+        by_hour.entry(hour(swap.timestamp)).or_default().push(i);
+    }
+
+    by_hour
+        .into_iter()
+        .filter(|(start, indexes)| {
+            let baseline = swaps[indexes[0]].baseline_hourly_volume;
+            let volume: f64 = indexes.iter().map(|&i| swaps[i].volume_usd).sum();
+            let new_wallet = indexes.iter().any(|&i| hour(swaps[i].first_seen) == *start);
+
+            baseline > 0.0 && volume > VOLUME_SPIKE_FACTOR * baseline && !new_wallet
+        })
+        .flat_map(|(_, indexes)| indexes)
+        .collect()
+}
+
+/// A swap of the window, with the wallet's first swap on the pool over the last
+/// `BASELINE_DAYS` and the pool's average hourly volume before the window.
+#[derive(Debug, clickhouse::Row, serde::Deserialize)]
+struct WindowSwap {
+    pool_address: String,
+    tx_hash: String,
+    log_index: u32,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    timestamp: OffsetDateTime,
+    wallet: String,
+    volume_usd: f64,
+    amount0_adjusted: f64,
+    amount0_direction: i8,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    first_seen: OffsetDateTime,
+    baseline_hourly_volume: f64,
+}
+
+/// Row of `indexer.wash_trades` (`detected_at` defaults to the insert time).
+#[derive(Debug, clickhouse::Row, serde::Serialize)]
+struct WashTrade {
+    chain_id: u64,
+    pool_address: String,
+    tx_hash: String,
+    log_index: u32,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    timestamp: OffsetDateTime,
+    wallet: String,
+    reason: String,
+    volume_usd: f64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: i64 = 3600;
+    /// Start of the hour the test swaps happen in
+    const NOW: i64 = 1_714_521_600;
+
+    /// Swap of `amount` token0 by `wallet`, `direction` 1 or -1, at `NOW + offset` seconds.
+    /// The wallet is known since the day before, the pool's baseline is 100 USD/hour.
+    fn swap(wallet: &str, direction: i8, amount: f64, offset: i64) -> WindowSwap {
+        let time = |t: i64| OffsetDateTime::from_unix_timestamp(t).unwrap();
+        WindowSwap {
+            pool_address: "0xpool".to_string(),
+            tx_hash: format!("0x{:x}", offset),
+            log_index: 0,
+            timestamp: time(NOW + offset),
+            wallet: wallet.to_string(),
+            volume_usd: amount,
+            amount0_adjusted: amount,
+            amount0_direction: direction,
+            first_seen: time(NOW - 24 * HOUR),
+            baseline_hourly_volume: 100.0,
+        }
+    }
+
+    fn sorted(mut indexes: Vec<usize>) -> Vec<usize> {
+        indexes.sort_unstable();
+        indexes
+    }
+
+    #[test]
+    fn test_round_trip_flags_net_zero_wallets_only() {
+        let swaps = vec![
+            swap("0xwash", 1, 100.0, 0),
+            swap("0xwash", -1, 100.0, 60),
+            swap("0xwash", 1, 50.0, 120),
+            swap("0xwash", -1, 52.0, 180),
+            // Buys and sells as often, but accumulates
+            swap("0xtrader", 1, 100.0, 240),
+            swap("0xtrader", -1, 10.0, 300),
+            swap("0xtrader", 1, 100.0, 360),
+            swap("0xtrader", -1, 10.0, 420),
+            // Net-zero, but a single round trip
+            swap("0xonce", 1, 10.0, 480),
+            swap("0xonce", -1, 10.0, 540),
+        ];
+
+        assert_eq!(sorted(round_trips(&swaps)), vec![0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_circular_needs_few_wallets_many_swaps_and_net_zero() {
+        // Alternating buys and sells by `wallets` in turn
+        let ring = |wallets: &[&str], swaps: i64| -> Vec<WindowSwap> {
+            (0..swaps)
+                .map(|i| {
+                    let wallet = wallets[i as usize % wallets.len()];
+                    swap(wallet, if i % 2 == 0 { 1 } else { -1 }, 10.0, i * 60)
+                })
+                .collect()
+        };
+
+        assert_eq!(circular(&ring(&["0xa", "0xb"], 10)).len(), 10);
+        assert!(
+            circular(&ring(&["0xa", "0xb"], 8)).is_empty(),
+            "too few swaps"
+        );
+        assert!(circular(&ring(&["0xa"], 10)).is_empty(), "a single wallet");
+        assert!(
+            circular(&ring(&["0xa", "0xb", "0xc", "0xd", "0xe"], 10)).is_empty(),
+            "too many wallets"
+        );
+
+        let mut one_sided = ring(&["0xa", "0xb"], 10);
+        one_sided[1].amount0_direction = 1;
+        assert!(circular(&one_sided).is_empty(), "not net-zero");
+    }
+
+    #[test]
+    fn test_volume_spike_without_new_wallets() {
+        let mut swaps = vec![
+            // 600 USD in the first hour: 6x the baseline
+            swap("0xa", 1, 300.0, 0),
+            swap("0xb", -1, 300.0, 60),
+            // 400 USD in the next hour: 4x
+            swap("0xa", 1, 400.0, HOUR),
+        ];
+        assert_eq!(sorted(volume_spikes(&swaps)), vec![0, 1]);
+
+        // A wallet trading the pool for the first time makes it organic
+        swaps[1].first_seen = swaps[1].timestamp;
+        assert!(volume_spikes(&swaps).is_empty());
+
+        // A pool without baseline has nothing to spike from
+        let mut new_pool = vec![swap("0xa", 1, 1_000.0, 0)];
+        new_pool[0].baseline_hourly_volume = 0.0;
+        assert!(volume_spikes(&new_pool).is_empty());
+    }
+
+    #[test]
+    fn test_wash_trades_record_each_reason() {
+        let swaps: Vec<WindowSwap> = (0..10)
+            .map(|i| {
+                swap(
+                    if i < 5 { "0xa" } else { "0xb" },
+                    if i % 2 == 0 { 1 } else { -1 },
+                    10.0,
+                    i,
+                )
+            })
+            .collect();
+
+        let trades = wash_trades(8453, &swaps);
+
+        let count = |reason: &str| trades.iter().filter(|t| t.reason == reason).count();
+        // Each wallet: 3 buys and 2 sells (or the reverse), 10 off net on 50
+        assert_eq!(count("round_trip"), 0);
+        assert_eq!(count("circular"), 10);
+        assert_eq!(count("volume_spike"), 0);
+        assert!(trades
+            .iter()
+            .all(|t| t.chain_id == 8453 && t.volume_usd == 10.0));
+    }
+}
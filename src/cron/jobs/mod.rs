@@ -1,5 +1,10 @@
+pub mod detect_wash_trading;
 pub mod pool_snapshots;
+pub mod reconcile_pool_reserves;
 pub mod refresh_materialized_views;
 pub mod token_snapshots;
 pub mod update_24h_stats;
+pub mod update_pool_fees;
 pub mod update_price_changes;
+pub mod update_token_supplies;
+pub mod update_trader_positions;
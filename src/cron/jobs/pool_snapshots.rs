@@ -17,7 +17,8 @@ const JOB_NAME: &str = "pool_snapshots";
 /// Snapshot data includes:
 /// - price, price_usd, tvl_usd
 /// - reserve0, reserve1, liquidity
-/// - volume_24h, swaps_24h, fees_24h
+/// - volume_24h, swaps_24h
+/// - fees_24h, fees_7d, fee_apr (maintained by the `update_pool_fees` job)
 pub async fn run(db: &Database, live_tx: &mpsc::Sender<IngestMessage>) -> Result<()> {
     info!("Starting pool_snapshots job...");
 
@@ -44,8 +45,9 @@ pub async fn run(db: &Database, live_tx: &mpsc::Sender<IngestMessage>) -> Result
             r#"
         SELECT 
             chain_id, address, price, price_usd, tvl_usd,
-            reserve0_adjusted, reserve1_adjusted, liquidity,
-            volume_24h, swaps_24h, fee
+            reserve0_adjusted::FLOAT8 AS reserve0_adjusted,
+            reserve1_adjusted::FLOAT8 AS reserve1_adjusted, liquidity,
+            volume_24h, swaps_24h, fees_24h, fees_7d, fee_apr
         FROM indexer.pools
         WHERE updated_at > $1 AND (tvl_usd > 0 OR volume_24h > 0)
         "#,
@@ -77,7 +79,9 @@ pub async fn run(db: &Database, live_tx: &mpsc::Sender<IngestMessage>) -> Result
         let liquidity_str: Option<String> = row.get("liquidity");
         let volume_24h: Option<f64> = row.get("volume_24h");
         let swaps_24h: Option<i64> = row.get("swaps_24h");
-        let fee: Option<i32> = row.get("fee");
+        let fees_24h: Option<f64> = row.get("fees_24h");
+        let fees_7d: Option<f64> = row.get("fees_7d");
+        let fee_apr: Option<f64> = row.get("fee_apr");
 
         // Parse liquidity string to UInt256
         let liquidity = liquidity_str
@@ -85,14 +89,6 @@ pub async fn run(db: &Database, live_tx: &mpsc::Sender<IngestMessage>) -> Result
             .map(UInt256::from)
             .unwrap_or(UInt256::from(0u128));
 
-        // fees_24h calculation
-        // fee is in ppm (e.g. 3000 = 0.3%)
-        let fees_24h = if let (Some(vol), Some(f)) = (volume_24h, fee) {
-            vol * (f as f64 / 1_000_000.0)
-        } else {
-            0.0
-        };
-
         snapshots.push(PoolSnapshot::new(
             chain_id as u64,
             address,
@@ -105,7 +101,9 @@ pub async fn run(db: &Database, live_tx: &mpsc::Sender<IngestMessage>) -> Result
             liquidity,
             volume_24h.unwrap_or(0.0),
             swaps_24h.unwrap_or(0) as u64,
-            fees_24h,
+            fees_24h.unwrap_or(0.0),
+            fees_7d.unwrap_or(0.0),
+            fee_apr.unwrap_or(0.0),
         ));
     }
 
@@ -0,0 +1,574 @@
+//! Job to reconcile V3/V4 pool reserves with on-chain balances.
+//!
+//! Concentrated-liquidity reserves are accumulated from event deltas and floored at
+//! zero, so they drift: protocol fee collection, donations and clamped deltas are not
+//! reflected. This job reads the real balances at the chain's last indexed block,
+//! corrects `reserve0_adjusted` / `reserve1_adjusted` in PostgreSQL and logs the drift.
+//!
+//! Reads at a past block need the node to still hold that block's state. Chains whose
+//! checkpoint is more than `MAX_CHECKPOINT_LAG` blocks behind the head are skipped,
+//! so a non-archive RPC is enough.
+//!
+//! - V3: `balanceOf(pool)` of both tokens is the pool's reserve.
+//! - V4: all pools live in the PoolManager, which has no per-pool balance. The reserves
+//!   of every pool holding a currency are checked against the manager's balance of it
+//!   and scaled down when they exceed it. The balance also backs ERC-6909 claims and
+//!   unclaimed fees, so a shortfall on our side is only logged.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use alloy::primitives::U256;
+use anyhow::Result;
+use fastnum::D256;
+use log::{debug, info, warn};
+
+use crate::db::models::DatabaseChain;
+use crate::db::Database;
+use crate::utils::{
+    compute_v4_pool_id, decimal_to_f64, str_to_decimal, u256_to_decimal, ZERO_ADDRESS,
+};
+use crate::worker::TokenFetcher;
+
+/// Relative difference between stored and on-chain reserve above which a V3 pool is corrected
+const V3_DRIFT_TOLERANCE: f64 = 0.001;
+/// Relative excess of V4 reserves over the PoolManager balance above which they are scaled
+const V4_DRIFT_TOLERANCE: f64 = 0.01;
+/// V3 pools checked per chain and run, by descending TVL
+const MAX_V3_POOLS_PER_RUN: i64 = 20_000;
+/// Checkpoint lag above which a chain is skipped (non-archive nodes keep the state
+/// of the last 128 blocks)
+const MAX_CHECKPOINT_LAG: u64 = 64;
+
+/// Reconciles V3/V4 pool reserves for all enabled chains.
+///
+/// Balances are read at the chain's sync checkpoint, so they match the state the
+/// indexer has applied. Chains still catching up are skipped (see `MAX_CHECKPOINT_LAG`).
+/// A correction is skipped when the pool was updated by the worker in the meantime
+/// (it is picked up on the next run).
+///
+/// `tvl_usd` is adjusted by the USD value of the correction, until the worker
+/// recomputes it on the pool's next event.
+pub async fn run(db: &Arc<Database>) -> Result<()> {
+    info!("Starting reconcile_pool_reserves job...");
+
+    let start = std::time::Instant::now();
+    let mut pool_count = 0;
+
+    for chain in db.postgres.get_chains().await? {
+        if !chain.enabled {
+            continue;
+        }
+
+        match reconcile_chain(db, &chain).await {
+            Ok(count) => pool_count += count,
+            Err(e) => warn!(
+                "Failed to reconcile pool reserves for chain {}: {:#}",
+                chain.chain_id, e
+            ),
+        }
+    }
+
+    info!(
+        "Completed reconcile_pool_reserves job in {:?} ({} pools corrected)",
+        start.elapsed(),
+        pool_count
+    );
+    Ok(())
+}
+
+async fn reconcile_chain(db: &Arc<Database>, chain: &DatabaseChain) -> Result<usize> {
+    let Some(checkpoint) = db.postgres.get_sync_checkpoint(chain.chain_id).await? else {
+        return Ok(0);
+    };
+    let block = checkpoint.last_indexed_block;
+    let fetcher = TokenFetcher::new(chain.rpc_url.clone(), chain.chain_id as i64, db.clone());
+
+    let head = fetcher.latest_block().await?;
+    if head.saturating_sub(block) > MAX_CHECKPOINT_LAG {
+        info!(
+            "Chain {}: Skipping reserve reconciliation, checkpoint {} is {} blocks behind the head",
+            chain.chain_id,
+            block,
+            head - block
+        );
+        return Ok(0);
+    }
+
+    let mut corrections = reconcile_v3(db, chain, &fetcher, block).await?;
+    let v3_count = corrections.len();
+    corrections.extend(reconcile_v4(db, chain, &fetcher, block).await?);
+
+    if corrections.is_empty() {
+        return Ok(0);
+    }
+
+    let drift_usd: f64 = corrections.iter().map(|c| c.drift_usd.abs()).sum();
+    if let Some(largest) = corrections
+        .iter()
+        .max_by(|a, b| a.drift_usd.abs().total_cmp(&b.drift_usd.abs()))
+    {
+        info!(
+            "Chain {}: Reserve drift at block {}: {} V3 and {} V4 pools off by ${:.2} in total, largest {} (${:.2})",
+            chain.chain_id,
+            block,
+            v3_count,
+            corrections.len() - v3_count,
+            drift_usd,
+            largest.address,
+            largest.drift_usd
+        );
+    }
+
+    save_corrections(db, chain.chain_id as i64, &corrections).await
+}
+
+/// Pools whose `balanceOf(pool)` differs from the stored reserves.
+async fn reconcile_v3(
+    db: &Database,
+    chain: &DatabaseChain,
+    fetcher: &TokenFetcher,
+    block: u64,
+) -> Result<Vec<Correction>> {
+    let pools = load_pools(db, chain.chain_id as i64, "v3", block, MAX_V3_POOLS_PER_RUN).await?;
+
+    let pairs: Vec<(String, String)> = pools
+        .iter()
+        .flat_map(|pool| {
+            [
+                (pool.token0.clone(), pool.address.clone()),
+                (pool.token1.clone(), pool.address.clone()),
+            ]
+        })
+        .collect();
+    let balances = fetcher.fetch_balances(&pairs, block).await;
+
+    let mut corrections = Vec::new();
+    for pool in &pools {
+        let (Some(balance0), Some(balance1)) = (
+            balances.get(&(pool.token0.clone(), pool.address.clone())),
+            balances.get(&(pool.token1.clone(), pool.address.clone())),
+        ) else {
+            continue;
+        };
+
+        let onchain0 = u256_to_decimal(*balance0, pool.token0_decimals);
+        let onchain1 = u256_to_decimal(*balance1, pool.token1_decimals);
+
+        if relative_drift(pool.reserve0, onchain0) > V3_DRIFT_TOLERANCE
+            || relative_drift(pool.reserve1, onchain1) > V3_DRIFT_TOLERANCE
+        {
+            corrections.push(Correction::new(pool, onchain0, onchain1));
+        }
+    }
+
+    Ok(corrections)
+}
+
+/// Pools of currencies whose total stored reserves exceed the PoolManager's balance.
+///
+/// Totals include pools the worker already moved past `block`: the tolerance
+/// absorbs those few blocks.
+async fn reconcile_v4(
+    db: &Database,
+    chain: &DatabaseChain,
+    fetcher: &TokenFetcher,
+    block: u64,
+) -> Result<Vec<Correction>> {
+    let pools = load_pools(db, chain.chain_id as i64, "v4", u64::MAX, i64::MAX).await?;
+    let wrapped_native = chain.native_token_address.to_lowercase();
+
+    // (currency, manager) -> (decimals, sum of stored reserves)
+    let mut totals: HashMap<(String, String), (u8, D256)> = HashMap::new();
+    for pool in &pools {
+        let Some(manager) = &pool.factory else {
+            continue;
+        };
+        for (currency, decimals, reserve) in pool.v4_currencies(&wrapped_native) {
+            totals
+                .entry((currency, manager.clone()))
+                .or_insert((decimals, D256::ZERO))
+                .1 += reserve;
+        }
+    }
+
+    let pairs: Vec<(String, String)> = totals.keys().cloned().collect();
+    let balances = fetcher.fetch_balances(&pairs, block).await;
+
+    let factors = scale_factors(chain.chain_id, &totals, &balances);
+
+    let mut corrections = Vec::new();
+    for pool in &pools {
+        let Some(manager) = &pool.factory else {
+            continue;
+        };
+        let [(currency0, _, _), (currency1, _, _)] = pool.v4_currencies(&wrapped_native);
+        let factor0 = factors.get(&(currency0, manager.clone()));
+        let factor1 = factors.get(&(currency1, manager.clone()));
+        if factor0.is_none() && factor1.is_none() {
+            continue;
+        }
+
+        let reserve0 = factor0.map_or(pool.reserve0, |f| pool.reserve0 * *f);
+        let reserve1 = factor1.map_or(pool.reserve1, |f| pool.reserve1 * *f);
+        corrections.push(Correction::new(pool, reserve0, reserve1));
+    }
+
+    Ok(corrections)
+}
+
+/// Scale factor applied to every pool reserve of an over-counted (currency, manager).
+///
+/// `totals` holds the decimals and summed stored reserves of each currency, `balances`
+/// the PoolManager's raw balance of it. Currencies without a balance are left alone.
+fn scale_factors(
+    chain_id: u64,
+    totals: &HashMap<(String, String), (u8, D256)>,
+    balances: &HashMap<(String, String), U256>,
+) -> HashMap<(String, String), D256> {
+    let mut factors = HashMap::new();
+    for (key, (decimals, indexed)) in totals {
+        let Some(balance) = balances.get(key) else {
+            continue;
+        };
+        let onchain = u256_to_decimal(*balance, *decimals);
+        let drift = relative_drift(*indexed, onchain);
+        if drift <= V4_DRIFT_TOLERANCE {
+            continue;
+        }
+
+        info!(
+            "Chain {}: PoolManager {} holds {} of {}, pools account for {} ({:.2}% drift)",
+            chain_id,
+            key.1,
+            onchain,
+            key.0,
+            indexed,
+            drift * 100.0
+        );
+        if *indexed > onchain {
+            factors.insert(key.clone(), onchain / *indexed);
+        }
+    }
+
+    factors
+}
+
+/// Pools of `protocol_version`, last updated at or before `max_block`, deepest first.
+async fn load_pools(
+    db: &Database,
+    chain_id: i64,
+    protocol_version: &str,
+    max_block: u64,
+    limit: i64,
+) -> Result<Vec<StoredPool>> {
+    let pg = db.postgres.pool.get().await?;
+    let max_block = max_block.min(i64::MAX as u64) as i64;
+
+    let rows = pg
+        .query(
+            r#"
+        SELECT
+            p.address, p.token0, p.token1, p.token0_decimals, p.token1_decimals,
+            p.reserve0_adjusted::TEXT AS reserve0_adjusted,
+            p.reserve1_adjusted::TEXT AS reserve1_adjusted,
+            p.block_number, p.factory, p.initial_fee, p.tick_spacing, p.hook_address,
+            COALESCE(t0.price_usd, 0) AS token0_price_usd,
+            COALESCE(t1.price_usd, 0) AS token1_price_usd
+        FROM indexer.pools p
+        LEFT JOIN indexer.tokens t0 ON t0.chain_id = p.chain_id AND t0.address = p.token0
+        LEFT JOIN indexer.tokens t1 ON t1.chain_id = p.chain_id AND t1.address = p.token1
+        WHERE p.chain_id = $1 AND p.protocol_version = $2 AND p.block_number <= $3
+        ORDER BY p.tvl_usd DESC NULLS LAST
+        LIMIT $4
+        "#,
+            &[&chain_id, &protocol_version, &max_block, &limit],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| {
+            let reserve = |col: &str| {
+                row.get::<_, Option<String>>(col)
+                    .as_deref()
+                    .and_then(str_to_decimal)
+                    .unwrap_or(D256::ZERO)
+            };
+
+            StoredPool {
+                address: row.get::<_, String>("address").to_lowercase(),
+                token0: row.get::<_, String>("token0").to_lowercase(),
+                token1: row.get::<_, String>("token1").to_lowercase(),
+                token0_decimals: row.get::<_, i16>("token0_decimals") as u8,
+                token1_decimals: row.get::<_, i16>("token1_decimals") as u8,
+                reserve0: reserve("reserve0_adjusted"),
+                reserve1: reserve("reserve1_adjusted"),
+                block_number: row.get::<_, Option<i64>>("block_number").unwrap_or(0),
+                factory: row
+                    .get::<_, Option<String>>("factory")
+                    .map(|f| f.to_lowercase()),
+                initial_fee: row.get::<_, Option<i32>>("initial_fee").unwrap_or(0) as u32,
+                tick_spacing: row.get::<_, Option<i32>>("tick_spacing").unwrap_or(0),
+                hook_address: row
+                    .get::<_, Option<String>>("hook_address")
+                    .unwrap_or_else(|| ZERO_ADDRESS.to_string()),
+                token0_price_usd: row.get("token0_price_usd"),
+                token1_price_usd: row.get("token1_price_usd"),
+            }
+        })
+        .collect())
+}
+
+/// Write corrected reserves, skipping pools the worker updated since they were read.
+async fn save_corrections(
+    db: &Database,
+    chain_id: i64,
+    corrections: &[Correction],
+) -> Result<usize> {
+    let addresses: Vec<&str> = corrections.iter().map(|c| c.address.as_str()).collect();
+    let block_numbers: Vec<i64> = corrections.iter().map(|c| c.block_number).collect();
+    let reserves0: Vec<String> = corrections.iter().map(|c| c.reserve0.to_string()).collect();
+    let reserves1: Vec<String> = corrections.iter().map(|c| c.reserve1.to_string()).collect();
+    let drifts: Vec<f64> = corrections.iter().map(|c| c.drift_usd).collect();
+
+    let pg = db.postgres.pool.get().await?;
+    let stmt = "
+        UPDATE indexer.pools p
+        SET
+            reserve0_adjusted = data.reserve0::NUMERIC,
+            reserve1_adjusted = data.reserve1::NUMERIC,
+            tvl_usd = CASE WHEN p.tvl_usd > 0 THEN GREATEST(p.tvl_usd + data.drift_usd, 0) ELSE p.tvl_usd END,
+            updated_at = NOW()
+        FROM (
+            SELECT * FROM UNNEST(
+                $2::text[],
+                $3::bigint[],
+                $4::text[],
+                $5::text[],
+                $6::float8[]
+            ) AS t(address, block_number, reserve0, reserve1, drift_usd)
+        ) AS data
+        WHERE p.chain_id = $1 AND p.address = data.address AND p.block_number = data.block_number
+    ";
+
+    let updated = pg
+        .execute(
+            stmt,
+            &[&chain_id, &addresses, &block_numbers, &reserves0, &reserves1, &drifts],
+        )
+        .await?;
+
+    Ok(updated as usize)
+}
+
+/// |stored - onchain| relative to the larger of the two, 0 when both are zero
+fn relative_drift(stored: D256, onchain: D256) -> f64 {
+    let (stored, onchain) = (decimal_to_f64(stored), decimal_to_f64(onchain));
+    let scale = stored.abs().max(onchain.abs());
+    if scale > 0.0 {
+        (stored - onchain).abs() / scale
+    } else {
+        0.0
+    }
+}
+
+struct StoredPool {
+    address: String,
+    token0: String,
+    token1: String,
+    token0_decimals: u8,
+    token1_decimals: u8,
+    reserve0: D256,
+    reserve1: D256,
+    block_number: i64,
+    /// PoolManager for V4 pools
+    factory: Option<String>,
+    initial_fee: u32,
+    tick_spacing: i32,
+    hook_address: String,
+    token0_price_usd: f64,
+    token1_price_usd: f64,
+}
+
+impl StoredPool {
+    /// (currency, decimals, reserve) of both sides as the PoolManager holds them.
+    ///
+    /// Native currency is stored as the wrapped native token: the pool ID tells
+    /// whether the pool was created with the native currency (zero address).
+    fn v4_currencies(&self, wrapped_native: &str) -> [(String, u8, D256); 2] {
+        let currency = |token: &str, other: &str| {
+            if token == wrapped_native
+                && compute_v4_pool_id(
+                    ZERO_ADDRESS,
+                    other,
+                    self.initial_fee,
+                    self.tick_spacing,
+                    &self.hook_address,
+                ) == self.address
+            {
+                ZERO_ADDRESS.to_string()
+            } else {
+                token.to_string()
+            }
+        };
+
+        [
+            (
+                currency(&self.token0, &self.token1),
+                self.token0_decimals,
+                self.reserve0,
+            ),
+            (
+                currency(&self.token1, &self.token0),
+                self.token1_decimals,
+                self.reserve1,
+            ),
+        ]
+    }
+}
+
+struct Correction {
+    address: String,
+    block_number: i64,
+    reserve0: D256,
+    reserve1: D256,
+    /// USD value of the correction (positive when reserves were under-counted)
+    drift_usd: f64,
+}
+
+impl Correction {
+    fn new(pool: &StoredPool, reserve0: D256, reserve1: D256) -> Self {
+        let drift_usd = decimal_to_f64(reserve0 - pool.reserve0) * pool.token0_price_usd
+            + decimal_to_f64(reserve1 - pool.reserve1) * pool.token1_price_usd;
+
+        debug!(
+            "Pool {}: reserves {} / {} -> {} / {} (${:.2})",
+            pool.address, pool.reserve0, pool.reserve1, reserve0, reserve1, drift_usd
+        );
+
+        Self {
+            address: pool.address.clone(),
+            block_number: pool.block_number,
+            reserve0,
+            reserve1,
+            drift_usd,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WETH: &str = "0x4200000000000000000000000000000000000006";
+    const USDC: &str = "0x833589fcd6edb6e08f4c7c32d4f71b54bda02913";
+    const MANAGER: &str = "0x498581ff718922c3f8e6a244956af099b2652b2b";
+    const HOOKS: &str = "0x0000000000000000000000000000000000000000";
+
+    fn dec(value: &str) -> D256 {
+        str_to_decimal(value).unwrap()
+    }
+
+    fn v4_pool(address: String, reserve0: &str, reserve1: &str) -> StoredPool {
+        StoredPool {
+            address,
+            token0: WETH.to_string(),
+            token1: USDC.to_string(),
+            token0_decimals: 18,
+            token1_decimals: 6,
+            reserve0: dec(reserve0),
+            reserve1: dec(reserve1),
+            block_number: 100,
+            factory: Some(MANAGER.to_string()),
+            initial_fee: 500,
+            tick_spacing: 10,
+            hook_address: HOOKS.to_string(),
+            token0_price_usd: 3000.0,
+            token1_price_usd: 1.0,
+        }
+    }
+
+    #[test]
+    fn test_relative_drift() {
+        assert!((relative_drift(dec("101"), dec("100")) - 0.01 / 1.01).abs() < 1e-12);
+        assert!((relative_drift(dec("100"), dec("101")) - 0.01 / 1.01).abs() < 1e-12);
+        assert_eq!(relative_drift(dec("5"), dec("5")), 0.0);
+        // One side empty is a full drift, both empty is none
+        assert_eq!(relative_drift(D256::ZERO, dec("5")), 1.0);
+        assert_eq!(relative_drift(dec("5"), D256::ZERO), 1.0);
+        assert_eq!(relative_drift(D256::ZERO, D256::ZERO), 0.0);
+    }
+
+    #[test]
+    fn test_v4_currencies_of_a_native_pool() {
+        let address = compute_v4_pool_id(ZERO_ADDRESS, USDC, 500, 10, HOOKS);
+        let pool = v4_pool(address, "2", "6000");
+
+        let [(currency0, decimals0, reserve0), (currency1, decimals1, reserve1)] =
+            pool.v4_currencies(WETH);
+        assert_eq!(
+            (currency0.as_str(), decimals0, reserve0),
+            (ZERO_ADDRESS, 18, dec("2"))
+        );
+        assert_eq!(
+            (currency1.as_str(), decimals1, reserve1),
+            (USDC, 6, dec("6000"))
+        );
+    }
+
+    #[test]
+    fn test_v4_currencies_of_a_wrapped_native_pool() {
+        let address = compute_v4_pool_id(WETH, USDC, 500, 10, HOOKS);
+        let pool = v4_pool(address, "2", "6000");
+
+        let [(currency0, _, _), (currency1, _, _)] = pool.v4_currencies(WETH);
+        assert_eq!(currency0, WETH);
+        assert_eq!(currency1, USDC);
+    }
+
+    #[test]
+    fn test_scale_factors_use_currency_decimals() {
+        let native = (ZERO_ADDRESS.to_string(), MANAGER.to_string());
+        let usdc = (USDC.to_string(), MANAGER.to_string());
+        let totals =
+            HashMap::from([(native.clone(), (18, dec("4"))), (usdc.clone(), (6, dec("1000")))]);
+        // The manager holds 2 ETH (18 decimals) and 1000.5 USDC (6 decimals)
+        let balances = HashMap::from([
+            (native.clone(), U256::from(2_000_000_000_000_000_000u128)),
+            (usdc.clone(), U256::from(1_000_500_000u64)),
+        ]);
+
+        let factors = scale_factors(1, &totals, &balances);
+
+        // ETH is over-counted twice, USDC is within tolerance
+        assert_eq!(factors.len(), 1);
+        assert_eq!(factors[&native], dec("0.5"));
+    }
+
+    #[test]
+    fn test_scale_factors_with_zero_balances_and_reserves() {
+        let drained = (WETH.to_string(), MANAGER.to_string());
+        let empty = (USDC.to_string(), MANAGER.to_string());
+        let under = (ZERO_ADDRESS.to_string(), MANAGER.to_string());
+        let unread = ("0xdead".to_string(), MANAGER.to_string());
+        let totals = HashMap::from([
+            (drained.clone(), (18, dec("3"))),
+            (empty.clone(), (6, D256::ZERO)),
+            (under.clone(), (18, D256::ZERO)),
+            (unread.clone(), (18, dec("1"))),
+        ]);
+        let balances = HashMap::from([
+            (drained.clone(), U256::ZERO),
+            (empty.clone(), U256::ZERO),
+            (under.clone(), U256::from(1_000_000_000_000_000_000u128)),
+        ]);
+
+        let factors = scale_factors(1, &totals, &balances);
+
+        // An empty manager zeroes the reserves; both-zero, under-counted and
+        // unread currencies are left alone
+        assert_eq!(factors.len(), 1);
+        assert_eq!(factors[&drained], D256::ZERO);
+    }
+}
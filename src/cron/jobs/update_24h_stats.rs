@@ -1,6 +1,6 @@
 //! Job to update 24h statistics (volume, swaps, last_swap_at) in PostgreSQL.
 //!
-//! Aggregates data from ClickHouse events table and updates PostgreSQL pools/tokens tables.
+//! Aggregates data from the ClickHouse trades table and updates PostgreSQL pools/tokens tables.
 //! Only updates pools that have had activity in the last 24 hours.
 
 use anyhow::Result;
@@ -9,12 +9,57 @@ use log::info;
 
 use crate::db::Database;
 
+/// `mev_role` values whose volume is not organic: arbitrage legs and the
+/// attacker side of sandwiches. Sandwich victims are real users and stay in.
+const NON_ORGANIC_MEV_ROLES: &str = "('arbitrage', 'sandwich_frontrun', 'sandwich_backrun')";
+
+/// Swaps flagged by the `detect_wash_trading` job in the window
+const WASH_TRADES: &str = "(SELECT chain_id, tx_hash, log_index FROM indexer.wash_trades \
+     WHERE timestamp >= now() - INTERVAL 24 HOUR)";
+
+/// Trades of the last 24 hours, deduplicated with FINAL.
+///
+/// A trade is organic when none of its hops is an MEV leg, and wash when any hop
+/// is flagged. Hops are matched to their trade by the trade's log index range,
+/// using the non-organic and flagged log indexes collected per transaction.
+fn trades_24h() -> String {
+    format!(
+        r#"
+        SELECT
+            t.chain_id as chain_id, t.route as route, t.token_in as token_in,
+            t.token_out as token_out, t.volume_usd as volume_usd, t.timestamp as timestamp,
+            NOT arrayExists(
+                i -> i BETWEEN t.first_log_index AND t.last_log_index, f.mev_hops
+            ) as organic,
+            arrayExists(
+                i -> i BETWEEN t.first_log_index AND t.last_log_index, f.wash_hops
+            ) as wash
+        FROM indexer.trades AS t FINAL
+        LEFT JOIN (
+            SELECT
+                chain_id, tx_hash,
+                groupArrayIf(log_index, mev_role IN {NON_ORGANIC_MEV_ROLES}) as mev_hops,
+                groupArrayIf(log_index, (chain_id, tx_hash, log_index) IN {WASH_TRADES})
+                    as wash_hops
+            FROM indexer.events
+            WHERE timestamp >= now() - INTERVAL 24 HOUR AND event_type = 'swap'
+            GROUP BY chain_id, tx_hash
+            HAVING notEmpty(mev_hops) OR notEmpty(wash_hops)
+        ) AS f ON f.chain_id = t.chain_id AND f.tx_hash = t.tx_hash
+        WHERE t.timestamp >= now() - INTERVAL 24 HOUR
+    "#
+    )
+}
+
 /// Updates 24h statistics for pools and tokens.
 ///
-/// Aggregates from ClickHouse:
+/// Aggregates from ClickHouse trades, so a routed trade is counted once per pool
+/// it crossed instead of once per hop:
 /// - volume_24h: sum(volume_usd) for last 24 hours
-/// - swaps_24h: count() of swaps for last 24 hours
-/// - last_swap_at: max(timestamp) for swaps
+/// - organic_volume_24h: volume_24h without MEV trades (see `NON_ORGANIC_MEV_ROLES`)
+/// - adjusted_volume_24h: organic_volume_24h without flagged wash trades
+/// - swaps_24h: count() of trades for last 24 hours
+/// - last_swap_at: max(timestamp) for trades
 ///
 /// Only updates pools/tokens that had activity in the window.
 pub async fn run(db: &Database) -> Result<()> {
@@ -38,25 +83,33 @@ pub async fn run(db: &Database) -> Result<()> {
 }
 
 async fn update_pool_stats(db: &Database) -> Result<usize> {
-    // Query ClickHouse for 24h pool aggregates
+    // Query ClickHouse for 24h pool aggregates, each trade counted once per pool on its route
     // Only returns pools that had swap activity in the last 24 hours
-    let query = r#"
+    let trades = trades_24h();
+    let query = format!(
+        r#"
         SELECT 
             chain_id,
             pool_address,
             sum(volume_usd) as volume_24h,
+            sumIf(volume_usd, organic) as organic_volume_24h,
+            sumIf(volume_usd, organic AND NOT wash) as adjusted_volume_24h,
             count() as swaps_24h,
             max(timestamp) as last_swap_at
-        FROM indexer.events
-        WHERE timestamp >= now() - INTERVAL 24 HOUR 
-          AND event_type = 'swap'
+        FROM (
+            SELECT
+                chain_id, arrayJoin(arrayDistinct(route)) as pool_address, volume_usd,
+                timestamp, organic, wash
+            FROM ({trades})
+        )
         GROUP BY chain_id, pool_address
-    "#;
+    "#
+    );
 
     let rows = db
         .clickhouse
         .client
-        .query(query)
+        .query(&query)
         .fetch_all::<PoolStats24h>()
         .await?;
 
@@ -72,6 +125,8 @@ async fn update_pool_stats(db: &Database) -> Result<usize> {
     let mut chain_ids = Vec::with_capacity(rows.len());
     let mut addresses = Vec::with_capacity(rows.len());
     let mut volumes = Vec::with_capacity(rows.len());
+    let mut organic_volumes = Vec::with_capacity(rows.len());
+    let mut adjusted_volumes = Vec::with_capacity(rows.len());
     let mut swaps = Vec::with_capacity(rows.len());
     let mut last_swaps = Vec::with_capacity(rows.len());
 
@@ -79,6 +134,8 @@ async fn update_pool_stats(db: &Database) -> Result<usize> {
         chain_ids.push(row.chain_id as i64);
         addresses.push(row.pool_address.clone());
         volumes.push(row.volume_24h);
+        organic_volumes.push(row.organic_volume_24h);
+        adjusted_volumes.push(row.adjusted_volume_24h);
         swaps.push(row.swaps_24h as i64);
 
         let last_swap_chrono: DateTime<Utc> = DateTime::from_timestamp(
@@ -93,6 +150,8 @@ async fn update_pool_stats(db: &Database) -> Result<usize> {
         UPDATE indexer.pools p
         SET 
             volume_24h = data.volume_24h,
+            organic_volume_24h = data.organic_volume_24h,
+            adjusted_volume_24h = data.adjusted_volume_24h,
             swaps_24h = data.swaps_24h,
             last_swap_at = data.last_swap_at,
             updated_at = NOW()
@@ -101,16 +160,29 @@ async fn update_pool_stats(db: &Database) -> Result<usize> {
                 $1::bigint[], 
                 $2::text[], 
                 $3::float8[], 
-                $4::bigint[], 
-                $5::timestamptz[]
-            ) AS t(chain_id, address, volume_24h, swaps_24h, last_swap_at)
+                $4::float8[], 
+                $5::float8[], 
+                $6::bigint[], 
+                $7::timestamptz[]
+            ) AS t(
+                chain_id, address, volume_24h, organic_volume_24h, adjusted_volume_24h,
+                swaps_24h, last_swap_at
+            )
         ) AS data
         WHERE p.chain_id = data.chain_id AND p.address = data.address
     ";
 
     pg.execute(
         stmt,
-        &[&chain_ids, &addresses, &volumes, &swaps, &last_swaps],
+        &[
+            &chain_ids,
+            &addresses,
+            &volumes,
+            &organic_volumes,
+            &adjusted_volumes,
+            &swaps,
+            &last_swaps,
+        ],
     )
     .await?;
 
@@ -119,30 +191,32 @@ async fn update_pool_stats(db: &Database) -> Result<usize> {
 }
 
 async fn update_token_stats(db: &Database) -> Result<usize> {
-    // Query ClickHouse for 24h token aggregates (by token0 and token1)
+    // Query ClickHouse for 24h token aggregates (by token_in and token_out)
     // Only returns tokens that had swap activity in the last 24 hours
-    let query = r#"
+    let trades = trades_24h();
+    let query = format!(
+        r#"
         SELECT 
             chain_id,
             token_address,
             sum(volume_usd) as volume_24h,
+            sumIf(volume_usd, organic) as organic_volume_24h,
+            sumIf(volume_usd, organic AND NOT wash) as adjusted_volume_24h,
             count() as swaps_24h
         FROM (
-            SELECT chain_id, token0 as token_address, volume_usd / 2 as volume_usd
-            FROM indexer.events
-            WHERE timestamp >= now() - INTERVAL 24 HOUR AND event_type = 'swap'
-            UNION ALL
-            SELECT chain_id, token1 as token_address, volume_usd / 2 as volume_usd
-            FROM indexer.events
-            WHERE timestamp >= now() - INTERVAL 24 HOUR AND event_type = 'swap'
+            SELECT
+                chain_id, arrayJoin([token_in, token_out]) as token_address,
+                volume_usd / 2 as volume_usd, organic, wash
+            FROM ({trades})
         )
         GROUP BY chain_id, token_address
-    "#;
+    "#
+    );
 
     let rows = db
         .clickhouse
         .client
-        .query(query)
+        .query(&query)
         .fetch_all::<TokenStats24h>()
         .await?;
 
@@ -158,12 +232,16 @@ async fn update_token_stats(db: &Database) -> Result<usize> {
     let mut chain_ids = Vec::with_capacity(rows.len());
     let mut addresses = Vec::with_capacity(rows.len());
     let mut volumes = Vec::with_capacity(rows.len());
+    let mut organic_volumes = Vec::with_capacity(rows.len());
+    let mut adjusted_volumes = Vec::with_capacity(rows.len());
     let mut swaps = Vec::with_capacity(rows.len());
 
     for row in &rows {
         chain_ids.push(row.chain_id as i64);
         addresses.push(row.token_address.clone());
         volumes.push(row.volume_24h);
+        organic_volumes.push(row.organic_volume_24h);
+        adjusted_volumes.push(row.adjusted_volume_24h);
         swaps.push(row.swaps_24h as i64);
     }
 
@@ -171,6 +249,8 @@ async fn update_token_stats(db: &Database) -> Result<usize> {
         UPDATE indexer.tokens t
         SET 
             volume_24h = data.volume_24h,
+            organic_volume_24h = data.organic_volume_24h,
+            adjusted_volume_24h = data.adjusted_volume_24h,
             swaps_24h = data.swaps_24h,
             updated_at = NOW()
         FROM (
@@ -178,14 +258,19 @@ async fn update_token_stats(db: &Database) -> Result<usize> {
                 $1::bigint[], 
                 $2::text[], 
                 $3::float8[], 
-                $4::bigint[]
-            ) AS t(chain_id, address, volume_24h, swaps_24h)
+                $4::float8[], 
+                $5::float8[], 
+                $6::bigint[]
+            ) AS t(chain_id, address, volume_24h, organic_volume_24h, adjusted_volume_24h, swaps_24h)
         ) AS data
         WHERE t.chain_id = data.chain_id AND t.address = data.address
     ";
 
-    pg.execute(stmt, &[&chain_ids, &addresses, &volumes, &swaps])
-        .await?;
+    pg.execute(
+        stmt,
+        &[&chain_ids, &addresses, &volumes, &organic_volumes, &adjusted_volumes, &swaps],
+    )
+    .await?;
 
     info!("Updated 24h stats for {} tokens (had activity)", rows.len());
     Ok(rows.len())
@@ -196,6 +281,8 @@ struct PoolStats24h {
     chain_id: u64,
     pool_address: String,
     volume_24h: f64,
+    organic_volume_24h: f64,
+    adjusted_volume_24h: f64,
     swaps_24h: u64,
     #[serde(with = "clickhouse::serde::time::datetime")]
     last_swap_at: time::OffsetDateTime,
@@ -206,5 +293,7 @@ struct TokenStats24h {
     chain_id: u64,
     token_address: String,
     volume_24h: f64,
+    organic_volume_24h: f64,
+    adjusted_volume_24h: f64,
     swaps_24h: u64,
 }
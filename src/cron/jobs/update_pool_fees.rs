@@ -0,0 +1,163 @@
+//! Job to update pool fee revenue and fee APR in PostgreSQL.
+//!
+//! Aggregates `fees_usd` of swaps from the ClickHouse events table and the average
+//! TVL from `pool_snapshots`, then updates the PostgreSQL pools table.
+//!
+//! `fees_usd` is computed per swap from the fee the swap paid: for V4 dynamic-fee
+//! pools that is the fee of the Swap event (the one `Pool::update_v4_fee` records),
+//! not the pool's current fee, so fee changes between swaps are accounted exactly.
+
+use anyhow::Result;
+use log::info;
+
+use crate::db::Database;
+
+/// Window of `fees_7d` and of the TVL average used for the APR
+const APR_WINDOW_DAYS: u32 = 7;
+
+/// Updates fee metrics for pools.
+///
+/// Aggregates from ClickHouse:
+/// - fees_24h: sum(fees_usd) for the last 24 hours
+/// - fees_7d: sum(fees_usd) for the last 7 days
+/// - avg_tvl_usd: avg(tvl_usd) of the pool snapshots over the last 7 days
+///
+/// and sets `fee_apr = fees / avg TVL * 365 / days * 100`, where `days` is the part
+/// of the window the pool has snapshots for (at least 1), so young pools are not
+/// diluted over a full week. Pools without snapshots fall back to the current `tvl_usd`.
+///
+/// Suspicious swaps (volume out of proportion to TVL) are left out. Pools that no
+/// longer had fees in the window are reset to 0.
+pub async fn run(db: &Database) -> Result<()> {
+    info!("Starting update_pool_fees job...");
+
+    let start = std::time::Instant::now();
+
+    let query = format!(
+        r#"
+        SELECT
+            f.chain_id AS chain_id,
+            f.pool_address AS pool_address,
+            f.fees_24h AS fees_24h,
+            f.fees_7d AS fees_7d,
+            t.avg_tvl_usd AS avg_tvl_usd,
+            least(
+                {APR_WINDOW_DAYS},
+                greatest(1, dateDiff('second', t.first_snapshot, now()) / 86400)
+            ) AS window_days
+        FROM (
+            SELECT
+                chain_id,
+                pool_address,
+                sumIf(fees_usd, timestamp >= now() - INTERVAL 24 HOUR) AS fees_24h,
+                sum(fees_usd) AS fees_7d
+            FROM indexer.events
+            WHERE timestamp >= now() - INTERVAL {APR_WINDOW_DAYS} DAY
+              AND event_type = 'swap'
+              AND is_suspicious = 0
+              AND fees_usd > 0
+            GROUP BY chain_id, pool_address
+        ) AS f
+        LEFT JOIN (
+            SELECT
+                chain_id,
+                pool_address,
+                avg(tvl_usd) AS avg_tvl_usd,
+                min(time) AS first_snapshot
+            FROM indexer.pool_snapshots
+            WHERE time >= now() - INTERVAL {APR_WINDOW_DAYS} DAY AND tvl_usd > 0
+            GROUP BY chain_id, pool_address
+        ) AS t ON f.chain_id = t.chain_id AND f.pool_address = t.pool_address
+    "#
+    );
+
+    let rows = db
+        .clickhouse
+        .client
+        .query(&query)
+        .fetch_all::<PoolFees>()
+        .await?;
+
+    let mut chain_ids = Vec::with_capacity(rows.len());
+    let mut addresses = Vec::with_capacity(rows.len());
+    let mut fees_24h = Vec::with_capacity(rows.len());
+    let mut fees_7d = Vec::with_capacity(rows.len());
+    let mut avg_tvls = Vec::with_capacity(rows.len());
+    let mut window_days = Vec::with_capacity(rows.len());
+
+    for row in &rows {
+        chain_ids.push(row.chain_id as i64);
+        addresses.push(row.pool_address.clone());
+        fees_24h.push(row.fees_24h);
+        fees_7d.push(row.fees_7d);
+        avg_tvls.push(row.avg_tvl_usd);
+        window_days.push(row.window_days);
+    }
+
+    let pg = db.postgres.pool.get().await?;
+
+    // Batch update PostgreSQL using UNNEST
+    let stmt = "
+        UPDATE indexer.pools p
+        SET
+            fees_24h = data.fees_24h,
+            fees_7d = data.fees_7d,
+            fee_apr = CASE
+                WHEN COALESCE(NULLIF(data.avg_tvl_usd, 0), p.tvl_usd) > 0
+                THEN data.fees_7d / COALESCE(NULLIF(data.avg_tvl_usd, 0), p.tvl_usd)
+                     * 365 / data.window_days * 100
+            END,
+            updated_at = NOW()
+        FROM (
+            SELECT * FROM UNNEST(
+                $1::bigint[],
+                $2::text[],
+                $3::float8[],
+                $4::float8[],
+                $5::float8[],
+                $6::float8[]
+            ) AS t(chain_id, address, fees_24h, fees_7d, avg_tvl_usd, window_days)
+        ) AS data
+        WHERE p.chain_id = data.chain_id AND p.address = data.address
+    ";
+
+    pg.execute(
+        stmt,
+        &[&chain_ids, &addresses, &fees_24h, &fees_7d, &avg_tvls, &window_days],
+    )
+    .await?;
+
+    // Pools that earned fees before but none in the window
+    let reset = pg
+        .execute(
+            "
+        UPDATE indexer.pools p
+        SET fees_24h = 0, fees_7d = 0, fee_apr = 0, updated_at = NOW()
+        WHERE p.fees_7d > 0
+          AND NOT EXISTS (
+              SELECT 1 FROM UNNEST($1::bigint[], $2::text[]) AS t(chain_id, address)
+              WHERE t.chain_id = p.chain_id AND t.address = p.address
+          )
+        ",
+            &[&chain_ids, &addresses],
+        )
+        .await?;
+
+    info!(
+        "Completed update_pool_fees job in {:?} ({} pools with fees, {} reset)",
+        start.elapsed(),
+        rows.len(),
+        reset
+    );
+    Ok(())
+}
+
+#[derive(Debug, clickhouse::Row, serde::Deserialize)]
+struct PoolFees {
+    chain_id: u64,
+    pool_address: String,
+    fees_24h: f64,
+    fees_7d: f64,
+    avg_tvl_usd: f64,
+    window_days: f64,
+}
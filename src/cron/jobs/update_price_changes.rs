@@ -129,8 +129,8 @@ async fn update_pool_price_changes(db: &Database) -> Result<usize> {
 }
 
 async fn update_token_price_changes(db: &Database) -> Result<usize> {
-    // Query ClickHouse for token price changes and market cap
-    // Calculates circulating_supply from token_supplies (mints - burns)
+    // Query ClickHouse for token price changes
+    // circulating_supply / market_cap_usd are owned by update_token_supplies
     let query = r#"
         WITH current_prices AS (
             SELECT 
@@ -159,26 +159,15 @@ async fn update_token_price_changes(db: &Database) -> Result<usize> {
             WHERE time >= now() - INTERVAL 169 HOUR 
               AND time <= now() - INTERVAL 167 HOUR
             ORDER BY time DESC LIMIT 1 BY chain_id, token_address
-        ),
-        supplies AS (
-             SELECT
-                chain_id,
-                token_address,
-                sum(total_supply) as circulating_supply
-             FROM indexer.token_supplies
-             GROUP BY chain_id, token_address
         )
         SELECT 
             c.chain_id,
             c.token_address,
             if(p24.price_24h_ago > 0, (c.current_price - p24.price_24h_ago) / p24.price_24h_ago * 100, 0) as price_change_24h,
-            if(p7d.price_7d_ago > 0, (c.current_price - p7d.price_7d_ago) / p7d.price_7d_ago * 100, 0) as price_change_7d,
-            COALESCE(s.circulating_supply, 0) as circulating_supply,
-            (circulating_supply * c.current_price) as market_cap_usd
+            if(p7d.price_7d_ago > 0, (c.current_price - p7d.price_7d_ago) / p7d.price_7d_ago * 100, 0) as price_change_7d
         FROM current_prices c
         LEFT JOIN prices_24h p24 ON c.chain_id = p24.chain_id AND c.token_address = p24.token_address
         LEFT JOIN prices_7d p7d ON c.chain_id = p7d.chain_id AND c.token_address = p7d.token_address
-        LEFT JOIN supplies s ON c.chain_id = s.chain_id AND c.token_address = s.token_address
         WHERE c.current_price > 0
     "#;
 
@@ -203,8 +192,6 @@ async fn update_token_price_changes(db: &Database) -> Result<usize> {
          SET 
             price_change_24h = $3, 
             price_change_7d = $4, 
-            circulating_supply = $5,
-            market_cap_usd = $6,
             updated_at = NOW()
          WHERE chain_id = $1 AND address = $2",
         )
@@ -220,8 +207,6 @@ async fn update_token_price_changes(db: &Database) -> Result<usize> {
                     &row.token_address,
                     &row.price_change_24h,
                     &row.price_change_7d,
-                    &row.circulating_supply,
-                    &row.market_cap_usd,
                 ],
             )
             .await;
@@ -231,7 +216,7 @@ async fn update_token_price_changes(db: &Database) -> Result<usize> {
         }
     }
 
-    info!("Updated price/market caps for {} tokens", updated);
+    info!("Updated price changes for {} tokens", updated);
     Ok(updated)
 }
 
@@ -251,6 +236,4 @@ struct TokenPriceChange {
     token_address: String,
     price_change_24h: f64,
     price_change_7d: f64,
-    circulating_supply: f64,
-    market_cap_usd: f64,
 }
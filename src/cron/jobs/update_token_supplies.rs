@@ -0,0 +1,209 @@
+//! Job to update circulating supply and market cap for priced tokens.
+//!
+//! Fetches `totalSupply()` and the balances of each chain's excluded holders via multicall,
+//! reconciles the on-chain figure with the supply accumulated from `supply_events`,
+//! and writes `circulating_supply` / `market_cap_usd` back to PostgreSQL.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::Result;
+use log::{debug, info, warn};
+
+use crate::db::models::DatabaseChain;
+use crate::db::Database;
+use crate::utils::{u256_to_f64, ZERO_ADDRESS};
+use crate::worker::TokenFetcher;
+
+/// Burn address excluded from circulating supply on every chain
+const DEAD_ADDRESS: &str = "0x000000000000000000000000000000000000dead";
+
+/// Relative difference between on-chain and indexed supply above which drift is reported
+const SUPPLY_DRIFT_TOLERANCE: f64 = 0.01;
+
+/// Updates circulating supply and market cap for all priced tokens on enabled chains.
+///
+/// For each token:
+/// - circulating_supply: totalSupply() minus balances of excluded holders
+/// - market_cap_usd: circulating_supply * price_usd
+///
+/// When `totalSupply()` cannot be read, the supply accumulated from indexed
+/// mint/burn events is used instead.
+pub async fn run(db: &Arc<Database>) -> Result<()> {
+    info!("Starting update_token_supplies job...");
+
+    let start = std::time::Instant::now();
+    let mut token_count = 0;
+
+    for chain in db.postgres.get_chains().await? {
+        if !chain.enabled {
+            continue;
+        }
+
+        match update_chain_supplies(db, &chain).await {
+            Ok(count) => token_count += count,
+            Err(e) => warn!(
+                "Failed to update token supplies for chain {}: {:#}",
+                chain.chain_id, e
+            ),
+        }
+    }
+
+    info!(
+        "Completed update_token_supplies job in {:?} ({} tokens)",
+        start.elapsed(),
+        token_count
+    );
+    Ok(())
+}
+
+async fn update_chain_supplies(db: &Arc<Database>, chain: &DatabaseChain) -> Result<usize> {
+    let chain_id = chain.chain_id as i64;
+    let pg = db.postgres.pool.get().await?;
+
+    // Only priced tokens have a meaningful market cap
+    let rows = pg
+        .query(
+            r#"
+        SELECT address, decimals, price_usd
+        FROM indexer.tokens
+        WHERE chain_id = $1 AND price_usd > 0
+        "#,
+            &[&chain_id],
+        )
+        .await?;
+
+    if rows.is_empty() {
+        return Ok(0);
+    }
+
+    let tokens: Vec<(String, u8, f64)> = rows
+        .iter()
+        .map(|row| {
+            let decimals: i16 = row.get("decimals");
+            (row.get("address"), decimals as u8, row.get("price_usd"))
+        })
+        .collect();
+
+    let mut excluded_holders = vec![ZERO_ADDRESS.to_string(), DEAD_ADDRESS.to_string()];
+    for holder in &chain.supply_excluded_addresses {
+        if !excluded_holders.contains(holder) {
+            excluded_holders.push(holder.clone());
+        }
+    }
+
+    let addresses: Vec<String> = tokens.iter().map(|(addr, _, _)| addr.clone()).collect();
+    let fetcher = TokenFetcher::new(chain.rpc_url.clone(), chain_id, db.clone());
+    let onchain = fetcher.fetch_supplies(&addresses, &excluded_holders).await;
+
+    // Supply accumulated from indexed mint/burn events (already decimal-adjusted)
+    let indexed: HashMap<String, f64> = db
+        .clickhouse
+        .client
+        .query(
+            r#"
+        SELECT token_address, sum(total_supply) AS total_supply
+        FROM indexer.token_supplies
+        WHERE chain_id = ?
+        GROUP BY token_address
+        "#,
+        )
+        .bind(chain.chain_id)
+        .fetch_all::<IndexedSupply>()
+        .await?
+        .into_iter()
+        .map(|row| (row.token_address, row.total_supply))
+        .collect();
+
+    let mut chain_ids = Vec::with_capacity(tokens.len());
+    let mut token_addresses = Vec::with_capacity(tokens.len());
+    let mut circulating_supplies = Vec::with_capacity(tokens.len());
+    let mut market_caps = Vec::with_capacity(tokens.len());
+    let mut drifted = 0;
+
+    for (address, decimals, price_usd) in &tokens {
+        let indexed_supply = indexed.get(address).copied().filter(|s| *s > 0.0);
+
+        let circulating = match onchain.get(address) {
+            Some(supply) => {
+                let total = u256_to_f64(supply.total_supply, *decimals);
+
+                if let Some(indexed_supply) = indexed_supply {
+                    let drift = (total - indexed_supply).abs() / total.max(indexed_supply);
+                    if drift > SUPPLY_DRIFT_TOLERANCE {
+                        drifted += 1;
+                        debug!(
+                            "Chain {}: Supply drift for {}: on-chain {} vs indexed {} ({:.2}%)",
+                            chain.chain_id,
+                            address,
+                            total,
+                            indexed_supply,
+                            drift * 100.0
+                        );
+                    }
+                }
+
+                u256_to_f64(supply.circulating_supply(), *decimals)
+            },
+            // Fall back to indexed mint/burn totals when totalSupply() is unavailable
+            None => match indexed_supply {
+                Some(indexed_supply) => indexed_supply,
+                None => continue,
+            },
+        };
+
+        chain_ids.push(chain_id);
+        token_addresses.push(address.clone());
+        circulating_supplies.push(circulating);
+        market_caps.push(circulating * price_usd);
+    }
+
+    if drifted > 0 {
+        warn!(
+            "Chain {}: {} tokens have on-chain supply diverging from indexed supply events by more than {}%",
+            chain.chain_id,
+            drifted,
+            SUPPLY_DRIFT_TOLERANCE * 100.0
+        );
+    }
+
+    if token_addresses.is_empty() {
+        return Ok(0);
+    }
+
+    let stmt = "
+        UPDATE indexer.tokens t
+        SET
+            circulating_supply = data.circulating_supply,
+            market_cap_usd = data.market_cap_usd,
+            updated_at = NOW()
+        FROM (
+            SELECT * FROM UNNEST(
+                $1::bigint[],
+                $2::text[],
+                $3::float8[],
+                $4::float8[]
+            ) AS t(chain_id, address, circulating_supply, market_cap_usd)
+        ) AS data
+        WHERE t.chain_id = data.chain_id AND t.address = data.address
+    ";
+
+    pg.execute(
+        stmt,
+        &[&chain_ids, &token_addresses, &circulating_supplies, &market_caps],
+    )
+    .await?;
+
+    info!(
+        "Chain {}: Updated supply for {} tokens",
+        chain.chain_id,
+        token_addresses.len()
+    );
+    Ok(token_addresses.len())
+}
+
+#[derive(Debug, clickhouse::Row, serde::Deserialize)]
+struct IndexedSupply {
+    token_address: String,
+    total_supply: f64,
+}
@@ -0,0 +1,310 @@
+//! Job to maintain per-wallet, per-token positions and PnL in ClickHouse.
+//!
+//! Replays swaps indexed since the last run into `indexer.trader_positions`
+//! (average-cost accounting), then re-marks open positions to the current
+//! PostgreSQL `tokens.price_usd`.
+//!
+//! The checkpoint is the highest block replayed. Swaps inserted below it later by
+//! `runic reindex` are unpriced, so they would be skipped anyway.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+use log::{info, warn};
+use time::OffsetDateTime;
+
+use crate::db::models::{DatabaseChain, TraderPosition};
+use crate::db::Database;
+use crate::utils::ZERO_ADDRESS;
+
+/// Maximum swaps replayed per chain and run (the rest is picked up next run)
+const MAX_SWAPS_PER_RUN: u64 = 1_000_000;
+/// Only replay swaps at least this old, so blocks still being inserted are not split
+const SETTLE_DELAY_SECS: u64 = 300;
+/// Wallets per positions lookup
+const WALLET_CHUNK: usize = 1_000;
+/// Tokens per re-marking statement
+const MARK_CHUNK: usize = 5_000;
+
+/// Updates trader positions for all enabled chains.
+///
+/// Per swap, the wallet (`tx_from`, or `maker` when unknown):
+/// - buys the token it received, at a cost of the swap's `volume_usd`
+/// - sells the token it paid, for proceeds of `volume_usd`, realizing PnL at average cost
+///
+/// Unpriced swaps (`volume_usd` = 0) are skipped: they have no cost basis.
+pub async fn run(db: &Arc<Database>) -> Result<()> {
+    info!("Starting update_trader_positions job...");
+
+    let start = std::time::Instant::now();
+    let mut swap_count = 0;
+
+    for chain in db.postgres.get_chains().await? {
+        if !chain.enabled {
+            continue;
+        }
+
+        match update_chain_positions(db, &chain).await {
+            Ok(count) => swap_count += count,
+            Err(e) => warn!(
+                "Failed to update trader positions for chain {}: {:#}",
+                chain.chain_id, e
+            ),
+        }
+    }
+
+    info!(
+        "Completed update_trader_positions job in {:?} ({} swaps replayed)",
+        start.elapsed(),
+        swap_count
+    );
+    Ok(())
+}
+
+async fn update_chain_positions(db: &Arc<Database>, chain: &DatabaseChain) -> Result<usize> {
+    let client = &db.clickhouse.client;
+    let now = OffsetDateTime::now_utc();
+
+    // Positions record the block of their last swap, so the highest one is the checkpoint
+    let checkpoint: u64 = client
+        .query(
+            "SELECT max(last_block_number) FROM indexer.trader_positions FINAL WHERE chain_id = ?",
+        )
+        .bind(chain.chain_id)
+        .fetch_one()
+        .await?;
+
+    let mut swaps = client
+        .query(
+            r#"
+        SELECT
+            block_number, timestamp,
+            if(tx_from != '', tx_from, maker) AS wallet,
+            token0, token1, amount0_adjusted, amount1_adjusted, amount0_direction, volume_usd
+        FROM indexer.events
+        WHERE chain_id = ?
+          AND event_type = 'swap'
+          AND block_number > ?
+          AND timestamp <= now() - INTERVAL ? SECOND
+          AND volume_usd > 0
+        ORDER BY block_number, log_index
+        LIMIT ?
+        "#,
+        )
+        .bind(chain.chain_id)
+        .bind(checkpoint)
+        .bind(SETTLE_DELAY_SECS)
+        .bind(MAX_SWAPS_PER_RUN)
+        .fetch_all::<PositionSwap>()
+        .await?;
+
+    // A truncated read may end mid-block: leave that block for the next run
+    if swaps.len() as u64 == MAX_SWAPS_PER_RUN {
+        let last_block = swaps.last().map(|s| s.block_number).unwrap_or_default();
+        if swaps.first().map(|s| s.block_number) != Some(last_block) {
+            swaps.retain(|s| s.block_number < last_block);
+        }
+    }
+    swaps.retain(|s| !s.wallet.is_empty() && s.wallet != ZERO_ADDRESS);
+
+    let prices = token_prices(db, chain.chain_id as i64).await?;
+
+    if !swaps.is_empty() {
+        let mut positions = load_positions(db, chain.chain_id, &swaps).await?;
+        apply_swaps(chain.chain_id, &mut positions, &swaps);
+        write_positions(db, positions, &prices, now).await?;
+    }
+
+    mark_open_positions(db, chain.chain_id, &prices).await?;
+
+    Ok(swaps.len())
+}
+
+/// Applies `swaps`, in order, to `positions` keyed by (wallet, token).
+fn apply_swaps(
+    chain_id: u64,
+    positions: &mut HashMap<(String, String), TraderPosition>,
+    swaps: &[PositionSwap],
+) {
+    for swap in swaps {
+        let (bought, sold, bought_amount, sold_amount) = match swap.amount0_direction {
+            1 => (
+                &swap.token0,
+                &swap.token1,
+                swap.amount0_adjusted,
+                swap.amount1_adjusted,
+            ),
+            -1 => (
+                &swap.token1,
+                &swap.token0,
+                swap.amount1_adjusted,
+                swap.amount0_adjusted,
+            ),
+            _ => continue,
+        };
+
+        positions
+            .entry((swap.wallet.clone(), bought.clone()))
+            .or_insert_with(|| {
+                TraderPosition::new(
+                    chain_id,
+                    swap.wallet.clone(),
+                    bought.clone(),
+                    swap.timestamp,
+                )
+            })
+            .apply_buy(
+                bought_amount,
+                swap.volume_usd,
+                swap.timestamp,
+                swap.block_number,
+            );
+
+        positions
+            .entry((swap.wallet.clone(), sold.clone()))
+            .or_insert_with(|| {
+                TraderPosition::new(chain_id, swap.wallet.clone(), sold.clone(), swap.timestamp)
+            })
+            .apply_sell(
+                sold_amount,
+                swap.volume_usd,
+                swap.timestamp,
+                swap.block_number,
+            );
+    }
+}
+
+/// Marks `positions` to `prices` and inserts them as new row versions.
+async fn write_positions(
+    db: &Database,
+    mut positions: HashMap<(String, String), TraderPosition>,
+    prices: &HashMap<String, f64>,
+    now: OffsetDateTime,
+) -> Result<()> {
+    if positions.is_empty() {
+        return Ok(());
+    }
+
+    let mut insert = db
+        .clickhouse
+        .client
+        .insert::<TraderPosition>("trader_positions")
+        .await
+        .context("Failed to start trader_positions insert")?;
+    for position in positions.values_mut() {
+        position.mark(prices.get(&position.token_address).copied().unwrap_or(0.0));
+        position.updated_at = now;
+        insert.write(position).await?;
+    }
+    insert
+        .end()
+        .await
+        .context("Failed to write trader positions")
+}
+
+/// Current positions of the wallets in `swaps`, keyed by (wallet, token).
+async fn load_positions(
+    db: &Database,
+    chain_id: u64,
+    swaps: &[PositionSwap],
+) -> Result<HashMap<(String, String), TraderPosition>> {
+    let mut wallets: Vec<&str> = swaps.iter().map(|s| s.wallet.as_str()).collect();
+    wallets.sort_unstable();
+    wallets.dedup();
+
+    let mut positions = HashMap::new();
+    for chunk in wallets.chunks(WALLET_CHUNK) {
+        let rows = db
+            .clickhouse
+            .client
+            .query(
+                "SELECT ?fields FROM indexer.trader_positions FINAL WHERE chain_id = ? AND wallet IN ?",
+            )
+            .bind(chain_id)
+            .bind(chunk)
+            .fetch_all::<TraderPosition>()
+            .await?;
+
+        for position in rows {
+            positions.insert(
+                (position.wallet.clone(), position.token_address.clone()),
+                position,
+            );
+        }
+    }
+
+    Ok(positions)
+}
+
+/// Priced tokens of the chain from PostgreSQL.
+async fn token_prices(db: &Database, chain_id: i64) -> Result<HashMap<String, f64>> {
+    let pg = db.postgres.pool.get().await?;
+    let rows = pg
+        .query(
+            "SELECT address, price_usd FROM indexer.tokens WHERE chain_id = $1 AND price_usd > 0",
+            &[&chain_id],
+        )
+        .await?;
+
+    Ok(rows
+        .iter()
+        .map(|row| (row.get("address"), row.get("price_usd")))
+        .collect())
+}
+
+/// Re-mark open positions whose token price moved since their last refresh.
+async fn mark_open_positions(
+    db: &Database,
+    chain_id: u64,
+    prices: &HashMap<String, f64>,
+) -> Result<()> {
+    let prices: Vec<(&String, &f64)> = prices.iter().collect();
+
+    for chunk in prices.chunks(MARK_CHUNK) {
+        let tokens: Vec<&str> = chunk.iter().map(|(token, _)| token.as_str()).collect();
+        let marks: Vec<f64> = chunk.iter().map(|(_, price)| **price).collect();
+
+        db.clickhouse
+            .client
+            .query(
+                r#"
+            INSERT INTO indexer.trader_positions
+            SELECT * EXCEPT (new_mark) REPLACE (
+                quantity * new_mark - cost_basis_usd AS unrealized_pnl_usd,
+                new_mark AS mark_price_usd,
+                now() AS updated_at
+            )
+            FROM (
+                SELECT *, transform(token_address, ?, ?, mark_price_usd) AS new_mark
+                FROM indexer.trader_positions FINAL
+                WHERE chain_id = ? AND quantity > 0 AND token_address IN ?
+            )
+            WHERE new_mark != mark_price_usd
+            "#,
+            )
+            .bind(&tokens)
+            .bind(&marks)
+            .bind(chain_id)
+            .bind(&tokens)
+            .execute()
+            .await
+            .context("Failed to re-mark open positions")?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug, clickhouse::Row, serde::Deserialize)]
+struct PositionSwap {
+    block_number: u64,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    timestamp: OffsetDateTime,
+    wallet: String,
+    token0: String,
+    token1: String,
+    amount0_adjusted: f64,
+    amount1_adjusted: f64,
+    amount0_direction: i8,
+    volume_usd: f64,
+}
@@ -1,4 +1,5 @@
 pub mod jobs;
 pub mod scheduler;
 
-pub use scheduler::{CronScheduler, CronSettings};
+pub use crate::config::CronSettings;
+pub use scheduler::{CronJob, CronScheduler};
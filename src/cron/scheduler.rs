@@ -5,50 +5,124 @@
 //! - Updating price changes from ClickHouse candles
 //! - Refreshing PostgreSQL materialized views
 //! - Taking pool and token snapshots to ClickHouse
+//! - Updating token circulating supply and market cap from on-chain totalSupply
+//! - Flagging wash trading for the adjusted volume metrics
+//! - Replaying swaps into per-wallet trader positions and PnL
+//! - Aggregating pool fee revenue and fee APR
+//! - Reconciling V3/V4 pool reserves with on-chain balances
+//!
+//! Each job's interval or cron expression, timeout and run-on-start flag come from
+//! the `cron` section of the settings, where jobs can also be disabled.
 
-use std::sync::Arc;
+use std::{future::Future, pin::Pin, sync::Arc, time::Duration};
 
-use anyhow::Result;
-use log::{error, info};
+use anyhow::{Context, Result};
+use log::{error, info, warn};
 use tokio::sync::mpsc;
 use tokio_cron_scheduler::{Job, JobScheduler};
 use tokio_util::sync::CancellationToken;
 
-use crate::db::{Database, IngestMessage};
+use crate::{
+    config::{CronJobSettings, CronSettings},
+    db::{Database, IngestMessage},
+};
 
 use super::jobs;
 
-/// Cron scheduler that manages periodic background jobs.
-pub struct CronScheduler {
-    db: Arc<Database>,
-    live_tx: mpsc::Sender<IngestMessage>,
-    settings: Arc<CronSettings>,
+/// Job of `cron::jobs`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CronJob {
+    Update24hStats,
+    UpdatePriceChanges,
+    RefreshMaterializedViews,
+    PoolSnapshots,
+    TokenSnapshots,
+    UpdateTokenSupplies,
+    DetectWashTrading,
+    UpdateTraderPositions,
+    UpdatePoolFees,
+    ReconcilePoolReserves,
 }
 
-/// Configuration for cron job intervals
-#[derive(Debug, Clone)]
-pub struct CronSettings {
-    /// Interval for updating 24h stats (volume, swaps) - default 5 minutes
-    pub update_stats_interval_secs: u64,
-    /// Interval for refreshing PostgreSQL materialized views - default 5 minutes
-    pub refresh_mv_interval_secs: u64,
-    /// Interval for taking pool snapshots - default 1 hour
-    pub pool_snapshot_interval_secs: u64,
-    /// Interval for taking token snapshots - default 1 day
-    pub token_snapshot_interval_secs: u64,
-}
+impl CronJob {
+    pub const ALL: [CronJob; 10] = [
+        CronJob::Update24hStats,
+        CronJob::UpdatePriceChanges,
+        CronJob::RefreshMaterializedViews,
+        CronJob::PoolSnapshots,
+        CronJob::TokenSnapshots,
+        CronJob::UpdateTokenSupplies,
+        CronJob::DetectWashTrading,
+        CronJob::UpdateTraderPositions,
+        CronJob::UpdatePoolFees,
+        CronJob::ReconcilePoolReserves,
+    ];
+
+    /// Name of the job's module, used as its key in the settings
+    pub fn name(&self) -> &'static str {
+        match self {
+            CronJob::Update24hStats => "update_24h_stats",
+            CronJob::UpdatePriceChanges => "update_price_changes",
+            CronJob::RefreshMaterializedViews => "refresh_materialized_views",
+            CronJob::PoolSnapshots => "pool_snapshots",
+            CronJob::TokenSnapshots => "token_snapshots",
+            CronJob::UpdateTokenSupplies => "update_token_supplies",
+            CronJob::DetectWashTrading => "detect_wash_trading",
+            CronJob::UpdateTraderPositions => "update_trader_positions",
+            CronJob::UpdatePoolFees => "update_pool_fees",
+            CronJob::ReconcilePoolReserves => "reconcile_pool_reserves",
+        }
+    }
 
-impl Default for CronSettings {
-    fn default() -> Self {
-        Self {
-            update_stats_interval_secs: 900,    // 15 minutes
-            refresh_mv_interval_secs: 300,      // 5 minutes
-            pool_snapshot_interval_secs: 3600,  // 1 hour
-            token_snapshot_interval_secs: 3600, // 1 hour
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|job| job.name() == name)
+    }
+
+    /// Interval when the settings set neither `interval_secs` nor `schedule`
+    pub fn default_interval(&self) -> Duration {
+        Duration::from_secs(match self {
+            CronJob::RefreshMaterializedViews => 300, // 5 minutes
+            CronJob::Update24hStats
+            | CronJob::UpdatePriceChanges
+            | CronJob::UpdateTraderPositions
+            | CronJob::UpdatePoolFees => 900, // 15 minutes
+            CronJob::PoolSnapshots
+            | CronJob::TokenSnapshots
+            | CronJob::UpdateTokenSupplies
+            | CronJob::DetectWashTrading => 3600, // 1 hour
+            CronJob::ReconcilePoolReserves => 21600,  // 6 hours
+        })
+    }
+
+    /// Whether `schedule` parses as the scheduler's cron expressions (with seconds)
+    pub fn is_valid_schedule(schedule: &str) -> bool {
+        Job::new_async(schedule, |_uuid, _lock| Box::pin(async {})).is_ok()
+    }
+
+    async fn run(&self, db: &Arc<Database>, live_tx: &mpsc::Sender<IngestMessage>) -> Result<()> {
+        match self {
+            CronJob::Update24hStats => jobs::update_24h_stats::run(db).await,
+            CronJob::UpdatePriceChanges => jobs::update_price_changes::run(db).await,
+            CronJob::RefreshMaterializedViews => jobs::refresh_materialized_views::run(db).await,
+            CronJob::PoolSnapshots => jobs::pool_snapshots::run(db, live_tx).await,
+            CronJob::TokenSnapshots => jobs::token_snapshots::run(db, live_tx).await,
+            CronJob::UpdateTokenSupplies => jobs::update_token_supplies::run(db).await,
+            CronJob::DetectWashTrading => jobs::detect_wash_trading::run(db).await,
+            CronJob::UpdateTraderPositions => jobs::update_trader_positions::run(db).await,
+            CronJob::UpdatePoolFees => jobs::update_pool_fees::run(db).await,
+            CronJob::ReconcilePoolReserves => jobs::reconcile_pool_reserves::run(db).await,
         }
     }
 }
 
+/// Cron scheduler that manages periodic background jobs.
+pub struct CronScheduler {
+    db: Arc<Database>,
+    live_tx: mpsc::Sender<IngestMessage>,
+    settings: Arc<CronSettings>,
+    jobs: Vec<CronJob>,
+}
+
 impl CronScheduler {
     pub fn new(
         db: Arc<Database>,
@@ -59,23 +133,36 @@ impl CronScheduler {
             db,
             live_tx,
             settings: Arc::new(settings),
+            jobs: CronJob::ALL.to_vec(),
         }
     }
 
+    /// Restricts the scheduler to `jobs` (still skipping the disabled ones).
+    pub fn with_jobs(mut self, jobs: Vec<CronJob>) -> Self {
+        self.jobs = jobs;
+        self
+    }
+
     /// Starts the cron scheduler and runs until cancellation.
     pub async fn run(&self, cancellation_token: CancellationToken) -> Result<()> {
         let mut scheduler = JobScheduler::new().await?;
 
-        // Register all jobs
-        self.register_update_24h_stats_job(&scheduler).await?;
-        self.register_update_price_changes_job(&scheduler).await?;
-        self.register_refresh_mv_job(&scheduler).await?;
-        self.register_pool_snapshots_job(&scheduler).await?;
-        self.register_token_snapshots_job(&scheduler).await?;
+        let mut registered = 0;
+        for &job in &self.jobs {
+            let settings = self.settings.job(job);
+            if !settings.enabled {
+                info!("Skipping disabled {} job", job.name());
+                continue;
+            }
+            self.register(&scheduler, job, &settings)
+                .await
+                .with_context(|| format!("Failed to register the {} job", job.name()))?;
+            registered += 1;
+        }
 
         // Start the scheduler
         scheduler.start().await?;
-        info!("Cron scheduler started with {} jobs", 5);
+        info!("Cron scheduler started with {} jobs", registered);
 
         // Wait for cancellation
         cancellation_token.cancelled().await;
@@ -85,115 +172,73 @@ impl CronScheduler {
         Ok(())
     }
 
-    async fn register_update_24h_stats_job(&self, scheduler: &JobScheduler) -> Result<()> {
+    async fn register(
+        &self,
+        scheduler: &JobScheduler,
+        job: CronJob,
+        settings: &CronJobSettings,
+    ) -> Result<()> {
         let db = self.db.clone();
-        let interval = self.settings.update_stats_interval_secs;
-
-        let job = Job::new_repeated_async(
-            std::time::Duration::from_secs(interval),
-            move |_uuid, _lock| {
-                let db = db.clone();
-                Box::pin(async move {
-                    if let Err(e) = jobs::update_24h_stats::run(&db).await {
-                        error!("Failed to update 24h stats: {:#}", e);
-                    }
-                })
+        let live_tx = self.live_tx.clone();
+        let timeout = settings.timeout_secs.map(Duration::from_secs);
+        let run = move |_uuid, _lock| {
+            let db = db.clone();
+            let live_tx = live_tx.clone();
+            Box::pin(run_job(job, db, live_tx, timeout)) as Pin<Box<dyn Future<Output = ()> + Send>>
+        };
+
+        let scheduled = match &settings.schedule {
+            Some(schedule) => {
+                scheduler
+                    .add(Job::new_async(schedule.as_str(), run.clone())?)
+                    .await?;
+                format!("on {}", schedule)
             },
-        )?;
-
-        scheduler.add(job).await?;
-        info!("Registered update_24h_stats job (every {}s)", interval);
-        Ok(())
-    }
-
-    async fn register_update_price_changes_job(&self, scheduler: &JobScheduler) -> Result<()> {
-        let db = self.db.clone();
-        let interval = self.settings.update_stats_interval_secs;
-
-        let job = Job::new_repeated_async(
-            std::time::Duration::from_secs(interval),
-            move |_uuid, _lock| {
-                let db = db.clone();
-                Box::pin(async move {
-                    if let Err(e) = jobs::update_price_changes::run(&db).await {
-                        error!("Failed to update price changes: {:#}", e);
-                    }
-                })
+            None => {
+                let interval = settings
+                    .interval_secs
+                    .map_or(job.default_interval(), Duration::from_secs);
+                scheduler
+                    .add(Job::new_repeated_async(interval, run.clone())?)
+                    .await?;
+                format!("every {}s", interval.as_secs())
             },
-        )?;
-
-        scheduler.add(job).await?;
-        info!("Registered update_price_changes job (every {}s)", interval);
-        Ok(())
-    }
-
-    async fn register_refresh_mv_job(&self, scheduler: &JobScheduler) -> Result<()> {
-        let db = self.db.clone();
-        let interval = self.settings.refresh_mv_interval_secs;
-
-        let job = Job::new_repeated_async(
-            std::time::Duration::from_secs(interval),
-            move |_uuid, _lock| {
-                let db = db.clone();
-                Box::pin(async move {
-                    if let Err(e) = jobs::refresh_materialized_views::run(&db).await {
-                        error!("Failed to refresh materialized views: {:#}", e);
-                    }
-                })
-            },
-        )?;
+        };
+        if settings.run_on_start {
+            scheduler
+                .add(Job::new_one_shot_async(Duration::ZERO, run)?)
+                .await?;
+        }
 
-        scheduler.add(job).await?;
         info!(
-            "Registered refresh_materialized_views job (every {}s)",
-            interval
+            "Registered {} job ({}{})",
+            job.name(),
+            scheduled,
+            if settings.run_on_start { ", and on start" } else { "" }
         );
         Ok(())
     }
+}
 
-    async fn register_pool_snapshots_job(&self, scheduler: &JobScheduler) -> Result<()> {
-        let db = self.db.clone();
-        let live_tx = self.live_tx.clone();
-        let interval = self.settings.pool_snapshot_interval_secs;
-
-        let job = Job::new_repeated_async(
-            std::time::Duration::from_secs(interval),
-            move |_uuid, _lock| {
-                let db = db.clone();
-                let live_tx = live_tx.clone();
-                Box::pin(async move {
-                    if let Err(e) = jobs::pool_snapshots::run(&db, &live_tx).await {
-                        error!("Failed to take pool snapshots: {:#}", e);
-                    }
-                })
-            },
-        )?;
-
-        scheduler.add(job).await?;
-        info!("Registered pool_snapshots job (every {}s)", interval);
-        Ok(())
-    }
-
-    async fn register_token_snapshots_job(&self, scheduler: &JobScheduler) -> Result<()> {
-        let db = self.db.clone();
-        let live_tx = self.live_tx.clone();
-        let interval = self.settings.token_snapshot_interval_secs;
-
-        let job = Job::new_repeated_async(
-            std::time::Duration::from_secs(interval),
-            move |_uuid, _lock| {
-                let db = db.clone();
-                let live_tx = live_tx.clone();
-                Box::pin(async move {
-                    if let Err(e) = jobs::token_snapshots::run(&db, &live_tx).await {
-                        error!("Failed to take token snapshots: {:#}", e);
-                    }
-                })
+/// Runs `job` once, logging its failure or timeout.
+async fn run_job(
+    job: CronJob,
+    db: Arc<Database>,
+    live_tx: mpsc::Sender<IngestMessage>,
+    timeout: Option<Duration>,
+) {
+    let result = match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, job.run(&db, &live_tx)).await {
+            Ok(result) => result,
+            Err(_) => {
+                warn!("Job {} timed out after {}s", job.name(), timeout.as_secs());
+                return;
             },
-        )?;
+        },
+        None => job.run(&db, &live_tx).await,
+    };
 
-        scheduler.add(job).await?;
-        info!("Registered token_snapshots job (every {}s)", interval);
-        Ok(())
+    if let Err(e) = result {
+        error!("Job {} failed: {:#}", job.name(), e);
     }
 }
@@ -1,6 +1,7 @@
 use std::time::Duration;
 
 use anyhow::Context;
+use chrono::DateTime;
 use clickhouse::{inserter::Inserter, Client};
 use log::info;
 use tokio::sync::mpsc;
@@ -9,10 +10,16 @@ use crate::{
     config::ClickHouseSettings,
     db::{
         clickhouse::ops::IngestMessage,
-        models::{Event, NewPool, PoolSnapshot, SupplyEvent, TokenSnapshot},
+        migrations::{self, AppliedMigration, MigrationStatus},
+        models::{
+            Arbitrage, Event, MevSandwich, NewPool, PoolSnapshot, SupplyEvent, TokenSnapshot, Trade,
+        },
     },
 };
 
+/// Migrations applied by `ClickhouseClient::migrate`
+const MIGRATIONS_DIR: &str = "schema/clickhouse";
+
 pub struct ClickhouseClient {
     pub client: Client,
 }
@@ -37,6 +44,9 @@ pub struct BatchIngestor {
     pub pool_snapshot_inserter: Inserter<PoolSnapshot>,
     pub token_snapshot_inserter: Inserter<TokenSnapshot>,
     pub supply_event_inserter: Inserter<SupplyEvent>,
+    pub mev_sandwich_inserter: Inserter<MevSandwich>,
+    pub arbitrage_inserter: Inserter<Arbitrage>,
+    pub trade_inserter: Inserter<Trade>,
 
     // Optional Redpanda publisher for live streaming (only used by LIVE ingestor)
     pub redpanda_publisher: Option<crate::pubsub::RedpandaPublisher>,
@@ -80,6 +90,19 @@ impl BatchIngestor {
         self.supply_event_inserter =
             Self::create_inserter(&self.client, "supply_events", &self.config);
     }
+
+    pub fn recreate_mev_sandwich_inserter(&mut self) {
+        self.mev_sandwich_inserter =
+            Self::create_inserter(&self.client, "mev_sandwiches", &self.config);
+    }
+
+    pub fn recreate_arbitrage_inserter(&mut self) {
+        self.arbitrage_inserter = Self::create_inserter(&self.client, "arbitrages", &self.config);
+    }
+
+    pub fn recreate_trade_inserter(&mut self) {
+        self.trade_inserter = Self::create_inserter(&self.client, "trades", &self.config);
+    }
 }
 
 impl ClickhouseClient {
@@ -168,6 +191,17 @@ impl ClickhouseClient {
                 "supply_events",
                 &historical_config,
             ),
+            mev_sandwich_inserter: BatchIngestor::create_inserter(
+                &client,
+                "mev_sandwiches",
+                &historical_config,
+            ),
+            arbitrage_inserter: BatchIngestor::create_inserter(
+                &client,
+                "arbitrages",
+                &historical_config,
+            ),
+            trade_inserter: BatchIngestor::create_inserter(&client, "trades", &historical_config),
             config: historical_config,
             redpanda_publisher: None, // Historical ingestor doesn't publish to Redpanda
         };
@@ -201,6 +235,13 @@ impl ClickhouseClient {
                 "supply_events",
                 &live_config,
             ),
+            mev_sandwich_inserter: BatchIngestor::create_inserter(
+                &client,
+                "mev_sandwiches",
+                &live_config,
+            ),
+            arbitrage_inserter: BatchIngestor::create_inserter(&client, "arbitrages", &live_config),
+            trade_inserter: BatchIngestor::create_inserter(&client, "trades", &live_config),
             config: live_config,
             redpanda_publisher: None, // Will be set by caller if Redpanda is enabled
         };
@@ -222,28 +263,94 @@ impl ClickhouseClient {
         ))
     }
 
+    /// Applies the pending migrations of `schema/clickhouse`.
+    ///
+    /// ClickHouse has no transactions: a migration failing halfway is not recorded
+    /// and runs again from its first statement, so statements must be idempotent.
+    /// Callers serialize concurrent runs (see `Database::migrate`).
     pub async fn migrate(&self) -> anyhow::Result<()> {
         info!("Running ClickHouse migrations");
-        let schema = tokio::fs::read_to_string("schema/clickhouse.sql")
+        self.client
+            .query(
+                "CREATE TABLE IF NOT EXISTS indexer.schema_migrations (
+                    version     UInt32,
+                    name        String,
+                    checksum    String,
+                    applied_at  DateTime DEFAULT now()
+                ) ENGINE = MergeTree
+                ORDER BY version",
+            )
+            .execute()
             .await
-            .context("Failed to read schema/clickhouse.sql")?;
+            .context("Failed to create indexer.schema_migrations")?;
+
+        let migrations = migrations::load(MIGRATIONS_DIR).await?;
+        let applied = self.applied_migrations().await?;
 
-        for statement in schema.split(';') {
-            let stmt = statement.trim();
-            if stmt.is_empty() {
-                continue;
+        for migration in migrations::pending(&migrations, &applied)? {
+            info!(
+                "Applying ClickHouse migration {}_{}",
+                migration.version, migration.name
+            );
+            for statement in migration.sql.split(';') {
+                let stmt = statement.trim();
+                if stmt.is_empty() {
+                    continue;
+                }
+                self.client.query(stmt).execute().await.with_context(|| {
+                    format!(
+                        "Migration {}_{} failed at statement: {}",
+                        migration.version, migration.name, stmt
+                    )
+                })?;
             }
             self.client
-                .query(stmt)
+                .query("INSERT INTO indexer.schema_migrations (version, name, checksum) VALUES (?, ?, ?)")
+                .bind(migration.version)
+                .bind(&migration.name)
+                .bind(&migration.checksum)
                 .execute()
-                .await
-                .with_context(|| format!("Failed to execute migration statement: {}", stmt))?;
+                .await?;
         }
 
         info!("ClickHouse migrations completed successfully");
         Ok(())
     }
 
+    /// Applied and pending migrations, without changing the database.
+    pub async fn migration_status(&self) -> anyhow::Result<Vec<MigrationStatus>> {
+        let migrations = migrations::load(MIGRATIONS_DIR).await?;
+        let tracked: u8 = self
+            .client
+            .query("EXISTS TABLE indexer.schema_migrations")
+            .fetch_one()
+            .await?;
+        let applied = if tracked == 1 { self.applied_migrations().await? } else { Vec::new() };
+        Ok(migrations::status(&migrations, &applied))
+    }
+
+    async fn applied_migrations(&self) -> anyhow::Result<Vec<AppliedMigration>> {
+        let rows = self
+            .client
+            .query(
+                "SELECT version, name, checksum, toUnixTimestamp(applied_at)
+                 FROM indexer.schema_migrations ORDER BY version",
+            )
+            .fetch_all::<(u32, String, String, u32)>()
+            .await
+            .context("Failed to read indexer.schema_migrations")?;
+
+        Ok(rows
+            .into_iter()
+            .map(|(version, name, checksum, applied_at)| AppliedMigration {
+                version,
+                name,
+                checksum,
+                applied_at: DateTime::from_timestamp(applied_at as i64, 0).unwrap_or_default(),
+            })
+            .collect())
+    }
+
     /// Health check - verify connection is still alive
     pub async fn health_check(&self) -> anyhow::Result<()> {
         self.client
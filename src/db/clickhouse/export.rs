@@ -0,0 +1,70 @@
+//! Streaming of table rows to files, used by `runic export`.
+
+use std::path::Path;
+
+use anyhow::Context;
+use tokio::io::AsyncWriteExt;
+
+use crate::db::clickhouse::ClickhouseClient;
+
+impl ClickhouseClient {
+    /// Distinct values of `partition_expr` (converted to strings) among the rows of
+    /// `table` matching `filter`, sorted.
+    pub async fn export_partitions(
+        &self,
+        table: &str,
+        partition_expr: &str,
+        filter: &str,
+    ) -> anyhow::Result<Vec<String>> {
+        self.client
+            .query(&format!(
+                "SELECT DISTINCT toString({}) AS partition FROM indexer.{} WHERE {} ORDER BY partition",
+                partition_expr, table, filter
+            ))
+            .fetch_all::<String>()
+            .await
+            .with_context(|| format!("Failed to list the export partitions of {}", table))
+    }
+
+    /// Writes the rows returned by `query` to `path` in a ClickHouse output `format`
+    /// (e.g. `Parquet`, `CSVWithNames`).
+    ///
+    /// The response is streamed to `{path}.part`, renamed once complete. Returns the
+    /// number of bytes written.
+    pub async fn export_to_file(
+        &self,
+        query: &str,
+        format: &str,
+        path: &Path,
+    ) -> anyhow::Result<u64> {
+        let partial = path.with_extension(format!(
+            "{}.part",
+            path.extension()
+                .and_then(|e| e.to_str())
+                .unwrap_or_default()
+        ));
+
+        let mut cursor = self.client.query(query).fetch_bytes(format)?;
+        let mut file = tokio::fs::File::create(&partial)
+            .await
+            .with_context(|| format!("Failed to create {}", partial.display()))?;
+
+        let mut written = 0;
+        let result: anyhow::Result<()> = async {
+            while let Some(chunk) = cursor.next().await? {
+                file.write_all(&chunk).await?;
+                written += chunk.len() as u64;
+            }
+            file.flush().await?;
+            Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e.context(format!("Failed to export to {}", path.display())));
+        }
+
+        tokio::fs::rename(&partial, path).await?;
+        Ok(written)
+    }
+}
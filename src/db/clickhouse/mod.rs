@@ -1,5 +1,8 @@
 pub mod client;
+pub mod export;
 pub mod ops;
+pub mod purge;
 
 pub use client::{BatchIngestor, ClickhouseClient};
 pub use ops::{BatchDataMessage, IngestMessage, SnapshotMessage};
+pub use purge::{PoolRewind, PurgedPools};
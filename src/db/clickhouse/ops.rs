@@ -5,7 +5,10 @@ use tokio_util::sync::CancellationToken;
 
 use crate::db::{
     clickhouse::client::BatchIngestor,
-    models::{Event, NewPool, Pool, PoolSnapshot, SupplyEvent, Token, TokenSnapshot},
+    models::{
+        Arbitrage, Event, MevSandwich, NewPool, Pool, PoolSnapshot, SupplyEvent, Token,
+        TokenSnapshot, Trade,
+    },
 };
 
 /// Batch of data from the indexer to be inserted into ClickHouse
@@ -17,6 +20,12 @@ pub struct BatchDataMessage {
     pub events: Vec<Event>,
     pub supply_events: Vec<SupplyEvent>,
     pub new_pools: Vec<NewPool>,
+    /// Sandwich attacks detected in this batch
+    pub mev_sandwiches: Vec<MevSandwich>,
+    /// Atomic arbitrages detected in this batch
+    pub arbitrages: Vec<Arbitrage>,
+    /// Swap hops chained into user trades
+    pub trades: Vec<Trade>,
     /// Updated pool states (for Redpanda, not stored in ClickHouse)
     pub pools: Vec<Pool>,
     /// Updated token states (for Redpanda, not stored in ClickHouse)
@@ -76,6 +85,24 @@ impl BatchIngestor {
                                     .context("Failed to write new pool")?;
                             }
 
+                            // Write detected sandwiches
+                            for sandwich in &batch.mev_sandwiches {
+                                self.mev_sandwich_inserter.write(sandwich).await
+                                    .context("Failed to write MEV sandwich")?;
+                            }
+
+                            // Write detected arbitrages
+                            for arbitrage in &batch.arbitrages {
+                                self.arbitrage_inserter.write(arbitrage).await
+                                    .context("Failed to write arbitrage")?;
+                            }
+
+                            // Write reconstructed trades
+                            for trade in &batch.trades {
+                                self.trade_inserter.write(trade).await
+                                    .context("Failed to write trade")?;
+                            }
+
                             // Publish to Redpanda if enabled (fire-and-forget)
                             if let Some(ref publisher) = self.redpanda_publisher {
                                 publisher.publish_batch(batch.chain_id, &batch).await;
@@ -129,6 +156,9 @@ impl BatchIngestor {
             self.new_pool_inserter.time_left(),
             self.pool_snapshot_inserter.time_left(),
             self.token_snapshot_inserter.time_left(),
+            self.mev_sandwich_inserter.time_left(),
+            self.arbitrage_inserter.time_left(),
+            self.trade_inserter.time_left(),
         ]
         .into_iter()
         .flatten()
@@ -142,19 +172,28 @@ impl BatchIngestor {
         let new_pool_stats = self.new_pool_inserter.commit().await?;
         let pool_snapshot_stats = self.pool_snapshot_inserter.commit().await?;
         let token_snapshot_stats = self.token_snapshot_inserter.commit().await?;
+        let mev_sandwich_stats = self.mev_sandwich_inserter.commit().await?;
+        let arbitrage_stats = self.arbitrage_inserter.commit().await?;
+        let trade_stats = self.trade_inserter.commit().await?;
 
         // Log only if any data was actually committed (transactions > 0)
         let total_rows = event_stats.rows
             + supply_event_stats.rows
             + new_pool_stats.rows
             + pool_snapshot_stats.rows
-            + token_snapshot_stats.rows;
+            + token_snapshot_stats.rows
+            + mev_sandwich_stats.rows
+            + arbitrage_stats.rows
+            + trade_stats.rows;
 
         let total_transactions = event_stats.transactions
             + supply_event_stats.transactions
             + new_pool_stats.transactions
             + pool_snapshot_stats.transactions
-            + token_snapshot_stats.transactions;
+            + token_snapshot_stats.transactions
+            + mev_sandwich_stats.transactions
+            + arbitrage_stats.transactions
+            + trade_stats.transactions;
 
         if total_transactions > 0 {
             let mut parts = Vec::new();
@@ -173,6 +212,15 @@ impl BatchIngestor {
             if token_snapshot_stats.rows > 0 {
                 parts.push(format!("TokenSnaps:{}", token_snapshot_stats.rows));
             }
+            if mev_sandwich_stats.rows > 0 {
+                parts.push(format!("Sandwiches:{}", mev_sandwich_stats.rows));
+            }
+            if arbitrage_stats.rows > 0 {
+                parts.push(format!("Arbs:{}", arbitrage_stats.rows));
+            }
+            if trade_stats.rows > 0 {
+                parts.push(format!("Trades:{}", trade_stats.rows));
+            }
 
             info!(
                 "[{}] Committed {} rows in {} txns [{}]",
@@ -194,6 +242,9 @@ impl BatchIngestor {
         let _ = self.new_pool_inserter.force_commit().await;
         let _ = self.pool_snapshot_inserter.force_commit().await;
         let _ = self.token_snapshot_inserter.force_commit().await;
+        let _ = self.mev_sandwich_inserter.force_commit().await;
+        let _ = self.arbitrage_inserter.force_commit().await;
+        let _ = self.trade_inserter.force_commit().await;
 
         info!("[{}] All inserters flushed", self.label);
         Ok(())
@@ -0,0 +1,336 @@
+//! Removal of one chain's rows, used by `runic chains reset`.
+
+use anyhow::Context;
+use log::info;
+
+use crate::db::clickhouse::ClickhouseClient;
+
+/// Tables feeding materialized views (their rows are replayed on a partial purge)
+const VIEW_SOURCES: [&str; 3] = ["events", "new_pools", "supply_events"];
+
+/// Targets of the materialized views on `VIEW_SOURCES` (candles_5m and up are
+/// fed by the candle below them)
+const AGGREGATES: [&str; 12] = [
+    "candles_1m",
+    "candles_5m",
+    "candles_15m",
+    "candles_1h",
+    "candles_4h",
+    "candles_1d",
+    "trader_stats",
+    "global_stats",
+    "hourly_stats",
+    "global_pool_stats",
+    "hourly_new_pools_stats",
+    "token_supplies",
+];
+
+/// Block-level rows without views
+const BLOCK_TABLES: [&str; 3] = ["mev_sandwiches", "arbitrages", "trades"];
+
+/// Point-in-time copies of PostgreSQL state, which cannot be rebuilt
+const SNAPSHOTS: [&str; 2] = ["pool_snapshots", "token_snapshots"];
+
+/// Pool changes of the blocks a partial purge deletes, read before the purge so
+/// PostgreSQL can be rewound with them (see `PostgresClient::purge_chain`).
+#[derive(Debug, Default)]
+pub struct PurgedPools {
+    /// Pools created at or after the cutoff
+    pub created: Vec<String>,
+    /// Changes the other pools accumulated from the cutoff on
+    pub rewinds: Vec<PoolRewind>,
+}
+
+/// Accumulated state one pool got from the purged events, as `Pool::update_from_event`
+/// applied it.
+#[derive(Debug, Clone, clickhouse::Row, serde::Deserialize)]
+pub struct PoolRewind {
+    pub pool_address: String,
+    pub swaps: u64,
+    /// Net decimal-adjusted token inflows (V3/V4 reserve accumulation)
+    pub reserve0_delta: f64,
+    pub reserve1_delta: f64,
+    /// Net liquidity added by mints, burns and V4 liquidity modifications
+    pub liquidity_delta: String,
+}
+
+/// One step of `purge_chain` after the wash trade flags.
+#[derive(Debug, PartialEq, Eq)]
+enum PurgeStep {
+    /// Delete the chain's rows of the table matching the filter
+    Delete(&'static str, String),
+    /// Keep the chain's rows below the block and re-insert them through the views
+    Replay(&'static str, u64),
+}
+
+/// Steps of a purge, in order: aggregates are emptied before any source row is
+/// replayed into them.
+fn purge_steps(from_block: Option<u64>) -> Vec<PurgeStep> {
+    let block_filter = from_block
+        .map(|block| format!("AND block_number >= {}", block))
+        .unwrap_or_default();
+
+    let mut steps: Vec<PurgeStep> = BLOCK_TABLES
+        .into_iter()
+        .map(|table| PurgeStep::Delete(table, block_filter.clone()))
+        .collect();
+    if from_block.is_none() {
+        steps.extend(SNAPSHOTS.map(|table| PurgeStep::Delete(table, String::new())));
+    }
+    steps.push(PurgeStep::Delete("trader_positions", String::new()));
+    steps.extend(AGGREGATES.map(|table| PurgeStep::Delete(table, String::new())));
+    steps.extend(VIEW_SOURCES.map(|table| match from_block {
+        None => PurgeStep::Delete(table, String::new()),
+        Some(block) => PurgeStep::Replay(table, block),
+    }));
+    steps
+}
+
+impl ClickhouseClient {
+    /// Pools created from `from_block` on, and what the events from `from_block` on
+    /// added to the other pools.
+    ///
+    /// Must run before `purge_chain` deletes those rows.
+    pub async fn purged_pools(
+        &self,
+        chain_id: u64,
+        from_block: u64,
+    ) -> anyhow::Result<PurgedPools> {
+        let created: Vec<String> = self
+            .client
+            .query(
+                "SELECT DISTINCT pool_address FROM indexer.new_pools
+                 WHERE chain_id = ? AND block_number >= ?",
+            )
+            .bind(chain_id)
+            .bind(from_block)
+            .fetch_all()
+            .await?;
+
+        let mut rewinds = self
+            .client
+            .query(
+                r#"
+            SELECT
+                pool_address,
+                countIf(event_type = 'swap') AS swaps,
+                sumIf(
+                    if(amount0_direction = 1, -amount0_adjusted, amount0_adjusted),
+                    event_type IN ('swap', 'mint', 'collect', 'modify_liquidity')
+                ) AS reserve0_delta,
+                sumIf(
+                    if(amount1_direction = 1, -amount1_adjusted, amount1_adjusted),
+                    event_type IN ('swap', 'mint', 'collect', 'modify_liquidity')
+                ) AS reserve1_delta,
+                toString(sumIf(
+                    toInt256(liquidity) * if(
+                        event_type = 'mint'
+                            OR (event_type = 'modify_liquidity' AND amount0_direction = -1),
+                        1, -1
+                    ),
+                    event_type IN ('mint', 'burn', 'modify_liquidity')
+                )) AS liquidity_delta
+            FROM indexer.events
+            WHERE chain_id = ? AND block_number >= ?
+            GROUP BY pool_address
+            "#,
+            )
+            .bind(chain_id)
+            .bind(from_block)
+            .fetch_all::<PoolRewind>()
+            .await?;
+        rewinds.retain(|rewind| !created.contains(&rewind.pool_address));
+
+        Ok(PurgedPools {
+            created,
+            rewinds,
+        })
+    }
+
+    /// Deletes the rows of `chain_id`, or only those from `from_block` on.
+    ///
+    /// Aggregates can't subtract rows, so they are emptied for the chain and, on a
+    /// partial purge, rebuilt by replaying the kept source rows through their views.
+    /// Snapshots are only deleted on a full purge, and trader positions are dropped
+    /// for the `update_trader_positions` job to replay (see `purge_steps`).
+    pub async fn purge_chain(&self, chain_id: u64, from_block: Option<u64>) -> anyhow::Result<()> {
+        // Wash trade flags point at events: drop those of the purged ones
+        match from_block {
+            None => self.delete_rows("wash_trades", chain_id, "").await?,
+            Some(block) => {
+                let (first_purged, purged) = self
+                    .client
+                    .query(
+                        "SELECT min(timestamp), count() FROM indexer.events
+                         WHERE chain_id = ? AND block_number >= ?",
+                    )
+                    .bind(chain_id)
+                    .bind(block)
+                    .fetch_one::<(u32, u64)>()
+                    .await?;
+                if purged > 0 {
+                    self.delete_rows(
+                        "wash_trades",
+                        chain_id,
+                        &format!("AND timestamp >= toDateTime({})", first_purged),
+                    )
+                    .await?;
+                }
+            },
+        }
+
+        for step in purge_steps(from_block) {
+            match step {
+                PurgeStep::Delete(table, filter) => {
+                    self.delete_rows(table, chain_id, &filter).await?
+                },
+                PurgeStep::Replay(table, block) => self.replay_rows(table, chain_id, block).await?,
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deletes the chain's rows of `table` matching `filter`, waiting for the mutation.
+    async fn delete_rows(&self, table: &str, chain_id: u64, filter: &str) -> anyhow::Result<()> {
+        self.client
+            .query(&format!(
+                "ALTER TABLE indexer.{} DELETE WHERE chain_id = {} {}",
+                table, chain_id, filter
+            ))
+            .with_option("mutations_sync", "2")
+            .execute()
+            .await
+            .with_context(|| format!("Failed to delete chain {} rows from {}", chain_id, table))
+    }
+
+    /// Keeps the chain's rows of `table` below `below_block` and re-inserts them, so
+    /// the materialized views on `table` aggregate them again.
+    ///
+    /// The rows are staged in `{table}_reset_{chain_id}`. If a run is interrupted,
+    /// that table holds the kept rows and must be restored or dropped by hand.
+    async fn replay_rows(
+        &self,
+        table: &str,
+        chain_id: u64,
+        below_block: u64,
+    ) -> anyhow::Result<()> {
+        let staging = format!("indexer.{}_reset_{}", table, chain_id);
+
+        let staged: u8 = self
+            .client
+            .query(&format!("EXISTS TABLE {}", staging))
+            .fetch_one()
+            .await?;
+        anyhow::ensure!(
+            staged == 0,
+            "{} exists: a previous reset was interrupted, restore or drop it first",
+            staging
+        );
+
+        for statement in [
+            format!("CREATE TABLE {} AS indexer.{}", staging, table),
+            format!(
+                "INSERT INTO {} SELECT * FROM indexer.{} WHERE chain_id = {} AND block_number < {}",
+                staging, table, chain_id, below_block
+            ),
+        ] {
+            self.client.query(&statement).execute().await?;
+        }
+
+        self.delete_rows(table, chain_id, "").await?;
+
+        for statement in [
+            format!("INSERT INTO indexer.{} SELECT * FROM {}", table, staging),
+            format!("DROP TABLE {}", staging),
+        ] {
+            self.client
+                .query(&statement)
+                .execute()
+                .await
+                .with_context(|| {
+                    format!("Failed to restore {} (rows kept in {})", table, staging)
+                })?;
+        }
+
+        info!(
+            "Chain {}: replayed {} rows below block {}",
+            chain_id, table, below_block
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_purge_deletes_every_table() {
+        let steps = purge_steps(None);
+
+        assert!(steps
+            .iter()
+            .all(|step| matches!(step, PurgeStep::Delete(_, filter) if filter.is_empty())));
+        let tables: Vec<&str> = steps
+            .iter()
+            .map(|step| match step {
+                PurgeStep::Delete(table, _) | PurgeStep::Replay(table, _) => *table,
+            })
+            .collect();
+        for table in BLOCK_TABLES
+            .iter()
+            .chain(&SNAPSHOTS)
+            .chain(&AGGREGATES)
+            .chain(&VIEW_SOURCES)
+            .chain(&["trader_positions"])
+        {
+            assert_eq!(
+                tables.iter().filter(|t| *t == table).count(),
+                1,
+                "{}",
+                table
+            );
+        }
+    }
+
+    #[test]
+    fn test_partial_purge_empties_aggregates_before_replaying() {
+        let steps = purge_steps(Some(100));
+
+        assert_eq!(
+            steps[..3],
+            [
+                PurgeStep::Delete("mev_sandwiches", "AND block_number >= 100".into()),
+                PurgeStep::Delete("arbitrages", "AND block_number >= 100".into()),
+                PurgeStep::Delete("trades", "AND block_number >= 100".into()),
+            ]
+        );
+        assert_eq!(
+            steps[3],
+            PurgeStep::Delete("trader_positions", String::new())
+        );
+
+        // Snapshots are kept
+        assert!(!steps.iter().any(|step| matches!(
+            step,
+            PurgeStep::Delete(table, _) if SNAPSHOTS.contains(table)
+        )));
+
+        let last_aggregate = steps
+            .iter()
+            .rposition(
+                |step| matches!(step, PurgeStep::Delete(table, _) if AGGREGATES.contains(table)),
+            )
+            .unwrap();
+        let first_replay = steps
+            .iter()
+            .position(|step| matches!(step, PurgeStep::Replay(..)))
+            .unwrap();
+        assert!(last_aggregate < first_replay);
+        assert_eq!(
+            steps[first_replay..],
+            VIEW_SOURCES.map(|table| PurgeStep::Replay(table, 100))
+        );
+    }
+}
@@ -0,0 +1,232 @@
+//! Versioned schema migrations shared by the ClickHouse and PostgreSQL clients.
+//!
+//! Each store has a directory of up-migrations named `NNNN_description.sql`
+//! (`schema/clickhouse`, `schema/postgres`), applied in version order and recorded
+//! with their checksum in the store's `indexer.schema_migrations` table. An applied
+//! migration must not be edited: schema changes go in a new file.
+
+use alloy::primitives::keccak256;
+use anyhow::{bail, Context};
+use chrono::{DateTime, Utc};
+
+/// Migration file read from a store's directory.
+#[derive(Debug, Clone)]
+pub struct Migration {
+    pub version: u32,
+    pub name: String,
+    pub sql: String,
+    pub checksum: String,
+}
+
+impl Migration {
+    fn new(version: u32, name: &str, sql: String) -> Self {
+        Self {
+            version,
+            name: name.to_string(),
+            checksum: keccak256(sql.as_bytes()).to_string(),
+            sql,
+        }
+    }
+}
+
+/// Row of `schema_migrations`.
+#[derive(Debug, Clone)]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub name: String,
+    pub checksum: String,
+    pub applied_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MigrationState {
+    Applied,
+    Pending,
+    /// Applied, but the file changed since
+    Modified,
+    /// Applied, but the file no longer exists
+    Missing,
+}
+
+impl MigrationState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationState::Applied => "applied",
+            MigrationState::Pending => "pending",
+            MigrationState::Modified => "modified",
+            MigrationState::Missing => "missing",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct MigrationStatus {
+    pub version: u32,
+    pub name: String,
+    pub state: MigrationState,
+    pub applied_at: Option<DateTime<Utc>>,
+}
+
+/// Parses `NNNN_description.sql` into its version and description.
+fn parse_file_name(file_name: &str) -> Option<(u32, &str)> {
+    let stem = file_name.strip_suffix(".sql")?;
+    let (version, name) = stem.split_once('_')?;
+    if version.is_empty() || !version.bytes().all(|b| b.is_ascii_digit()) || name.is_empty() {
+        return None;
+    }
+    Some((version.parse().ok()?, name))
+}
+
+/// Reads the migrations of `dir`, sorted by version.
+pub async fn load(dir: &str) -> anyhow::Result<Vec<Migration>> {
+    let mut entries = tokio::fs::read_dir(dir)
+        .await
+        .with_context(|| format!("Failed to read migrations directory {}", dir))?;
+
+    let mut migrations = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+        let path = entry.path();
+        let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if !file_name.ends_with(".sql") {
+            continue;
+        }
+        let Some((version, name)) = parse_file_name(file_name) else {
+            bail!(
+                "Invalid migration file name {}, expected NNNN_description.sql",
+                path.display()
+            );
+        };
+        let sql = tokio::fs::read_to_string(&path)
+            .await
+            .with_context(|| format!("Failed to read {}", path.display()))?;
+        migrations.push(Migration::new(version, name, sql));
+    }
+
+    sort_migrations(&mut migrations)?;
+    Ok(migrations)
+}
+
+fn sort_migrations(migrations: &mut [Migration]) -> anyhow::Result<()> {
+    migrations.sort_by_key(|m| m.version);
+    if let Some(pair) = migrations
+        .windows(2)
+        .find(|pair| pair[0].version == pair[1].version)
+    {
+        bail!(
+            "Migrations {}_{} and {}_{} share a version",
+            pair[0].version,
+            pair[0].name,
+            pair[1].version,
+            pair[1].name
+        );
+    }
+    Ok(())
+}
+
+/// Merges the migration files with the applied rows, by version.
+pub fn status(migrations: &[Migration], applied: &[AppliedMigration]) -> Vec<MigrationStatus> {
+    let mut statuses: Vec<MigrationStatus> = migrations
+        .iter()
+        .map(|migration| {
+            let row = applied.iter().find(|a| a.version == migration.version);
+            MigrationStatus {
+                version: migration.version,
+                name: migration.name.clone(),
+                state: match row {
+                    None => MigrationState::Pending,
+                    Some(row) if row.checksum != migration.checksum => MigrationState::Modified,
+                    Some(_) => MigrationState::Applied,
+                },
+                applied_at: row.map(|a| a.applied_at),
+            }
+        })
+        .collect();
+
+    statuses.extend(
+        applied
+            .iter()
+            .filter(|a| !migrations.iter().any(|m| m.version == a.version))
+            .map(|a| MigrationStatus {
+                version: a.version,
+                name: a.name.clone(),
+                state: MigrationState::Missing,
+                applied_at: Some(a.applied_at),
+            }),
+    );
+    statuses.sort_by_key(|s| s.version);
+    statuses
+}
+
+/// Returns the migrations to apply, in order.
+///
+/// Fails when an applied migration was edited, since its change would never run.
+pub fn pending<'a>(
+    migrations: &'a [Migration],
+    applied: &[AppliedMigration],
+) -> anyhow::Result<Vec<&'a Migration>> {
+    let mut pending = Vec::new();
+    for migration in migrations {
+        match applied.iter().find(|a| a.version == migration.version) {
+            None => pending.push(migration),
+            Some(row) if row.checksum != migration.checksum => bail!(
+                "Migration {}_{} was modified after being applied, add a new migration instead",
+                migration.version,
+                migration.name
+            ),
+            Some(_) => {},
+        }
+    }
+    Ok(pending)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn applied(migration: &Migration) -> AppliedMigration {
+        AppliedMigration {
+            version: migration.version,
+            name: migration.name.clone(),
+            checksum: migration.checksum.clone(),
+            applied_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parse_file_name() {
+        assert_eq!(parse_file_name("0001_initial.sql"), Some((1, "initial")));
+        assert_eq!(
+            parse_file_name("0012_add_fee_tier.sql"),
+            Some((12, "add_fee_tier"))
+        );
+        assert_eq!(parse_file_name("initial.sql"), None);
+        assert_eq!(parse_file_name("v1_initial.sql"), None);
+        assert_eq!(parse_file_name("0001_.sql"), None);
+    }
+
+    #[test]
+    fn test_pending_skips_applied_and_rejects_modified() {
+        let mut migrations = vec![
+            Migration::new(2, "second", "SELECT 2".to_string()),
+            Migration::new(1, "first", "SELECT 1".to_string()),
+        ];
+        sort_migrations(&mut migrations).unwrap();
+
+        let rows = vec![applied(&migrations[0])];
+        let versions: Vec<u32> = pending(&migrations, &rows)
+            .unwrap()
+            .iter()
+            .map(|m| m.version)
+            .collect();
+        assert_eq!(versions, vec![2]);
+
+        let edited = [Migration::new(1, "first", "SELECT 10".to_string())];
+        assert!(pending(&edited, &rows).is_err());
+        assert_eq!(status(&edited, &rows)[0].state, MigrationState::Modified);
+
+        migrations.push(Migration::new(1, "duplicate", "SELECT 1".to_string()));
+        assert!(sort_migrations(&mut migrations).is_err());
+    }
+}
@@ -1,12 +1,13 @@
 use std::sync::Arc;
 
-use log::{error, info};
+use log::{error, info, warn};
 use tokio::{sync::mpsc, task::JoinHandle};
 use tokio_util::sync::CancellationToken;
 
 use crate::config::Settings;
 
 pub mod clickhouse;
+pub mod migrations;
 pub mod models;
 pub mod postgres;
 
@@ -35,10 +36,6 @@ impl Database {
 
         let postgres = PostgresClient::new(settings.postgres.clone()).await?;
 
-        // Run migrations
-        clickhouse.migrate().await?;
-        postgres.migrate().await?;
-
         let clickhouse = Arc::new(clickhouse);
 
         // Spawn historical ingestor (high-throughput for chain sync)
@@ -68,4 +65,21 @@ impl Database {
             live_handle,
         ))
     }
+
+    /// Applies the pending ClickHouse and PostgreSQL migrations.
+    ///
+    /// A PostgreSQL advisory lock keeps concurrent processes (e.g. several
+    /// `runic run` replicas starting together) from migrating at the same time.
+    pub async fn migrate(&self) -> anyhow::Result<()> {
+        let lock = self.postgres.lock_migrations().await?;
+        let result = async {
+            self.clickhouse.migrate().await?;
+            self.postgres.migrate().await
+        }
+        .await;
+        if let Err(e) = lock.release().await {
+            warn!("{:#}", e);
+        }
+        result
+    }
 }
@@ -0,0 +1,48 @@
+use clickhouse::Row;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// An atomic arbitrage: a closed token cycle swapped within one transaction
+/// (ClickHouse `arbitrages`).
+///
+/// Population: Detected by the worker's MEV pass over the swaps of each transaction.
+/// One row per cycle, e.g. A→B on pool 1, B→C on pool 2, C→A on pool 3.
+///
+/// Query Patterns:
+///   - "Get arbitrages by trader X"
+///   - "Get arbitrages routed through pool X"
+///   - "Total arbitrage profit per day"
+#[derive(Debug, Clone, Serialize, Row)]
+pub struct Arbitrage {
+    // Identifiers
+    pub chain_id: u64,
+    pub block_number: u64,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    pub timestamp: OffsetDateTime,
+    pub tx_hash: String,
+    /// `log_index` of the first and last swap of the cycle
+    pub first_log_index: u32,
+    pub last_log_index: u32,
+
+    /// `maker` of the first swap
+    pub trader: String,
+
+    // Cycle
+    /// Token the cycle starts and ends with
+    pub token: String,
+    /// Tokens in swap order, starting token first (not repeated at the end)
+    pub path: Vec<String>,
+    /// Pools in swap order
+    pub pools: Vec<String>,
+    pub hops: u8,
+
+    /// Decimal-adjusted amount of `token` sold by the first swap
+    pub amount_in: f64,
+    /// Decimal-adjusted amount of `token` bought by the last swap
+    pub amount_out: f64,
+
+    /// (amount_out - amount_in) valued at the `token` USD price
+    pub profit_usd: f64,
+    /// Sum of the legs' `volume_usd`
+    pub volume_usd: f64,
+}
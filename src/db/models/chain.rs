@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use super::PriceFeed;
+
 /// Simple struct with chain tokens for synchronous access
 /// Used to calculate pool token priorities
 #[derive(Debug, Clone)]
@@ -10,25 +12,57 @@ pub struct ChainTokens {
     pub major_tokens: Vec<String>,
     pub stablecoins: Vec<String>,
     pub stable_pool_address: String,
+    /// Native/stable pools used to derive the native token price
+    pub reference_pools: Vec<String>,
+    /// Chainlink aggregators used as authoritative token prices
+    pub price_feeds: Vec<PriceFeed>,
 }
 
 impl ChainTokens {
+    /// Falls back to `stable_pool_address` as the only reference pool when
+    /// `reference_pools` is empty.
     pub fn new(
         wrapped_native_token: String,
         stable_token: String,
         major_tokens: Vec<String>,
         stablecoins: Vec<String>,
         stable_pool_address: String,
+        reference_pools: Vec<String>,
+        price_feeds: Vec<PriceFeed>,
     ) -> Self {
+        let reference_pools = if reference_pools.is_empty() && !stable_pool_address.is_empty() {
+            vec![stable_pool_address.to_lowercase()]
+        } else {
+            reference_pools
+                .into_iter()
+                .map(|p| p.to_lowercase())
+                .collect()
+        };
+
         Self {
             wrapped_native_token,
             stable_token,
             major_tokens,
             stablecoins,
             stable_pool_address,
+            reference_pools,
+            price_feeds,
         }
     }
 
+    /// Every token named in the chain configuration (lowercase). Pricing strategies
+    /// draw their anchors and whitelist from these tokens.
+    pub fn listed_tokens(&self) -> Vec<String> {
+        std::iter::once(&self.wrapped_native_token)
+            .chain(std::iter::once(&self.stable_token))
+            .chain(&self.stablecoins)
+            .chain(&self.major_tokens)
+            .chain(self.price_feeds.iter().map(|feed| &feed.token_address))
+            .filter(|token| !token.is_empty())
+            .map(|token| token.to_lowercase())
+            .collect()
+    }
+
     pub fn is_wrapped_native(&self, token: &str) -> bool {
         self.wrapped_native_token.to_lowercase() == token.to_lowercase()
     }
@@ -62,6 +96,46 @@ impl ChainTokens {
     pub fn is_stable_pool(&self, address: &str) -> bool {
         self.stable_pool_address.to_lowercase() == address.to_lowercase()
     }
+
+    pub fn price_feed(&self, aggregator_address: &str) -> Option<&PriceFeed> {
+        self.price_feeds.iter().find(|feed| {
+            feed.aggregator_address
+                .eq_ignore_ascii_case(aggregator_address)
+        })
+    }
+
+    pub fn is_reference_pool(&self, address: &str) -> bool {
+        self.reference_pools.contains(&address.to_lowercase())
+    }
+}
+
+/// Thresholds applied by a chain's pricing strategy.
+///
+/// Missing values fall back to the defaults.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct PricingThresholds {
+    /// Pools below this TVL produce no swap prices or volume
+    pub min_pool_tvl_usd: f64,
+    /// Minimum USD liquidity on the trusted side of a pool for it to set a price
+    pub min_liquidity_usd: f64,
+    /// Divergence between the pool price and the swap-implied price above which
+    /// the implied price is used (0.10 = 10%)
+    pub max_price_divergence: f64,
+}
+
+impl Default for PricingThresholds {
+    fn default() -> Self {
+        Self {
+            min_pool_tvl_usd: 50_000.0,
+            min_liquidity_usd: 10_000.0,
+            max_price_divergence: 0.10,
+        }
+    }
+}
+
+fn default_pricing_strategy() -> String {
+    "default".to_string()
 }
 
 /// Blockchain configuration stored in PostgreSQL.
@@ -82,9 +156,50 @@ pub struct DatabaseChain {
     pub stable_token_address: String,
     pub stable_token_decimals: u8,
     pub stable_pool_address: String,
+    /// Native/stable pools whose liquidity-weighted median sets the native price.
+    /// Empty means `stable_pool_address` alone.
+    #[serde(default)]
+    pub reference_pools: Vec<String>,
     pub major_tokens: Vec<String>,
     pub stablecoins: Vec<String>,
     #[serde(default)]
     pub factories: Vec<String>,
+    /// Holders (treasury, burn addresses) whose balances are excluded from circulating supply
+    #[serde(default)]
+    pub supply_excluded_addresses: Vec<String>,
+    /// Chainlink aggregators used as authoritative prices (stored in `indexer.price_feeds`)
+    #[serde(default)]
+    pub price_feeds: Vec<PriceFeed>,
+    /// Name of the `PricingStrategy` used for this chain
+    #[serde(default = "default_pricing_strategy")]
+    pub pricing_strategy: String,
+    #[serde(default)]
+    pub pricing_thresholds: PricingThresholds,
     pub updated_at: Option<DateTime<Utc>>,
 }
+
+impl DatabaseChain {
+    /// Lowercases the addresses and sets the chain id of the price feeds, as
+    /// stored by the workers.
+    pub fn normalize(&mut self) {
+        for address in [
+            &mut self.native_token_address,
+            &mut self.stable_token_address,
+            &mut self.stable_pool_address,
+        ]
+        .into_iter()
+        .chain(&mut self.reference_pools)
+        .chain(&mut self.major_tokens)
+        .chain(&mut self.stablecoins)
+        .chain(&mut self.factories)
+        .chain(&mut self.supply_excluded_addresses)
+        {
+            *address = address.to_lowercase();
+        }
+        for feed in &mut self.price_feeds {
+            feed.chain_id = self.chain_id;
+            feed.aggregator_address = feed.aggregator_address.to_lowercase();
+            feed.token_address = feed.token_address.to_lowercase();
+        }
+    }
+}
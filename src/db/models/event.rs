@@ -1,14 +1,15 @@
 use alloy::primitives::U256;
 use clickhouse::{types::UInt256, Row};
+use fastnum::D256;
 use serde::Serialize;
 use time::OffsetDateTime;
 
 use crate::{
     abis::{v2, v3, v4},
-    db::models::{Pool, Token},
+    db::models::{Pool, PriceProvenance, Token},
     utils::{
-        calculate_mint_amounts, hex_encode, into_u256, sqrt_price_x96_str_to_adjusted_price,
-        u256_to_f64,
+        calculate_mint_amounts, decimal_to_f64, get_sqrt_ratio_at_tick, hex_encode, into_u256,
+        serialize_decimal_as_f64, sqrt_price_x96_str_to_adjusted_price, u256_to_decimal,
     },
 };
 
@@ -32,8 +33,9 @@ pub struct Event {
     pub token1: String,
 
     // Actors
-    pub maker: String, // Swap: tx.origin or router
-    pub owner: String, // Mint/Burn: LP position owner
+    pub maker: String,   // Swap: tx.origin or router
+    pub owner: String,   // Mint/Burn: LP position owner
+    pub tx_from: String, // Transaction sender (EOA), empty when unknown
 
     // Event type
     pub event_type: String, // 'swap', 'mint', 'burn', 'collect', 'modify_liquidity'
@@ -42,9 +44,11 @@ pub struct Event {
     pub amount0: UInt256,
     pub amount1: UInt256,
 
-    // Amounts (adjusted)
-    pub amount0_adjusted: f64,
-    pub amount1_adjusted: f64,
+    // Amounts (adjusted, exact; Float64 in ClickHouse)
+    #[serde(serialize_with = "serialize_decimal_as_f64")]
+    pub amount0_adjusted: D256,
+    #[serde(serialize_with = "serialize_decimal_as_f64")]
+    pub amount1_adjusted: D256,
 
     // Amount direction
     pub amount0_direction: i8,
@@ -57,6 +61,17 @@ pub struct Event {
     pub fees_usd: f64,
     pub fee: u32,
     pub is_suspicious: bool, // True if price manipulation suspected
+    /// Part played in a detected MEV pattern, empty for organic swaps
+    /// ('sandwich_frontrun', 'sandwich_victim', 'sandwich_backrun', 'arbitrage')
+    pub mev_role: String,
+
+    // Price provenance (see PriceProvenance)
+    pub price_confidence: f64,
+    pub price_source: String,
+    pub price_method: String,
+    pub price_source_pools: Vec<String>,
+    pub price_hops: u8,
+    pub price_min_liquidity_usd: f64,
 
     // V3/V4 concentrated liquidity data
     pub sqrt_price_x96: UInt256,
@@ -90,7 +105,7 @@ impl Event {
         };
 
         let amount0 = into_u256(raw_amount0);
-        let amount0_adjusted = u256_to_f64(raw_amount0, token0.decimals);
+        let amount0_adjusted = u256_to_decimal(raw_amount0, token0.decimals);
 
         let (raw_amount1, amount1_direction) = if event.amount1In > event.amount1Out {
             (event.amount1In - event.amount1Out, -1i8)
@@ -98,14 +113,12 @@ impl Event {
             (event.amount1Out - event.amount1In, 1i8)
         };
         let amount1 = into_u256(raw_amount1);
-        let amount1_adjusted = u256_to_f64(raw_amount1, token1.decimals);
+        let amount1_adjusted = u256_to_decimal(raw_amount1, token1.decimals);
 
         // Calculate price from swap amounts (simple V2 price)
-        let price = if amount0_adjusted.abs() > 1e-15 {
-            (amount1_adjusted / amount0_adjusted).abs()
-        } else {
-            0.0
-        };
+        let amount0_f64 = decimal_to_f64(amount0_adjusted);
+        let price =
+            if amount0_f64 > 1e-15 { decimal_to_f64(amount1_adjusted) / amount0_f64 } else { 0.0 };
 
         Self {
             chain_id,
@@ -119,6 +132,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: hex_encode(event.sender.as_slice()),
             owner: String::new(),
+            tx_from: String::new(),
             event_type: String::from("swap"),
             amount0,
             amount1,
@@ -132,6 +146,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: UInt256::from_le_bytes([0u8; 32]),
             tick: 0,
             tick_lower: 0,
@@ -172,8 +193,8 @@ impl Event {
         let amount0 = into_u256(abs_amount0);
         let amount1 = into_u256(abs_amount1);
 
-        let amount0_adjusted = u256_to_f64(abs_amount0, token0.decimals);
-        let amount1_adjusted = u256_to_f64(abs_amount1, token1.decimals);
+        let amount0_adjusted = u256_to_decimal(abs_amount0, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(abs_amount1, token1.decimals);
 
         let sqrt_price_u256 = U256::from(event.sqrtPriceX96);
         let price = sqrt_price_x96_str_to_adjusted_price(
@@ -195,6 +216,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: hex_encode(event.sender.as_slice()),
             owner: String::new(),
+            tx_from: String::new(),
             event_type: String::from("swap"),
             amount0,
             amount1,
@@ -208,6 +230,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: into_u256(sqrt_price_u256),
             tick: event.tick.try_into().unwrap_or(0),
             tick_lower: 0,
@@ -249,8 +278,8 @@ impl Event {
         let amount0 = into_u256(abs_amount0_u256);
         let amount1 = into_u256(abs_amount1_u256);
 
-        let amount0_adjusted = u256_to_f64(abs_amount0_u256, token0.decimals);
-        let amount1_adjusted = u256_to_f64(abs_amount1_u256, token1.decimals);
+        let amount0_adjusted = u256_to_decimal(abs_amount0_u256, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(abs_amount1_u256, token1.decimals);
 
         let sqrt_price_u256 = U256::from(event.sqrtPriceX96);
         let price = sqrt_price_x96_str_to_adjusted_price(
@@ -272,6 +301,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: hex_encode(event.sender.as_slice()),
             owner: String::new(),
+            tx_from: String::new(),
             event_type: String::from("swap"),
             amount0,
             amount1,
@@ -285,6 +315,13 @@ impl Event {
             fees_usd: 0.0,
             fee: event.fee.to::<u32>(),
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: into_u256(sqrt_price_u256),
             tick: event.tick.try_into().unwrap_or(0),
             tick_lower: 0,
@@ -309,8 +346,8 @@ impl Event {
 
         let amount0 = into_u256(event.amount0);
         let amount1 = into_u256(event.amount1);
-        let amount0_adjusted = u256_to_f64(event.amount0, token0.decimals);
-        let amount1_adjusted = u256_to_f64(event.amount1, token1.decimals);
+        let amount0_adjusted = u256_to_decimal(event.amount0, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(event.amount1, token1.decimals);
 
         Self {
             chain_id,
@@ -324,6 +361,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: String::new(),
             owner: hex_encode(event.sender.as_slice()),
+            tx_from: String::new(),
             event_type: String::from("mint"),
             amount0,
             amount1,
@@ -337,6 +375,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: UInt256::from_le_bytes([0u8; 32]),
             tick: 0,
             tick_lower: 0,
@@ -361,8 +406,8 @@ impl Event {
 
         let amount0 = into_u256(event.amount0);
         let amount1 = into_u256(event.amount1);
-        let amount0_adjusted = u256_to_f64(event.amount0, token0.decimals);
-        let amount1_adjusted = u256_to_f64(event.amount1, token1.decimals);
+        let amount0_adjusted = u256_to_decimal(event.amount0, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(event.amount1, token1.decimals);
 
         Self {
             chain_id,
@@ -376,6 +421,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: String::new(),
             owner: hex_encode(event.sender.as_slice()),
+            tx_from: String::new(),
             event_type: String::from("burn"),
             amount0,
             amount1,
@@ -389,6 +435,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: UInt256::from_le_bytes([0u8; 32]),
             tick: 0,
             tick_lower: 0,
@@ -413,8 +466,8 @@ impl Event {
 
         let amount0 = into_u256(event.amount0);
         let amount1 = into_u256(event.amount1);
-        let amount0_adjusted = u256_to_f64(event.amount0, token0.decimals);
-        let amount1_adjusted = u256_to_f64(event.amount1, token1.decimals);
+        let amount0_adjusted = u256_to_decimal(event.amount0, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(event.amount1, token1.decimals);
 
         let liquidity = into_u256(U256::from(event.amount));
 
@@ -430,6 +483,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: String::new(),
             owner: hex_encode(event.owner.as_slice()),
+            tx_from: String::new(),
             event_type: String::from("mint"),
             amount0,
             amount1,
@@ -443,6 +497,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: UInt256::from_le_bytes([0u8; 32]),
             tick: 0,
             tick_lower: event.tickLower.try_into().unwrap_or(0),
@@ -467,8 +528,8 @@ impl Event {
 
         let amount0 = into_u256(event.amount0);
         let amount1 = into_u256(event.amount1);
-        let amount0_adjusted = u256_to_f64(event.amount0, token0.decimals);
-        let amount1_adjusted = u256_to_f64(event.amount1, token1.decimals);
+        let amount0_adjusted = u256_to_decimal(event.amount0, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(event.amount1, token1.decimals);
 
         let liquidity = into_u256(U256::from(event.amount));
 
@@ -484,6 +545,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: String::new(),
             owner: hex_encode(event.owner.as_slice()),
+            tx_from: String::new(),
             event_type: String::from("burn"),
             amount0,
             amount1,
@@ -497,6 +559,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: UInt256::from_le_bytes([0u8; 32]),
             tick: 0,
             tick_lower: event.tickLower.try_into().unwrap_or(0),
@@ -524,8 +593,8 @@ impl Event {
 
         let amount0 = into_u256(amount0_u256);
         let amount1 = into_u256(amount1_u256);
-        let amount0_adjusted = u256_to_f64(amount0_u256, token0.decimals);
-        let amount1_adjusted = u256_to_f64(amount1_u256, token1.decimals);
+        let amount0_adjusted = u256_to_decimal(amount0_u256, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(amount1_u256, token1.decimals);
 
         Self {
             chain_id,
@@ -539,6 +608,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: String::new(),
             owner: hex_encode(event.owner.as_slice()),
+            tx_from: String::new(),
             event_type: String::from("collect"),
             amount0,
             amount1,
@@ -552,6 +622,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: UInt256::from_le_bytes([0u8; 32]),
             tick: 0,
             tick_lower: event.tickLower.try_into().unwrap_or(0),
@@ -585,32 +662,32 @@ impl Event {
             (delta.unsigned_abs(), -1i8)
         };
 
-        let (amount0_f64, amount1_f64) = if let Some(current_tick) = pool.tick {
-            let liquidity_f64 = u256_to_f64(U256::from(liq_abs), 0);
-
-            calculate_mint_amounts(
-                liquidity_f64,
-                current_tick,
-                event.tickLower.as_i32(),
-                event.tickUpper.as_i32(),
-            )
-        } else {
-            (0.0, 0.0)
-        };
-
-        let amount0 =
-            into_u256(U256::from_str_radix(&format!("{:.0}", amount0_f64), 10).unwrap_or_default());
-        let amount1 =
-            into_u256(U256::from_str_radix(&format!("{:.0}", amount1_f64), 10).unwrap_or_default());
+        // Current price of the pool, falling back to the price at its current tick
+        let sqrt_price_x96 = pool
+            .sqrt_price_x96
+            .as_deref()
+            .and_then(|sqrt_price| U256::from_str_radix(sqrt_price, 10).ok())
+            .filter(|sqrt_price| !sqrt_price.is_zero())
+            .or_else(|| pool.tick.and_then(get_sqrt_ratio_at_tick));
+
+        // Exact amounts as computed by the PoolManager (SqrtPriceMath)
+        let (amount0_u256, amount1_u256) = sqrt_price_x96
+            .zip(u128::try_from(liq_abs).ok())
+            .and_then(|(sqrt_price_x96, liquidity)| {
+                calculate_mint_amounts(
+                    liquidity,
+                    sqrt_price_x96,
+                    event.tickLower.as_i32(),
+                    event.tickUpper.as_i32(),
+                    direction == -1,
+                )
+            })
+            .unwrap_or_default();
 
-        let amount0_adjusted = u256_to_f64(
-            U256::from_str_radix(&format!("{:.0}", amount0_f64), 10).unwrap_or_default(),
-            token0.decimals,
-        );
-        let amount1_adjusted = u256_to_f64(
-            U256::from_str_radix(&format!("{:.0}", amount1_f64), 10).unwrap_or_default(),
-            token1.decimals,
-        );
+        let amount0 = into_u256(amount0_u256);
+        let amount1 = into_u256(amount1_u256);
+        let amount0_adjusted = u256_to_decimal(amount0_u256, token0.decimals);
+        let amount1_adjusted = u256_to_decimal(amount1_u256, token1.decimals);
 
         Self {
             chain_id,
@@ -624,6 +701,7 @@ impl Event {
             token1: token1.address.clone(),
             maker: String::new(),
             owner: hex_encode(event.sender.as_slice()),
+            tx_from: String::new(),
             event_type: String::from("modify_liquidity"),
             amount0,
             amount1,
@@ -637,6 +715,13 @@ impl Event {
             fees_usd: 0.0,
             fee: 0,
             is_suspicious: false,
+            mev_role: String::new(),
+            price_confidence: 0.0,
+            price_source: String::new(),
+            price_method: String::new(),
+            price_source_pools: Vec::new(),
+            price_hops: 0,
+            price_min_liquidity_usd: 0.0,
             sqrt_price_x96: UInt256::from_le_bytes([0u8; 32]),
             tick: 0,
             tick_lower: event.tickLower.try_into().unwrap_or(0),
@@ -644,4 +729,24 @@ impl Event {
             liquidity: into_u256(U256::from(liq_abs)),
         }
     }
+
+    /// Adjusted token0 amount as f64, for price and USD math.
+    pub fn amount0_f64(&self) -> f64 {
+        decimal_to_f64(self.amount0_adjusted)
+    }
+
+    /// Adjusted token1 amount as f64, for price and USD math.
+    pub fn amount1_f64(&self) -> f64 {
+        decimal_to_f64(self.amount1_adjusted)
+    }
+
+    /// Record how `price_usd` was obtained.
+    pub fn set_price_provenance(&mut self, provenance: &PriceProvenance) {
+        self.price_confidence = provenance.confidence;
+        self.price_source = provenance.source.clone();
+        self.price_method = provenance.method.clone();
+        self.price_source_pools = provenance.source_pools.clone();
+        self.price_hops = provenance.hops.min(u8::MAX as u32) as u8;
+        self.price_min_liquidity_usd = provenance.min_liquidity_usd;
+    }
 }
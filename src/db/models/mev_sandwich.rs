@@ -0,0 +1,43 @@
+use clickhouse::Row;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+/// A sandwich attack on one victim swap (ClickHouse `mev_sandwiches`).
+///
+/// Population: Detected by the worker's MEV pass over the swaps of each block.
+/// One row per (front-run, victim, back-run) triple: a sandwich with several
+/// victims yields several rows sharing the same front-run and back-run.
+///
+/// Query Patterns:
+///   - "Get sandwiches on pool X"
+///   - "Get sandwiches by attacker X / suffered by victim X"
+///   - "Total extracted value per day"
+#[derive(Debug, Clone, Serialize, Row)]
+pub struct MevSandwich {
+    // Identifiers
+    pub chain_id: u64,
+    pub block_number: u64,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    pub timestamp: OffsetDateTime,
+    pub pool_address: String,
+
+    // Actors (transaction `tx_from`)
+    pub attacker: String,
+    pub victim: String,
+
+    // The three swaps
+    pub frontrun_tx_hash: String,
+    pub frontrun_log_index: u32,
+    pub victim_tx_hash: String,
+    pub victim_log_index: u32,
+    pub backrun_tx_hash: String,
+    pub backrun_log_index: u32,
+
+    /// Token bought by the front-run and the victim, sold by the back-run
+    pub token_bought: String,
+
+    /// Attacker profit attributed to this victim (shares sum to the sandwich profit)
+    pub profit_usd: f64,
+    /// Value the victim lost compared to the front-run's execution price (lower bound)
+    pub victim_loss_usd: f64,
+}
@@ -1,21 +1,33 @@
+pub mod arbitrage;
 pub mod chain;
 pub mod checkpoint;
 pub mod event;
+pub mod mev_sandwich;
 pub mod native_token_price;
 pub mod new_pool;
 pub mod pool;
 pub mod pool_snapshot;
+pub mod price_feed;
+pub mod price_provenance;
 pub mod supply_event;
 pub mod token;
 pub mod token_snapshot;
+pub mod trade;
+pub mod trader_position;
 
-pub use chain::{ChainTokens, DatabaseChain};
+pub use arbitrage::Arbitrage;
+pub use chain::{ChainTokens, DatabaseChain, PricingThresholds};
 pub use checkpoint::SyncCheckpoint;
 pub use event::Event;
+pub use mev_sandwich::MevSandwich;
 pub use native_token_price::NativeTokenPrice;
 pub use new_pool::NewPool;
 pub use pool::Pool;
 pub use pool_snapshot::PoolSnapshot;
+pub use price_feed::{OraclePrice, PriceFeed};
+pub use price_provenance::PriceProvenance;
 pub use supply_event::SupplyEvent;
 pub use token::Token;
 pub use token_snapshot::TokenSnapshot;
+pub use trade::Trade;
+pub use trader_position::TraderPosition;
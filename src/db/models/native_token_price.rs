@@ -1,19 +1,51 @@
 use chrono::{DateTime, Utc};
+use log::warn;
 
 use super::Pool;
 
+/// Maximum relative deviation from the liquidity-weighted median before a
+/// reference pool is rejected as an outlier (5%)
+const MAX_REFERENCE_DEVIATION: f64 = 0.05;
+
+/// Price moves larger than this versus the previous value (25%) must be confirmed,
+/// either by a majority of reference liquidity or by the following updates
+const MAX_UNCONFIRMED_PRICE_JUMP: f64 = 0.25;
+
+/// Consecutive updates on the same side of the previous price after which an
+/// unconfirmed move is accepted (follows sustained crashes and rallies)
+const PRICE_JUMP_CONFIRMATION_UPDATES: u32 = 3;
+
+/// Divergence between the oracle and the DEX-derived price above which a warning is logged (5%)
+const MAX_ORACLE_DEX_DIVERGENCE: f64 = 0.05;
+
 /// Native token price per chain (PostgreSQL)
 ///
 /// Stores the current USD price of the native token (ETH, MATIC, etc.)
 /// for each chain. Used for calculating USD values of gas and native transfers.
 ///
-/// Price is derived from the stable pool (native/stablecoin pair) using:
+/// Price is derived from the chain's reference pools (native/stablecoin pairs) using:
 /// - V2: Reserve-based pricing (reserve_stable / reserve_native)
 /// - V3/V4: sqrtPriceX96-based pricing
+///
+/// Each pool price is weighted by the pool's liquidity and combined into a weighted
+/// median, so a single manipulated or drained pool cannot move the price on its own.
+///
+/// When a Chainlink feed for the native token has a fresh answer, it overrides the
+/// DEX-derived price and the two are compared as an independent check.
 #[derive(Debug, Clone)]
 pub struct NativeTokenPrice {
     pub chain_id: i64,
     pub price_usd: f64,
+    /// How the price was derived (`chainlink`, `dex_median`, `dex_single`)
+    pub source: String,
+    /// Reference pools that agreed with the published price
+    pub source_pools: Vec<String>,
+    /// Share of reference liquidity agreeing with the price, in [0, 1]
+    pub confidence: f64,
+    /// Large price move waiting for confirmation by the following updates (not persisted)
+    pub pending_price: Option<f64>,
+    /// Consecutive updates the pending move has been seen on the same side (not persisted)
+    pub pending_updates: u32,
     pub updated_at: DateTime<Utc>,
 }
 
@@ -22,20 +54,164 @@ impl NativeTokenPrice {
         Self {
             chain_id,
             price_usd,
+            source: String::new(),
+            source_pools: Vec::new(),
+            confidence: 0.0,
+            pending_price: None,
+            pending_updates: 0,
             updated_at: Utc::now(),
         }
     }
 
-    /// Update the native token price from the stable pool state.
-    ///
-    /// CRITICAL: This function now takes the wrapped native token address to verify
-    /// the stable pool actually contains the native token. This prevents misconfigured
-    /// pools from producing incorrect prices.
+    /// Update the native token price from the states of the chain's reference pools.
     ///
     /// ## Algorithm:
-    /// 1. Verify the pool contains the wrapped native token (as either token0 or token1)
-    /// 2. Determine which token in the pool is the native token
-    /// 3. Calculate: native_price_usd = stablecoin_amount / native_amount
+    /// 1. Derive a native USD price from every reference pool containing the native token
+    /// 2. Weight each pool by its liquidity (native reserve valued at the pool price + stable reserve)
+    /// 3. Take the liquidity-weighted median, then drop pools deviating more than 5% from it
+    /// 4. Publish the weighted median of the remaining pools
+    ///
+    /// A move of more than 25% versus the previous price is only accepted when at least two
+    /// pools holding the majority of the liquidity agree, when the next update lands within
+    /// 5% of the pending price, or after three consecutive updates on the same side of the
+    /// previous price. This rejects one-off manipulation (e.g. a flash-loan swap) while still
+    /// following genuine crashes and rallies with a short delay.
+    ///
+    /// Returns true if the price was updated.
+    pub fn update_from_reference_pools(
+        &mut self,
+        pools: &[&Pool],
+        wrapped_native_address: &str,
+    ) -> bool {
+        // (pool address, native price, liquidity weight)
+        let quotes: Vec<(&str, f64, f64)> = pools
+            .iter()
+            .filter_map(|pool| {
+                let price = Self::price_from_pool(pool, wrapped_native_address)?;
+                let weight = Self::reference_liquidity(pool, wrapped_native_address, price);
+                Some((pool.address.as_str(), price, weight))
+            })
+            .collect();
+
+        self.update_from_quotes(quotes)
+    }
+
+    /// Aggregate `(pool address, native price, liquidity weight)` quotes into the published
+    /// price. See [`Self::update_from_reference_pools`] for the algorithm.
+    fn update_from_quotes(&mut self, quotes: Vec<(&str, f64, f64)>) -> bool {
+        if quotes.is_empty() {
+            return false;
+        }
+
+        // Pools without known reserves fall back to equal weighting
+        let total_weight: f64 = quotes.iter().map(|(_, _, w)| w).sum();
+        let quotes: Vec<(&str, f64, f64)> = if total_weight > 0.0 {
+            quotes
+        } else {
+            quotes.into_iter().map(|(a, p, _)| (a, p, 1.0)).collect()
+        };
+        let total_weight: f64 = quotes.iter().map(|(_, _, w)| w).sum();
+
+        let Some(median) = weighted_median(quotes.iter().map(|(_, p, w)| (*p, *w))) else {
+            return false;
+        };
+
+        // Outlier rejection against the median
+        let agreeing: Vec<&(&str, f64, f64)> = quotes
+            .iter()
+            .filter(|(_, price, _)| (price / median - 1.0).abs() <= MAX_REFERENCE_DEVIATION)
+            .collect();
+
+        let Some(price) = weighted_median(agreeing.iter().map(|(_, p, w)| (*p, *w))) else {
+            return false;
+        };
+
+        let agreeing_weight: f64 = agreeing.iter().map(|(_, _, w)| w).sum();
+        let mut confidence = agreeing_weight / total_weight;
+        if agreeing.len() < 2 {
+            // A single pool is never fully trusted
+            confidence *= 0.5;
+        }
+
+        // Outlier rejection against the previous value
+        if self.price_usd > 0.0 {
+            let jump = (price / self.price_usd - 1.0).abs();
+            let confirmed_by_liquidity = agreeing.len() >= 2 && confidence > 0.5;
+            let confirmed_by_pending = self
+                .pending_price
+                .is_some_and(|pending| (price / pending - 1.0).abs() <= MAX_REFERENCE_DEVIATION);
+
+            // Consecutive unconfirmed moves in the same direction (sustained crash or rally)
+            let same_side = self
+                .pending_price
+                .is_some_and(|pending| (pending > self.price_usd) == (price > self.price_usd));
+            let pending_updates = if same_side { self.pending_updates + 1 } else { 1 };
+            let confirmed_by_persistence = pending_updates >= PRICE_JUMP_CONFIRMATION_UPDATES;
+
+            if jump > MAX_UNCONFIRMED_PRICE_JUMP
+                && !confirmed_by_liquidity
+                && !confirmed_by_pending
+                && !confirmed_by_persistence
+            {
+                warn!(
+                    "Chain {}: Native price move ${:.4} -> ${:.4} not confirmed by reference pools, holding previous price",
+                    self.chain_id, self.price_usd, price
+                );
+                self.pending_price = Some(price);
+                self.pending_updates = pending_updates;
+                return false;
+            }
+        }
+
+        self.price_usd = price;
+        self.source =
+            if agreeing.len() >= 2 { "dex_median".to_string() } else { "dex_single".to_string() };
+        self.source_pools = agreeing.iter().map(|(a, _, _)| a.to_string()).collect();
+        self.confidence = confidence;
+        self.pending_price = None;
+        self.pending_updates = 0;
+        self.updated_at = Utc::now();
+        true
+    }
+
+    /// Set the price from a Chainlink answer, which is authoritative over DEX prices.
+    ///
+    /// If the current price was just derived from the reference pools, it is compared
+    /// with the oracle and a warning is logged when they diverge by more than 5%.
+    ///
+    /// Returns true if the price was updated.
+    pub fn update_from_oracle(&mut self, price_usd: f64, aggregator_address: &str) -> bool {
+        if price_usd <= 0.0 || !price_usd.is_finite() {
+            return false;
+        }
+
+        if self.source.starts_with("dex") && self.price_usd > 0.0 {
+            let divergence = (self.price_usd / price_usd - 1.0).abs();
+            if divergence > MAX_ORACLE_DEX_DIVERGENCE {
+                warn!(
+                    "Chain {}: DEX native price ${:.4} diverges {:.1}% from oracle ${:.4}",
+                    self.chain_id,
+                    self.price_usd,
+                    divergence * 100.0,
+                    price_usd
+                );
+            }
+        }
+
+        self.price_usd = price_usd;
+        self.source = "chainlink".to_string();
+        self.source_pools = vec![aggregator_address.to_string()];
+        self.confidence = 1.0;
+        self.pending_price = None;
+        self.pending_updates = 0;
+        self.updated_at = Utc::now();
+        true
+    }
+
+    /// Native token USD price implied by a single native/stable pool.
+    ///
+    /// CRITICAL: Verifies the pool actually contains the wrapped native token.
+    /// This prevents misconfigured pools from producing incorrect prices.
     ///
     /// ## Price Convention:
     /// - pool.price = token1/token0 (always, Uniswap convention)
@@ -46,29 +222,24 @@ impl NativeTokenPrice {
     /// - If native is token0: we need token1 (stable) per token0 (native) = token1_price = price
     /// - If native is token1: we need token0 (stable) per token1 (native) = token0_price = 1/price
     ///
-    /// Returns true if the price was updated, false if pool doesn't contain native or no valid price.
-    pub fn update_from_pool(&mut self, pool: &Pool, wrapped_native_address: &str) -> bool {
-        // Get pool price - return false if no price
-        let Some(price) = pool.price else {
-            return false;
-        };
+    /// Returns None if the pool doesn't contain native or has no valid price.
+    fn price_from_pool(pool: &Pool, wrapped_native_address: &str) -> Option<f64> {
+        let price = pool.price?;
 
         // Basic validity check only
         if price <= 0.0 || !price.is_finite() {
-            return false;
+            return None;
         }
 
         let native_lower = wrapped_native_address.to_lowercase();
-        let token0_lower = pool.token0.to_lowercase();
-        let token1_lower = pool.token1.to_lowercase();
 
         // Determine which token is the native token
-        let native_is_token0 = token0_lower == native_lower;
-        let native_is_token1 = token1_lower == native_lower;
+        let native_is_token0 = pool.token0.to_lowercase() == native_lower;
+        let native_is_token1 = pool.token1.to_lowercase() == native_lower;
 
         // Verify the pool contains the native token
         if !native_is_token0 && !native_is_token1 {
-            return false;
+            return None;
         }
 
         // Calculate native token USD price based on which token is native
@@ -82,7 +253,7 @@ impl NativeTokenPrice {
             // price = native/stablecoin, so we need 1/price
             if price < 1e-15 {
                 // Avoid division by very small numbers
-                return false;
+                return None;
             }
             1.0 / price
         };
@@ -93,15 +264,31 @@ impl NativeTokenPrice {
         const MAX_NATIVE_PRICE: f64 = 1e6; // $1 million - no native token should exceed this
 
         if !native_price_usd.is_finite()
-            || native_price_usd < MIN_NATIVE_PRICE
-            || native_price_usd > MAX_NATIVE_PRICE
+            || !(MIN_NATIVE_PRICE..=MAX_NATIVE_PRICE).contains(&native_price_usd)
         {
-            return false;
+            return None;
         }
 
-        self.price_usd = native_price_usd;
-        self.updated_at = Utc::now();
-        true
+        Some(native_price_usd)
+    }
+
+    /// Approximate USD liquidity of a reference pool: native reserve valued at the
+    /// pool's own price plus the stable reserve (assumed to be worth $1).
+    fn reference_liquidity(pool: &Pool, wrapped_native_address: &str, native_price: f64) -> f64 {
+        let reserve0 = pool.reserve0_f64().unwrap_or(0.0).max(0.0);
+        let reserve1 = pool.reserve1_f64().unwrap_or(0.0).max(0.0);
+
+        let liquidity = if pool.token0.eq_ignore_ascii_case(wrapped_native_address) {
+            reserve0 * native_price + reserve1
+        } else {
+            reserve1 * native_price + reserve0
+        };
+
+        if liquidity.is_finite() {
+            liquidity
+        } else {
+            0.0
+        }
     }
 
     /// Check if this event type can impact the native token price.
@@ -117,3 +304,118 @@ impl NativeTokenPrice {
         )
     }
 }
+
+/// Weighted median of `(value, weight)` pairs: the smallest value at which the
+/// cumulative weight reaches half of the total. Returns None for empty input.
+fn weighted_median(values: impl Iterator<Item = (f64, f64)>) -> Option<f64> {
+    let mut values: Vec<(f64, f64)> = values.filter(|(v, w)| v.is_finite() && *w >= 0.0).collect();
+    if values.is_empty() {
+        return None;
+    }
+
+    values.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let half = values.iter().map(|(_, w)| w).sum::<f64>() / 2.0;
+    let mut cumulative = 0.0;
+    for (value, weight) in &values {
+        cumulative += weight;
+        if cumulative >= half {
+            return Some(*value);
+        }
+    }
+
+    values.last().map(|(v, _)| *v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price_at(price_usd: f64) -> NativeTokenPrice {
+        NativeTokenPrice::new(1, price_usd)
+    }
+
+    #[test]
+    fn test_weighted_median() {
+        assert_eq!(weighted_median(std::iter::empty()), None);
+        assert_eq!(weighted_median([(5.0, 1.0)].into_iter()), Some(5.0));
+        // Unweighted: lower middle value
+        assert_eq!(
+            weighted_median([(3.0, 1.0), (1.0, 1.0), (2.0, 1.0)].into_iter()),
+            Some(2.0)
+        );
+        // A single deep pool dominates
+        assert_eq!(
+            weighted_median([(1.0, 1.0), (2.0, 1.0), (10.0, 100.0)].into_iter()),
+            Some(10.0)
+        );
+        // Non-finite values and negative weights are ignored
+        assert_eq!(
+            weighted_median([(f64::NAN, 10.0), (4.0, -1.0), (7.0, 1.0)].into_iter()),
+            Some(7.0)
+        );
+    }
+
+    #[test]
+    fn test_outlier_pool_is_rejected() {
+        let mut native = price_at(0.0);
+        let updated = native.update_from_quotes(vec![
+            ("a", 2000.0, 100.0),
+            ("b", 2010.0, 100.0),
+            ("c", 3000.0, 50.0),
+        ]);
+
+        assert!(updated);
+        assert_eq!(native.price_usd, 2000.0);
+        assert_eq!(native.source, "dex_median");
+        assert_eq!(native.source_pools, vec!["a".to_string(), "b".to_string()]);
+        assert!((native.confidence - 0.8).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_single_pool_jump_is_held_then_confirmed_by_next_update() {
+        let mut native = price_at(2000.0);
+
+        assert!(!native.update_from_quotes(vec![("a", 1000.0, 1.0)]));
+        assert_eq!(native.price_usd, 2000.0);
+        assert_eq!(native.pending_price, Some(1000.0));
+
+        assert!(native.update_from_quotes(vec![("a", 1020.0, 1.0)]));
+        assert_eq!(native.price_usd, 1020.0);
+        assert_eq!(native.pending_price, None);
+        assert_eq!(native.source, "dex_single");
+    }
+
+    #[test]
+    fn test_sustained_crash_is_followed() {
+        let mut native = price_at(2000.0);
+
+        // Each update differs by more than 5% from the previous pending price
+        assert!(!native.update_from_quotes(vec![("a", 1400.0, 1.0)]));
+        assert!(!native.update_from_quotes(vec![("a", 1200.0, 1.0)]));
+        assert_eq!(native.price_usd, 2000.0);
+
+        assert!(native.update_from_quotes(vec![("a", 1000.0, 1.0)]));
+        assert_eq!(native.price_usd, 1000.0);
+        assert_eq!(native.pending_updates, 0);
+    }
+
+    #[test]
+    fn test_alternating_spikes_are_never_confirmed() {
+        let mut native = price_at(2000.0);
+
+        for spike in [3000.0, 1000.0, 3500.0, 800.0] {
+            assert!(!native.update_from_quotes(vec![("a", spike, 1.0)]));
+        }
+        assert_eq!(native.price_usd, 2000.0);
+        assert_eq!(native.pending_updates, 1);
+    }
+
+    #[test]
+    fn test_jump_backed_by_majority_liquidity_is_accepted() {
+        let mut native = price_at(2000.0);
+
+        assert!(native.update_from_quotes(vec![("a", 1000.0, 10.0), ("b", 1005.0, 10.0)]));
+        assert_eq!(native.price_usd, 1000.0);
+    }
+}
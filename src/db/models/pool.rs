@@ -1,13 +1,14 @@
 use alloy::primitives::{Address, U256};
 use chrono::{DateTime, Utc};
 use clickhouse::types::UInt256;
+use fastnum::D256;
 
 use crate::{
     abis::{v2, v3, v4},
-    db::models::{chain::ChainTokens, Event, Token},
+    db::models::{chain::ChainTokens, Event, PriceProvenance, Token},
     utils::{
-        bigint_add, bigint_sub, hex_encode, reserve_to_f64, sqrt_price_x96_str_to_adjusted_price,
-        validate_price_ratio,
+        bigint_add, bigint_sub, decimal_to_f64, hex_encode, reserve_to_decimal,
+        serialize_opt_decimal_as_f64, sqrt_price_x96_str_to_adjusted_price, validate_price_ratio,
     },
 };
 
@@ -75,8 +76,11 @@ pub struct Pool {
     // V2 state: reserves
     pub reserve0: Option<String>,
     pub reserve1: Option<String>,
-    pub reserve0_adjusted: Option<f64>,
-    pub reserve1_adjusted: Option<f64>,
+    /// Decimal-adjusted balances, exact (V2: from Sync, V3/V4: accumulated from events)
+    #[serde(serialize_with = "serialize_opt_decimal_as_f64")]
+    pub reserve0_adjusted: Option<D256>,
+    #[serde(serialize_with = "serialize_opt_decimal_as_f64")]
+    pub reserve1_adjusted: Option<D256>,
 
     // V3/V4 state: concentrated liquidity
     pub sqrt_price_x96: Option<String>,
@@ -92,6 +96,8 @@ pub struct Pool {
     pub token0_price: Option<f64>,
     pub token1_price: Option<f64>,
     pub price_usd: Option<f64>,
+    /// How `price_usd` was obtained (None while unpriced)
+    pub price_provenance: Option<PriceProvenance>,
 
     // Rolling window stats (24h / 7d)
     pub price_change_24h: Option<f64>,
@@ -99,6 +105,12 @@ pub struct Pool {
     pub volume_24h: Option<f64>,
     pub swaps_24h: Option<u64>,
 
+    // LP fee revenue (USD), maintained by the `update_pool_fees` cron job
+    pub fees_24h: Option<f64>,
+    pub fees_7d: Option<f64>,
+    /// `fees_7d` over the average 7d TVL, annualized (percentage)
+    pub fee_apr: Option<f64>,
+
     // Lifetime stats
     pub total_swaps: Option<u64>,
     pub total_volume_usd: Option<f64>,
@@ -162,10 +174,14 @@ impl Pool {
             token0_price: None,
             token1_price: None,
             price_usd: None,
+            price_provenance: None,
             price_change_24h: None,
             price_change_7d: None,
             volume_24h: None,
             swaps_24h: None,
+            fees_24h: None,
+            fees_7d: None,
+            fee_apr: None,
             total_swaps: None,
             total_volume_usd: None,
             tvl_usd: None,
@@ -214,9 +230,9 @@ impl Pool {
             tx_hash: Some(tx_hash),
             reserve0: None,
             reserve1: None,
-            reserve0_adjusted: Some(0.0), // Initialize with 0 for V3 balance tracking
-            reserve1_adjusted: Some(0.0), // Initialize with 0 for V3 balance tracking
-            liquidity: Some(String::from("0")), // Initialize with 0 liquidity for V3
+            reserve0_adjusted: Some(D256::ZERO), // Initialize with 0 for V3 balance tracking
+            reserve1_adjusted: Some(D256::ZERO), // Initialize with 0 for V3 balance tracking
+            liquidity: Some(String::from("0")),  // Initialize with 0 liquidity for V3
             sqrt_price_x96: None,
             tick: None,
             tick_spacing: Some(event.tickSpacing.as_i32()),
@@ -224,10 +240,14 @@ impl Pool {
             token0_price: None,
             token1_price: None,
             price_usd: None,
+            price_provenance: None,
             price_change_24h: None,
             price_change_7d: None,
             volume_24h: None,
             swaps_24h: None,
+            fees_24h: None,
+            fees_7d: None,
+            fee_apr: None,
             total_swaps: None,
             total_volume_usd: None,
             tvl_usd: None,
@@ -303,9 +323,9 @@ impl Pool {
             tx_hash: Some(tx_hash),
             reserve0: None,
             reserve1: None,
-            reserve0_adjusted: Some(0.0), // Initialize with 0 for V4 balance tracking
-            reserve1_adjusted: Some(0.0), // Initialize with 0 for V4 balance tracking
-            liquidity: Some(String::from("0")), // Initialize with 0 liquidity
+            reserve0_adjusted: Some(D256::ZERO), // Initialize with 0 for V4 balance tracking
+            reserve1_adjusted: Some(D256::ZERO), // Initialize with 0 for V4 balance tracking
+            liquidity: Some(String::from("0")),  // Initialize with 0 liquidity
             sqrt_price_x96: Some(sqrt_price_str),
             tick_spacing: Some(event.tickSpacing.as_i32()),
             tick: Some(event.tick.as_i32()),
@@ -313,10 +333,14 @@ impl Pool {
             token0_price,
             token1_price,
             price_usd: None,
+            price_provenance: None,
             price_change_24h: None,
             price_change_7d: None,
             volume_24h: None,
             swaps_24h: None,
+            fees_24h: None,
+            fees_7d: None,
+            fee_apr: None,
             total_swaps: None,
             total_volume_usd: None,
             tvl_usd: None,
@@ -370,18 +394,23 @@ impl Pool {
                 };
 
                 if update_reserves {
-                    let r0 = self.reserve0_adjusted.unwrap_or(0.0);
-                    let r1 = self.reserve1_adjusted.unwrap_or(0.0);
+                    let r0 = self.reserve0_adjusted.unwrap_or(D256::ZERO);
+                    let r1 = self.reserve1_adjusted.unwrap_or(D256::ZERO);
 
                     // direction: 1 = Out (Loss), -1 = In (Gain)
                     // delta = amount * -direction
                     // Example Swap Out: dir=1, delta = -amount
                     // Example Swap In: dir=-1, delta = amount
-                    let delta0 = event.amount0_adjusted * (-event.amount0_direction as f64);
-                    let delta1 = event.amount1_adjusted * (-event.amount1_direction as f64);
+                    // Exact decimal arithmetic, so balances do not drift over many events.
+                    let apply = |reserve: D256, amount: D256, direction: i8| {
+                        let next = if direction == 1 { reserve - amount } else { reserve + amount };
+                        next.max(D256::ZERO)
+                    };
 
-                    self.reserve0_adjusted = Some((r0 + delta0).max(0.0));
-                    self.reserve1_adjusted = Some((r1 + delta1).max(0.0));
+                    self.reserve0_adjusted =
+                        Some(apply(r0, event.amount0_adjusted, event.amount0_direction));
+                    self.reserve1_adjusted =
+                        Some(apply(r1, event.amount1_adjusted, event.amount1_direction));
                 }
             }
             // For V2 pools, price is updated via update_v2_reserves from Sync events
@@ -437,6 +466,16 @@ impl Pool {
         }
     }
 
+    /// Adjusted token0 balance as f64, for USD math.
+    pub fn reserve0_f64(&self) -> Option<f64> {
+        self.reserve0_adjusted.map(decimal_to_f64)
+    }
+
+    /// Adjusted token1 balance as f64, for USD math.
+    pub fn reserve1_f64(&self) -> Option<f64> {
+        self.reserve1_adjusted.map(decimal_to_f64)
+    }
+
     /// Update V2 pool reserves from Sync event.
     ///
     /// This sets reserves and calculates price from the reserve ratio.
@@ -452,12 +491,11 @@ impl Pool {
             self.reserve0 = Some(reserve0.to_string());
             self.reserve1 = Some(reserve1.to_string());
 
-            // Use BigDecimal-based conversion to avoid precision loss for large reserves
-            let r0_adjusted = reserve_to_f64(reserve0, self.token0_decimals);
-            let r1_adjusted = reserve_to_f64(reserve1, self.token1_decimals);
-
-            self.reserve0_adjusted = Some(r0_adjusted);
-            self.reserve1_adjusted = Some(r1_adjusted);
+            // Exact decimal reserves, f64 only for the price ratio
+            self.reserve0_adjusted = Some(reserve_to_decimal(reserve0, self.token0_decimals));
+            self.reserve1_adjusted = Some(reserve_to_decimal(reserve1, self.token1_decimals));
+            let r0_adjusted = self.reserve0_f64().unwrap_or(0.0);
+            let r1_adjusted = self.reserve1_f64().unwrap_or(0.0);
 
             // Calculate prices from reserves (Uniswap style)
             // price = always token1/token0
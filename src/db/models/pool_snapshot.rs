@@ -9,6 +9,7 @@ use time::OffsetDateTime;
 /// Query Patterns:
 ///   - "Get TVL history for pool X over 30 days"
 ///   - "Get volume trend for pool X"
+///   - "Get fee APR history for pool X"
 #[derive(Debug, Clone, Serialize, Row)]
 pub struct PoolSnapshot {
     // Identifiers
@@ -27,10 +28,13 @@ pub struct PoolSnapshot {
     pub reserve1: f64,
     pub liquidity: UInt256,
 
-    // Period metrics (rolling 24h at snapshot time)
+    // Period metrics (rolling windows at snapshot time)
     pub volume_24h: f64,
     pub swaps_24h: u64,
     pub fees_24h: f64,
+    pub fees_7d: f64,
+    /// Annualized `fees_7d` over the average 7d TVL (percentage)
+    pub fee_apr: f64,
 }
 
 impl PoolSnapshot {
@@ -47,6 +51,8 @@ impl PoolSnapshot {
         volume_24h: f64,
         swaps_24h: u64,
         fees_24h: f64,
+        fees_7d: f64,
+        fee_apr: f64,
     ) -> Self {
         Self {
             chain_id,
@@ -61,6 +67,8 @@ impl PoolSnapshot {
             volume_24h,
             swaps_24h,
             fees_24h,
+            fees_7d,
+            fee_apr,
         }
     }
 }
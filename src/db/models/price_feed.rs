@@ -0,0 +1,76 @@
+use alloy::primitives::U256;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::utils::u256_to_f64;
+
+/// Chainlink aggregator configured as a price source for a token (PostgreSQL)
+///
+/// `aggregator_address` must be the underlying aggregator that emits
+/// `AnswerUpdated`/`NewTransmission`, not the `EACAggregatorProxy` in front of it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct PriceFeed {
+    pub chain_id: u64,
+    pub aggregator_address: String,
+    pub token_address: String,
+    /// Decimals of the aggregator answer (8 for most USD feeds)
+    pub decimals: u8,
+    /// Quote currency of the answer: `usd` or `native`
+    #[serde(default = "default_denomination")]
+    pub denomination: String,
+    /// Answers older than this are considered stale and ignored
+    #[serde(default = "default_heartbeat_secs")]
+    pub heartbeat_secs: u64,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+fn default_denomination() -> String {
+    "usd".to_string()
+}
+
+fn default_heartbeat_secs() -> u64 {
+    86_400
+}
+
+impl PriceFeed {
+    pub fn is_native_denominated(&self) -> bool {
+        self.denomination.eq_ignore_ascii_case("native")
+    }
+
+    /// Raw aggregator answer scaled by the feed decimals, in the feed denomination
+    pub fn answer_to_price(&self, answer: U256) -> f64 {
+        u256_to_f64(answer, self.decimals)
+    }
+}
+
+/// Latest answer received from a price feed (PostgreSQL)
+///
+/// Persisted so workers can use oracle prices immediately after a restart
+/// instead of waiting for the next round.
+#[derive(Debug, Clone)]
+pub struct OraclePrice {
+    pub chain_id: i64,
+    pub aggregator_address: String,
+    pub token_address: String,
+    /// Answer divided by the feed decimals, in the feed denomination
+    pub price: f64,
+    pub round_id: String,
+    pub block_number: u64,
+    /// Block timestamp of the answer (unix seconds)
+    pub answered_at: u64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OraclePrice {
+    /// Whether the answer can be used at `block_timestamp`.
+    ///
+    /// Answers from a later block (e.g. after a re-sync from an earlier block)
+    /// and answers older than the feed heartbeat are rejected.
+    pub fn is_fresh(&self, block_timestamp: u64, heartbeat_secs: u64) -> bool {
+        self.answered_at <= block_timestamp
+            && block_timestamp - self.answered_at <= heartbeat_secs
+            && self.price > 0.0
+            && self.price.is_finite()
+    }
+}
@@ -0,0 +1,109 @@
+use serde::Serialize;
+
+/// Pool liquidity (USD) at which a hop no longer lowers confidence
+const FULL_CONFIDENCE_LIQUIDITY_USD: f64 = 1_000_000.0;
+
+/// Confidence kept per additional pool hop
+const HOP_CONFIDENCE_DECAY: f64 = 0.95;
+
+/// Confidence kept when a price was implied from swap amounts instead of pool state
+const IMPLIED_PRICE_CONFIDENCE: f64 = 0.8;
+
+/// How a USD price was obtained, attached to events, pools and tokens.
+///
+/// Confidence is 1.0 for stablecoins and fresh oracle answers, and drops with every
+/// pool hop according to the liquidity of that pool, so consumers can filter out
+/// prices resting on thin liquidity.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct PriceProvenance {
+    /// 0.0 (untrusted) to 1.0 (fixed or oracle)
+    pub confidence: f64,
+    /// Anchor the price comes from: `stable`, `native`, `oracle`
+    pub source: String,
+    /// How the last step was computed: `fixed`, `oracle`, `sqrt_price`, `reserves`, `implied`
+    pub method: String,
+    /// Pools along the route (or the oracle aggregator), anchor first
+    pub source_pools: Vec<String>,
+    /// Number of pools between the price and its anchor
+    pub hops: u32,
+    /// USD liquidity of the weakest hop (0 when no pool is involved)
+    pub min_liquidity_usd: f64,
+}
+
+impl PriceProvenance {
+    /// Stablecoin pegged at $1.
+    pub fn stable() -> Self {
+        Self {
+            confidence: 1.0,
+            source: "stable".to_string(),
+            method: "fixed".to_string(),
+            source_pools: Vec::new(),
+            hops: 0,
+            min_liquidity_usd: 0.0,
+        }
+    }
+
+    /// Fresh answer from a Chainlink aggregator.
+    pub fn oracle(aggregator_address: &str) -> Self {
+        Self {
+            confidence: 1.0,
+            source: "oracle".to_string(),
+            method: "oracle".to_string(),
+            source_pools: vec![aggregator_address.to_string()],
+            hops: 0,
+            min_liquidity_usd: 0.0,
+        }
+    }
+
+    /// Native token price as published by `NativeTokenPrice`.
+    pub fn native(confidence: f64, native_source: &str, source_pools: &[String]) -> Self {
+        let method = if native_source == "chainlink" { "oracle" } else { "sqrt_price" };
+
+        Self {
+            confidence: confidence.clamp(0.0, 1.0),
+            source: "native".to_string(),
+            method: method.to_string(),
+            source_pools: source_pools.to_vec(),
+            hops: 0,
+            min_liquidity_usd: 0.0,
+        }
+    }
+
+    /// Extend this price by one pool hop.
+    ///
+    /// `liquidity_usd` is the value of the known side of the pool. Confidence is capped by
+    /// the weakest hop and decays with every hop after the first.
+    pub fn through_pool(&self, pool_address: &str, liquidity_usd: f64, method: &str) -> Self {
+        let hop_confidence = (liquidity_usd / FULL_CONFIDENCE_LIQUIDITY_USD)
+            .sqrt()
+            .clamp(0.0, 1.0);
+        let decay = if self.hops == 0 { 1.0 } else { HOP_CONFIDENCE_DECAY };
+        let method_confidence = if method == "implied" { IMPLIED_PRICE_CONFIDENCE } else { 1.0 };
+
+        let mut source_pools = self.source_pools.clone();
+        source_pools.push(pool_address.to_string());
+
+        Self {
+            confidence: self.confidence.min(hop_confidence) * decay * method_confidence,
+            source: self.source.clone(),
+            method: method.to_string(),
+            source_pools,
+            hops: self.hops + 1,
+            min_liquidity_usd: if self.hops == 0 {
+                liquidity_usd
+            } else {
+                self.min_liquidity_usd.min(liquidity_usd)
+            },
+        }
+    }
+
+    /// Same route, but the last step used the price implied by swap amounts.
+    pub fn implied(&self) -> Self {
+        let mut provenance = self.clone();
+        if provenance.method != "implied" {
+            provenance.method = "implied".to_string();
+            provenance.confidence *= IMPLIED_PRICE_CONFIDENCE;
+        }
+        provenance
+    }
+}
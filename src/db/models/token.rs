@@ -1,5 +1,7 @@
 use chrono::{DateTime, Utc};
 
+use super::PriceProvenance;
+
 /// Token metadata and current market state (PostgreSQL)
 ///
 /// Primary Key: (chain_id, address)
@@ -18,6 +20,8 @@ pub struct Token {
     // Current price state
     pub price_usd: Option<f64>,
     pub price_updated_at: Option<DateTime<Utc>>,
+    /// How `price_usd` was obtained (None while unpriced)
+    pub price_provenance: Option<PriceProvenance>,
 
     // Price changes (rolling windows)
     pub price_change_24h: Option<f64>,
@@ -64,6 +68,7 @@ impl Token {
             decimals,
             price_usd: None,
             price_updated_at: None,
+            price_provenance: None,
             price_change_24h: None,
             price_change_7d: None,
             logo_url: None,
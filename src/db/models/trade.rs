@@ -0,0 +1,54 @@
+use clickhouse::Row;
+use fastnum::D256;
+use serde::Serialize;
+use time::OffsetDateTime;
+
+use crate::utils::serialize_decimal_as_f64;
+
+/// A user trade reconstructed from the swap hops of one transaction
+/// (ClickHouse `trades`).
+///
+/// Population: Built by the worker from the `swap` events of each batch. A trade
+/// routed A→B→C through two pools is two `events` rows but one `trades` row, so
+/// chain-wide volume summed over `trades` is not double-counted.
+///
+/// Query Patterns:
+///   - "Get trade history of trader X"
+///   - "Most traded pairs (token_in, token_out)"
+///   - "Routed vs direct volume"
+#[derive(Debug, Clone, Serialize, Row)]
+pub struct Trade {
+    // Identifiers
+    pub chain_id: u64,
+    pub block_number: u64,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    pub timestamp: OffsetDateTime,
+    pub tx_hash: String,
+    /// `log_index` of the first and last hop
+    pub first_log_index: u32,
+    pub last_log_index: u32,
+
+    /// `maker` of the first hop
+    pub trader: String,
+
+    // Input and output (decimal-adjusted)
+    pub token_in: String,
+    pub token_out: String,
+    #[serde(serialize_with = "serialize_decimal_as_f64")]
+    pub amount_in: D256,
+    #[serde(serialize_with = "serialize_decimal_as_f64")]
+    pub amount_out: D256,
+
+    // Route
+    /// Pools in hop order
+    pub route: Vec<String>,
+    /// Tokens in hop order, `token_in` first and `token_out` last
+    pub path: Vec<String>,
+    pub hops: u8,
+
+    // USD (0 when the token price is unknown)
+    pub amount_in_usd: f64,
+    pub amount_out_usd: f64,
+    /// Trade value: `amount_in_usd`, or `amount_out_usd` when the input is unpriced
+    pub volume_usd: f64,
+}
@@ -0,0 +1,166 @@
+use clickhouse::Row;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Quantities below this are treated as a closed position (float dust)
+const DUST_QUANTITY: f64 = 1e-12;
+
+/// Position of a wallet in one token, with average-cost PnL accounting
+/// (ClickHouse `trader_positions`).
+///
+/// Population: The `update_trader_positions` cron job replays new swaps in block
+/// order and re-marks open positions to the current `Token.price_usd`. Each refresh
+/// inserts a new version of the row (ReplacingMergeTree on `updated_at`).
+///
+/// Query Patterns:
+///   - "Get open positions and PnL of wallet X"
+///   - "Leaderboard by realized / total PnL" (see the `trader_pnl` view)
+///   - "Top holders of token X by cost basis"
+#[derive(Debug, Clone, Serialize, Deserialize, Row)]
+pub struct TraderPosition {
+    // Identifiers
+    pub chain_id: u64,
+    pub wallet: String,
+    pub token_address: String,
+
+    // Position (decimal-adjusted quantity, USD cost of the quantity held)
+    pub quantity: f64,
+    pub cost_basis_usd: f64,
+
+    // PnL
+    pub realized_pnl_usd: f64,
+    /// quantity * mark_price_usd - cost_basis_usd
+    pub unrealized_pnl_usd: f64,
+    /// `Token.price_usd` at the last refresh
+    pub mark_price_usd: f64,
+
+    // Activity
+    pub total_bought_usd: f64,
+    pub total_sold_usd: f64,
+    pub buys: u64,
+    pub sells: u64,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    pub first_trade: OffsetDateTime,
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    pub last_trade: OffsetDateTime,
+    /// Block of the last swap applied (the job's per-chain checkpoint)
+    pub last_block_number: u64,
+
+    #[serde(with = "clickhouse::serde::time::datetime")]
+    pub updated_at: OffsetDateTime,
+}
+
+impl TraderPosition {
+    pub fn new(chain_id: u64, wallet: String, token_address: String, at: OffsetDateTime) -> Self {
+        Self {
+            chain_id,
+            wallet,
+            token_address,
+            quantity: 0.0,
+            cost_basis_usd: 0.0,
+            realized_pnl_usd: 0.0,
+            unrealized_pnl_usd: 0.0,
+            mark_price_usd: 0.0,
+            total_bought_usd: 0.0,
+            total_sold_usd: 0.0,
+            buys: 0,
+            sells: 0,
+            first_trade: at,
+            last_trade: at,
+            last_block_number: 0,
+            updated_at: at,
+        }
+    }
+
+    /// Average cost per unit of the quantity held
+    pub fn avg_cost_usd(&self) -> f64 {
+        if self.quantity > DUST_QUANTITY {
+            self.cost_basis_usd / self.quantity
+        } else {
+            0.0
+        }
+    }
+
+    /// Add `quantity` bought for `value_usd` to the position.
+    pub fn apply_buy(&mut self, quantity: f64, value_usd: f64, at: OffsetDateTime, block: u64) {
+        self.quantity += quantity;
+        self.cost_basis_usd += value_usd;
+        self.total_bought_usd += value_usd;
+        self.buys += 1;
+        self.touch(at, block);
+    }
+
+    /// Remove `quantity` sold for `proceeds_usd`, realizing PnL at the average cost.
+    ///
+    /// Only the part covered by the tracked position is realized: tokens received
+    /// outside swaps (transfers, airdrops) have no known cost basis.
+    pub fn apply_sell(&mut self, quantity: f64, proceeds_usd: f64, at: OffsetDateTime, block: u64) {
+        let matched = quantity.min(self.quantity.max(0.0));
+        if matched > 0.0 && quantity > 0.0 {
+            let cost = matched * self.avg_cost_usd();
+            self.realized_pnl_usd += proceeds_usd * (matched / quantity) - cost;
+            self.cost_basis_usd -= cost;
+            self.quantity -= matched;
+        }
+        if self.quantity <= DUST_QUANTITY {
+            self.quantity = 0.0;
+            self.cost_basis_usd = 0.0;
+        }
+
+        self.total_sold_usd += proceeds_usd;
+        self.sells += 1;
+        self.touch(at, block);
+    }
+
+    /// Mark the position to `price_usd` (ignored when the price is unknown).
+    pub fn mark(&mut self, price_usd: f64) {
+        if price_usd > 0.0 {
+            self.mark_price_usd = price_usd;
+        }
+        self.unrealized_pnl_usd = self.quantity * self.mark_price_usd - self.cost_basis_usd;
+    }
+
+    fn touch(&mut self, at: OffsetDateTime, block: u64) {
+        if self.buys + self.sells == 1 {
+            self.first_trade = at;
+        }
+        self.last_trade = self.last_trade.max(at);
+        self.last_block_number = self.last_block_number.max(block);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_average_cost_realized_and_unrealized_pnl() {
+        let at = OffsetDateTime::UNIX_EPOCH;
+        let mut position = TraderPosition::new(1, "0xwallet".into(), "0xtoken".into(), at);
+
+        position.apply_buy(10.0, 100.0, at, 1); // 10 @ $10
+        position.apply_buy(10.0, 300.0, at, 2); // 10 @ $30, avg $20
+        position.apply_sell(5.0, 250.0, at, 3); // 5 @ $50: +$150 realized
+
+        assert_eq!(position.quantity, 15.0);
+        assert!((position.realized_pnl_usd - 150.0).abs() < 1e-9);
+        assert!((position.cost_basis_usd - 300.0).abs() < 1e-9);
+
+        position.mark(40.0);
+        assert!((position.unrealized_pnl_usd - 300.0).abs() < 1e-9);
+        assert_eq!(position.last_block_number, 3);
+    }
+
+    #[test]
+    fn test_sell_beyond_position_only_realizes_tracked_part() {
+        let at = OffsetDateTime::UNIX_EPOCH;
+        let mut position = TraderPosition::new(1, "0xwallet".into(), "0xtoken".into(), at);
+
+        position.apply_buy(1.0, 10.0, at, 1);
+        position.apply_sell(2.0, 40.0, at, 2); // Half the sale has no known cost
+
+        assert_eq!(position.quantity, 0.0);
+        assert!((position.realized_pnl_usd - 10.0).abs() < 1e-9);
+        assert_eq!(position.total_sold_usd, 40.0);
+    }
+}
@@ -1,9 +1,18 @@
 use anyhow::Context;
-use deadpool_postgres::{Manager, ManagerConfig, Pool, RecyclingMethod};
+use deadpool_postgres::{GenericClient, Manager, ManagerConfig, Object, Pool, RecyclingMethod};
 use log::info;
 use tokio_postgres::NoTls;
 
-use crate::config::PostgresSettings;
+use crate::{
+    config::PostgresSettings,
+    db::migrations::{self, AppliedMigration, MigrationStatus},
+};
+
+/// Migrations applied by `PostgresClient::migrate`
+const MIGRATIONS_DIR: &str = "schema/postgres";
+
+/// `pg_advisory_lock` key serializing `Database::migrate` across processes
+const MIGRATION_LOCK_ID: i64 = 0x72756e6963; // "runic"
 
 /// Split SQL into statements, respecting dollar-quoted strings.
 /// This handles PostgreSQL function definitions that use $$ ... $$ blocks.
@@ -130,25 +139,58 @@ impl PostgresClient {
         Ok(())
     }
 
+    /// Applies the pending migrations of `schema/postgres`, then the optional
+    /// `schema/inserts_postgres.sql` seed.
+    ///
+    /// All pending migrations run in one transaction, rolled back if any fails.
     pub async fn migrate(&self) -> anyhow::Result<()> {
         info!("Running PostgreSQL migrations");
-        let client = self.pool.get().await?;
-
-        let schema = tokio::fs::read_to_string("schema/postgres.sql")
+        let mut client = self.pool.get().await?;
+        client
+            .batch_execute(
+                r#"
+                CREATE SCHEMA IF NOT EXISTS indexer;
+                CREATE TABLE IF NOT EXISTS indexer.schema_migrations (
+                    version     INTEGER PRIMARY KEY,
+                    name        TEXT NOT NULL,
+                    checksum    TEXT NOT NULL,
+                    applied_at  TIMESTAMPTZ NOT NULL DEFAULT NOW()
+                );
+                "#,
+            )
             .await
-            .context("Failed to read schema/postgres.sql")?;
+            .context("Failed to create indexer.schema_migrations")?;
+
+        let migrations = migrations::load(MIGRATIONS_DIR).await?;
+        let transaction = client.transaction().await?;
+        let applied = applied_migrations(&transaction).await?;
 
-        // Split SQL statements properly, respecting dollar-quoted strings (e.g., function bodies)
-        for stmt in split_sql_statements(&schema) {
-            let stmt = stmt.trim();
-            if stmt.is_empty() {
-                continue;
+        for migration in migrations::pending(&migrations, &applied)? {
+            info!(
+                "Applying PostgreSQL migration {}_{}",
+                migration.version, migration.name
+            );
+            // Split SQL statements properly, respecting dollar-quoted strings (e.g., function bodies)
+            for stmt in split_sql_statements(&migration.sql) {
+                let stmt = stmt.trim();
+                if stmt.is_empty() {
+                    continue;
+                }
+                transaction.execute(stmt, &[]).await.with_context(|| {
+                    format!(
+                        "Migration {}_{} failed at statement: {}",
+                        migration.version, migration.name, stmt
+                    )
+                })?;
             }
-            client
-                .execute(stmt, &[])
-                .await
-                .with_context(|| format!("Failed to execute migration statement: {}", stmt))?;
+            transaction
+                .execute(
+                    "INSERT INTO indexer.schema_migrations (version, name, checksum) VALUES ($1, $2, $3)",
+                    &[&(migration.version as i32), &migration.name, &migration.checksum],
+                )
+                .await?;
         }
+        transaction.commit().await?;
 
         info!("PostgreSQL schema applied successfully");
 
@@ -170,4 +212,68 @@ impl PostgresClient {
         info!("PostgreSQL migrations completed successfully");
         Ok(())
     }
+
+    /// Applied and pending migrations, without changing the database.
+    pub async fn migration_status(&self) -> anyhow::Result<Vec<MigrationStatus>> {
+        let migrations = migrations::load(MIGRATIONS_DIR).await?;
+        let client = self.pool.get().await?;
+        let tracked: bool = client
+            .query_one(
+                "SELECT to_regclass('indexer.schema_migrations') IS NOT NULL",
+                &[],
+            )
+            .await?
+            .get(0);
+        let applied = if tracked { applied_migrations(&client).await? } else { Vec::new() };
+        Ok(migrations::status(&migrations, &applied))
+    }
+
+    /// Holds a session-level advisory lock until `MigrationLock::release`, so only
+    /// one process migrates the databases at a time.
+    pub async fn lock_migrations(&self) -> anyhow::Result<MigrationLock> {
+        let client = self.pool.get().await?;
+        info!("Waiting for the migration lock");
+        client
+            .execute("SELECT pg_advisory_lock($1)", &[&MIGRATION_LOCK_ID])
+            .await
+            .context("Failed to acquire the migration lock")?;
+        Ok(MigrationLock {
+            client,
+        })
+    }
+}
+
+/// Advisory lock held while migrating
+pub struct MigrationLock {
+    client: Object,
+}
+
+impl MigrationLock {
+    pub async fn release(self) -> anyhow::Result<()> {
+        self.client
+            .execute("SELECT pg_advisory_unlock($1)", &[&MIGRATION_LOCK_ID])
+            .await
+            .context("Failed to release the migration lock")?;
+        Ok(())
+    }
+}
+
+async fn applied_migrations(client: &impl GenericClient) -> anyhow::Result<Vec<AppliedMigration>> {
+    let rows = client
+        .query(
+            "SELECT version, name, checksum, applied_at FROM indexer.schema_migrations ORDER BY version",
+            &[],
+        )
+        .await
+        .context("Failed to read indexer.schema_migrations")?;
+
+    Ok(rows
+        .iter()
+        .map(|row| AppliedMigration {
+            version: row.get::<_, i32>("version") as u32,
+            name: row.get("name"),
+            checksum: row.get("checksum"),
+            applied_at: row.get("applied_at"),
+        })
+        .collect())
 }
@@ -1,7 +1,16 @@
-use log::error;
+use std::collections::HashMap;
 
-use crate::db::models::{DatabaseChain, NativeTokenPrice, Pool, SyncCheckpoint, Token};
+use log::{error, info};
+
+use tokio_postgres::types::ToSql;
+
+use crate::db::clickhouse::PurgedPools;
+use crate::db::models::{
+    DatabaseChain, NativeTokenPrice, OraclePrice, Pool, PriceFeed, PriceProvenance,
+    PricingThresholds, SyncCheckpoint, Token,
+};
 use crate::db::postgres::PostgresClient;
+use crate::utils::str_to_decimal;
 
 /// Sanitize a string for PostgreSQL by removing null bytes (0x00)
 /// which are invalid in UTF-8 text columns
@@ -19,12 +28,14 @@ impl PostgresClient {
             SELECT 
                 chain_id, name, rpc_url, hypersync_url, enabled,
                 native_token_address, native_token_decimals, native_token_name, native_token_symbol,
-                stable_token_address, stable_token_decimals, stable_pool_address,
-                major_tokens, stablecoins, factories, updated_at
+                stable_token_address, stable_token_decimals, stable_pool_address, reference_pools,
+                major_tokens, stablecoins, factories, supply_excluded_addresses, pricing_strategy,
+                min_pool_tvl_usd, min_liquidity_usd, max_price_divergence, updated_at
             FROM indexer.chains
         "#;
 
         let rows = client.query(query, &[]).await?;
+        let mut price_feeds = self.get_price_feeds().await?;
         let chains = rows
             .iter()
             .map(|row| {
@@ -32,11 +43,30 @@ impl PostgresClient {
                 let native_token_address: String = row.get("native_token_address");
                 let stable_token_address: String = row.get("stable_token_address");
                 let stable_pool_address: String = row.get("stable_pool_address");
+                let reference_pools: Vec<String> = row
+                    .get::<_, Option<Vec<String>>>("reference_pools")
+                    .unwrap_or_default();
                 let major_tokens: Vec<String> = row.get("major_tokens");
                 let stablecoins: Vec<String> = row.get("stablecoins");
                 let factories: Vec<String> = row.get("factories");
+                let supply_excluded_addresses: Vec<String> = row
+                    .get::<_, Option<Vec<String>>>("supply_excluded_addresses")
+                    .unwrap_or_default();
 
                 let chain_id: i64 = row.get("chain_id");
+                let price_feeds = price_feeds.remove(&(chain_id as u64)).unwrap_or_default();
+                let defaults = PricingThresholds::default();
+                let pricing_thresholds = PricingThresholds {
+                    min_pool_tvl_usd: row
+                        .get::<_, Option<f64>>("min_pool_tvl_usd")
+                        .unwrap_or(defaults.min_pool_tvl_usd),
+                    min_liquidity_usd: row
+                        .get::<_, Option<f64>>("min_liquidity_usd")
+                        .unwrap_or(defaults.min_liquidity_usd),
+                    max_price_divergence: row
+                        .get::<_, Option<f64>>("max_price_divergence")
+                        .unwrap_or(defaults.max_price_divergence),
+                };
                 let native_decimals: i16 = row.get("native_token_decimals");
                 let stable_decimals: i16 = row.get("stable_token_decimals");
 
@@ -53,9 +83,22 @@ impl PostgresClient {
                     stable_token_address: stable_token_address.to_lowercase(),
                     stable_token_decimals: stable_decimals as u8,
                     stable_pool_address: stable_pool_address.to_lowercase(),
+                    reference_pools: reference_pools
+                        .into_iter()
+                        .map(|s| s.to_lowercase())
+                        .collect(),
                     major_tokens: major_tokens.into_iter().map(|s| s.to_lowercase()).collect(),
                     stablecoins: stablecoins.into_iter().map(|s| s.to_lowercase()).collect(),
                     factories: factories.into_iter().map(|s| s.to_lowercase()).collect(),
+                    supply_excluded_addresses: supply_excluded_addresses
+                        .into_iter()
+                        .map(|s| s.to_lowercase())
+                        .collect(),
+                    price_feeds,
+                    pricing_strategy: row
+                        .get::<_, Option<String>>("pricing_strategy")
+                        .unwrap_or_else(|| "default".to_string()),
+                    pricing_thresholds,
                     updated_at: row.get("updated_at"),
                 }
             })
@@ -64,16 +107,22 @@ impl PostgresClient {
         Ok(chains)
     }
 
-    /// Insert or update a single chain
+    /// Insert or update a single chain, with its price feeds.
+    ///
+    /// Feeds of the chain that are no longer listed in `chain.price_feeds` are disabled.
     pub async fn set_chain(&self, chain: &DatabaseChain) -> anyhow::Result<()> {
         let client = self.pool.get().await?;
         let query = r#"
             INSERT INTO indexer.chains (
                 chain_id, name, rpc_url, hypersync_url, enabled,
                 native_token_address, native_token_decimals, native_token_name, native_token_symbol,
-                stable_token_address, stable_token_decimals, stable_pool_address,
-                major_tokens, stablecoins, factories, updated_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                stable_token_address, stable_token_decimals, stable_pool_address, reference_pools,
+                major_tokens, stablecoins, factories, supply_excluded_addresses, pricing_strategy,
+                min_pool_tvl_usd, min_liquidity_usd, max_price_divergence, updated_at
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18,
+                $19, $20, $21, $22
+            )
             ON CONFLICT (chain_id) DO UPDATE SET
                 name = EXCLUDED.name,
                 rpc_url = EXCLUDED.rpc_url,
@@ -86,9 +135,15 @@ impl PostgresClient {
                 stable_token_address = EXCLUDED.stable_token_address,
                 stable_token_decimals = EXCLUDED.stable_token_decimals,
                 stable_pool_address = EXCLUDED.stable_pool_address,
+                reference_pools = EXCLUDED.reference_pools,
                 major_tokens = EXCLUDED.major_tokens,
                 stablecoins = EXCLUDED.stablecoins,
                 factories = EXCLUDED.factories,
+                supply_excluded_addresses = EXCLUDED.supply_excluded_addresses,
+                pricing_strategy = EXCLUDED.pricing_strategy,
+                min_pool_tvl_usd = EXCLUDED.min_pool_tvl_usd,
+                min_liquidity_usd = EXCLUDED.min_liquidity_usd,
+                max_price_divergence = EXCLUDED.max_price_divergence,
                 updated_at = EXCLUDED.updated_at
         "#;
 
@@ -112,9 +167,15 @@ impl PostgresClient {
                     &chain.stable_token_address,
                     &stable_decimals_i16,
                     &chain.stable_pool_address,
+                    &chain.reference_pools,
                     &chain.major_tokens,
                     &chain.stablecoins,
                     &chain.factories,
+                    &chain.supply_excluded_addresses,
+                    &chain.pricing_strategy,
+                    &chain.pricing_thresholds.min_pool_tvl_usd,
+                    &chain.pricing_thresholds.min_liquidity_usd,
+                    &chain.pricing_thresholds.max_price_divergence,
                     &chain.updated_at,
                 ],
             )
@@ -124,6 +185,213 @@ impl PostgresClient {
                 e
             })?;
 
+        for feed in &chain.price_feeds {
+            self.set_price_feed(feed).await?;
+        }
+        self.disable_unlisted_price_feeds(chain).await?;
+
+        Ok(())
+    }
+
+    // ==================== PRICE FEEDS ====================
+
+    /// Get all enabled price feeds, grouped by chain
+    pub async fn get_price_feeds(&self) -> anyhow::Result<HashMap<u64, Vec<PriceFeed>>> {
+        let client = self.pool.get().await?;
+        let query = r#"
+            SELECT
+                chain_id, aggregator_address, token_address, decimals,
+                denomination, heartbeat_secs, description
+            FROM indexer.price_feeds
+            WHERE enabled
+            ORDER BY chain_id, aggregator_address
+        "#;
+
+        let rows = client.query(query, &[]).await?;
+        let mut feeds: HashMap<u64, Vec<PriceFeed>> = HashMap::new();
+
+        for row in &rows {
+            let chain_id: i64 = row.get("chain_id");
+            let aggregator_address: String = row.get("aggregator_address");
+            let token_address: String = row.get("token_address");
+            let decimals: i16 = row.get("decimals");
+            let heartbeat_secs: i64 = row.get("heartbeat_secs");
+
+            feeds.entry(chain_id as u64).or_default().push(PriceFeed {
+                chain_id: chain_id as u64,
+                aggregator_address: aggregator_address.to_lowercase(),
+                token_address: token_address.to_lowercase(),
+                decimals: decimals as u8,
+                denomination: row.get("denomination"),
+                heartbeat_secs: heartbeat_secs as u64,
+                description: row.get("description"),
+            });
+        }
+
+        Ok(feeds)
+    }
+
+    /// Insert or update a single price feed (enables it)
+    pub async fn set_price_feed(&self, feed: &PriceFeed) -> anyhow::Result<()> {
+        let client = self.pool.get().await?;
+        let query = r#"
+            INSERT INTO indexer.price_feeds (
+                chain_id, aggregator_address, token_address, decimals,
+                denomination, heartbeat_secs, description, enabled
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, TRUE)
+            ON CONFLICT (chain_id, aggregator_address) DO UPDATE SET
+                token_address = EXCLUDED.token_address,
+                decimals = EXCLUDED.decimals,
+                denomination = EXCLUDED.denomination,
+                heartbeat_secs = EXCLUDED.heartbeat_secs,
+                description = EXCLUDED.description,
+                enabled = TRUE
+        "#;
+
+        let chain_id_i64 = feed.chain_id as i64;
+        let decimals_i16 = feed.decimals as i16;
+        let heartbeat_i64 = feed.heartbeat_secs as i64;
+
+        client
+            .execute(
+                query,
+                &[
+                    &chain_id_i64,
+                    &feed.aggregator_address.to_lowercase(),
+                    &feed.token_address.to_lowercase(),
+                    &decimals_i16,
+                    &feed.denomination,
+                    &heartbeat_i64,
+                    &feed.description,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!(
+                    "Failed to insert price feed {} for chain {}: {:?}",
+                    feed.aggregator_address, feed.chain_id, e
+                );
+                e
+            })?;
+
+        Ok(())
+    }
+
+    /// Disable the chain's price feeds that are not listed in `chain.price_feeds`,
+    /// so removed aggregators stop pricing tokens
+    async fn disable_unlisted_price_feeds(&self, chain: &DatabaseChain) -> anyhow::Result<()> {
+        let client = self.pool.get().await?;
+        let query = r#"
+            UPDATE indexer.price_feeds
+            SET enabled = FALSE
+            WHERE chain_id = $1 AND enabled AND NOT (aggregator_address = ANY($2))
+        "#;
+
+        let chain_id_i64 = chain.chain_id as i64;
+        let listed: Vec<String> = chain
+            .price_feeds
+            .iter()
+            .map(|feed| feed.aggregator_address.to_lowercase())
+            .collect();
+
+        let disabled = client.execute(query, &[&chain_id_i64, &listed]).await?;
+        if disabled > 0 {
+            info!(
+                "Chain {}: disabled {} price feeds no longer configured",
+                chain.chain_id, disabled
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Get the latest persisted oracle answers for a chain
+    pub async fn get_oracle_prices(&self, chain_id: i64) -> anyhow::Result<Vec<OraclePrice>> {
+        let client = self.pool.get().await?;
+        let query = r#"
+            SELECT
+                chain_id, aggregator_address, token_address, price, round_id,
+                block_number, answered_at, updated_at
+            FROM indexer.oracle_prices
+            WHERE chain_id = $1
+        "#;
+
+        let rows = client.query(query, &[&chain_id]).await?;
+        let prices = rows
+            .iter()
+            .map(|row| OraclePrice {
+                chain_id: row.get("chain_id"),
+                aggregator_address: row.get("aggregator_address"),
+                token_address: row.get("token_address"),
+                price: row.get("price"),
+                round_id: row.get("round_id"),
+                block_number: row.get::<_, i64>("block_number") as u64,
+                answered_at: row.get::<_, i64>("answered_at") as u64,
+                updated_at: row.get("updated_at"),
+            })
+            .collect();
+
+        Ok(prices)
+    }
+
+    /// Batch upsert the latest oracle answers
+    pub async fn set_oracle_prices(&self, prices: &[&OraclePrice]) -> anyhow::Result<()> {
+        if prices.is_empty() {
+            return Ok(());
+        }
+
+        let client = self.pool.get().await?;
+        let query = r#"
+            INSERT INTO indexer.oracle_prices (
+                chain_id, aggregator_address, token_address, price, round_id,
+                block_number, answered_at, updated_at
+            )
+            SELECT * FROM UNNEST(
+                $1::bigint[], $2::text[], $3::text[], $4::float8[], $5::text[],
+                $6::bigint[], $7::bigint[], $8::timestamptz[]
+            )
+            ON CONFLICT (chain_id, aggregator_address) DO UPDATE SET
+                token_address = EXCLUDED.token_address,
+                price = EXCLUDED.price,
+                round_id = EXCLUDED.round_id,
+                block_number = EXCLUDED.block_number,
+                answered_at = EXCLUDED.answered_at,
+                updated_at = EXCLUDED.updated_at
+        "#;
+
+        let chain_ids: Vec<i64> = prices.iter().map(|p| p.chain_id).collect();
+        let aggregators: Vec<&str> = prices
+            .iter()
+            .map(|p| p.aggregator_address.as_str())
+            .collect();
+        let tokens: Vec<&str> = prices.iter().map(|p| p.token_address.as_str()).collect();
+        let values: Vec<f64> = prices.iter().map(|p| p.price).collect();
+        let round_ids: Vec<&str> = prices.iter().map(|p| p.round_id.as_str()).collect();
+        let blocks: Vec<i64> = prices.iter().map(|p| p.block_number as i64).collect();
+        let answered: Vec<i64> = prices.iter().map(|p| p.answered_at as i64).collect();
+        let updated: Vec<chrono::DateTime<chrono::Utc>> =
+            prices.iter().map(|p| p.updated_at).collect();
+
+        client
+            .execute(
+                query,
+                &[
+                    &chain_ids,
+                    &aggregators,
+                    &tokens,
+                    &values,
+                    &round_ids,
+                    &blocks,
+                    &answered,
+                    &updated,
+                ],
+            )
+            .await
+            .map_err(|e| {
+                error!("Failed to upsert {} oracle prices: {:?}", prices.len(), e);
+                e
+            })?;
+
         Ok(())
     }
 
@@ -146,7 +414,9 @@ impl PostgresClient {
                 price_usd, price_updated_at, price_change_24h, price_change_7d,
                 logo_url, banner_url, website, twitter, telegram, discord,
                 volume_24h, swaps_24h, total_swaps, total_volume_usd, pool_count,
-                circulating_supply, market_cap_usd, first_seen_block, last_activity_at, updated_at
+                circulating_supply, market_cap_usd, first_seen_block, last_activity_at, updated_at,
+                price_confidence, price_source, price_method, price_source_pools, price_hops,
+                price_min_liquidity_usd
             FROM indexer.tokens
             WHERE chain_id = $1 AND address = ANY($2)
         "#;
@@ -158,18 +428,21 @@ impl PostgresClient {
     }
 
     /// Batch insert/update multiple tokens (true batch insert with multi-row VALUES)
+    ///
+    /// `circulating_supply` is owned by the `update_token_supplies` job and is never
+    /// overwritten here; `market_cap_usd` is recomputed from the stored supply.
     pub async fn set_tokens(&self, tokens: &[&Token]) -> anyhow::Result<()> {
         if tokens.is_empty() {
             return Ok(());
         }
 
-        const COLS_PER_ROW: usize = 25;
+        const COLS_PER_ROW: usize = 31;
         const BATCH_SIZE: usize = 300; // Smaller batches due to large number of columns
 
         let client = self.pool.get().await?;
 
         for chunk in tokens.chunks(BATCH_SIZE) {
-            // Build VALUES placeholders: ($1,$2,...,$25), ($26,$27,...,$50), ...
+            // Build VALUES placeholders: ($1,$2,...,$31), ($32,$33,...,$62), ...
             let values_clauses: Vec<String> = chunk
                 .iter()
                 .enumerate()
@@ -189,7 +462,9 @@ impl PostgresClient {
                     price_usd, price_updated_at, price_change_24h, price_change_7d,
                     logo_url, banner_url, website, twitter, telegram, discord,
                     volume_24h, swaps_24h, total_swaps, total_volume_usd, pool_count,
-                    circulating_supply, market_cap_usd, first_seen_block, last_activity_at, updated_at
+                    circulating_supply, market_cap_usd, first_seen_block, last_activity_at, updated_at,
+                    price_confidence, price_source, price_method, price_source_pools, price_hops,
+                    price_min_liquidity_usd
                 ) VALUES {}
                 ON CONFLICT (chain_id, address) DO UPDATE SET
                     symbol = EXCLUDED.symbol,
@@ -210,11 +485,16 @@ impl PostgresClient {
                     total_swaps = EXCLUDED.total_swaps,
                     total_volume_usd = EXCLUDED.total_volume_usd,
                     pool_count = EXCLUDED.pool_count,
-                    circulating_supply = EXCLUDED.circulating_supply,
-                    market_cap_usd = EXCLUDED.market_cap_usd,
+                    market_cap_usd = indexer.tokens.circulating_supply * EXCLUDED.price_usd,
                     first_seen_block = EXCLUDED.first_seen_block,
                     last_activity_at = EXCLUDED.last_activity_at,
-                    updated_at = EXCLUDED.updated_at
+                    updated_at = EXCLUDED.updated_at,
+                    price_confidence = EXCLUDED.price_confidence,
+                    price_source = EXCLUDED.price_source,
+                    price_method = EXCLUDED.price_method,
+                    price_source_pools = EXCLUDED.price_source_pools,
+                    price_hops = EXCLUDED.price_hops,
+                    price_min_liquidity_usd = EXCLUDED.price_min_liquidity_usd
                 "#,
                 values_clauses.join(", ")
             );
@@ -232,6 +512,10 @@ impl PostgresClient {
             let mut total_swaps_vec: Vec<Option<i64>> = Vec::with_capacity(chunk.len());
             let mut pool_counts: Vec<Option<i64>> = Vec::with_capacity(chunk.len());
             let mut first_seen_blocks: Vec<Option<i64>> = Vec::with_capacity(chunk.len());
+            let provenances: Vec<ProvenanceParams> = chunk
+                .iter()
+                .map(|token| ProvenanceParams::from(token.price_provenance.as_ref()))
+                .collect();
 
             for token in chunk {
                 chain_ids.push(token.chain_id as i64);
@@ -271,6 +555,7 @@ impl PostgresClient {
                 params.push(&first_seen_blocks[i]);
                 params.push(&token.last_activity_at);
                 params.push(&token.updated_at);
+                provenances[i].push_params(&mut params);
             }
 
             client.execute(&query, &params).await.map_err(|e| {
@@ -301,10 +586,13 @@ impl PostgresClient {
                 token0_decimals, token1_decimals, base_token, quote_token, is_inverted,
                 quote_token_priority, protocol, protocol_version, factory, fee, initial_fee,
                 hook_address, created_at, block_number, tx_hash, reserve0, reserve1,
-                reserve0_adjusted, reserve1_adjusted, sqrt_price_x96, tick, tick_spacing,
+                reserve0_adjusted::TEXT AS reserve0_adjusted,
+                reserve1_adjusted::TEXT AS reserve1_adjusted, sqrt_price_x96, tick, tick_spacing,
                 liquidity, price, token0_price, token1_price, price_usd, price_change_24h,
-                price_change_7d, volume_24h, swaps_24h, total_swaps, total_volume_usd,
-                tvl_usd, last_swap_at, updated_at
+                price_change_7d, volume_24h, swaps_24h, fees_24h, fees_7d, fee_apr, total_swaps,
+                total_volume_usd, tvl_usd, last_swap_at, updated_at, price_confidence,
+                price_source, price_method, price_source_pools, price_hops,
+                price_min_liquidity_usd
             FROM indexer.pools
             WHERE chain_id = $1 AND address = ANY($2)
         "#;
@@ -314,13 +602,40 @@ impl PostgresClient {
         Ok(pools)
     }
 
+    /// Get every pool of a chain with a price, used to seed the pricing graph
+    pub async fn get_priced_pools(&self, chain_id: i64) -> anyhow::Result<Vec<Pool>> {
+        let client = self.pool.get().await?;
+        let query = r#"
+            SELECT 
+                chain_id, address, token0, token1, token0_symbol, token1_symbol,
+                token0_decimals, token1_decimals, base_token, quote_token, is_inverted,
+                quote_token_priority, protocol, protocol_version, factory, fee, initial_fee,
+                hook_address, created_at, block_number, tx_hash, reserve0, reserve1,
+                reserve0_adjusted::TEXT AS reserve0_adjusted,
+                reserve1_adjusted::TEXT AS reserve1_adjusted, sqrt_price_x96, tick, tick_spacing,
+                liquidity, price, token0_price, token1_price, price_usd, price_change_24h,
+                price_change_7d, volume_24h, swaps_24h, fees_24h, fees_7d, fee_apr, total_swaps,
+                total_volume_usd, tvl_usd, last_swap_at, updated_at, price_confidence,
+                price_source, price_method, price_source_pools, price_hops,
+                price_min_liquidity_usd
+            FROM indexer.pools
+            WHERE chain_id = $1 AND token0_price > 0 AND token1_price > 0
+        "#;
+
+        let rows = client.query(query, &[&chain_id]).await?;
+        let pools = rows.iter().map(row_to_pool).collect();
+        Ok(pools)
+    }
+
     /// Batch insert/update multiple pools (true batch insert with multi-row VALUES)
     pub async fn set_pools(&self, pools: &[&Pool]) -> anyhow::Result<()> {
         if pools.is_empty() {
             return Ok(());
         }
 
-        const COLS_PER_ROW: usize = 42;
+        const COLS_PER_ROW: usize = 48;
+        // Column offsets of reserve0_adjusted and reserve1_adjusted
+        const NUMERIC_COLS: [usize; 2] = [23, 24];
         const BATCH_SIZE: usize = 300; // Smaller batches to avoid "value too large to transmit"
 
         let client = self.pool.get().await?;
@@ -333,7 +648,14 @@ impl PostgresClient {
                 .map(|(i, _)| {
                     let start = i * COLS_PER_ROW + 1;
                     let placeholders: Vec<String> = (start..start + COLS_PER_ROW)
-                        .map(|n| format!("${}", n))
+                        .map(|n| {
+                            // Exact reserves are bound as text and stored as NUMERIC
+                            if NUMERIC_COLS.contains(&(n - start)) {
+                                format!("${}::TEXT::NUMERIC", n)
+                            } else {
+                                format!("${}", n)
+                            }
+                        })
                         .collect();
                     format!("({})", placeholders.join(", "))
                 })
@@ -349,7 +671,8 @@ impl PostgresClient {
                     reserve0_adjusted, reserve1_adjusted, sqrt_price_x96, tick, tick_spacing,
                     liquidity, price, token0_price, token1_price, price_usd, price_change_24h,
                     price_change_7d, volume_24h, swaps_24h, total_swaps, total_volume_usd,
-                    tvl_usd, last_swap_at, updated_at
+                    tvl_usd, last_swap_at, updated_at, price_confidence, price_source, price_method,
+                    price_source_pools, price_hops, price_min_liquidity_usd
                 ) VALUES {}
                 ON CONFLICT (chain_id, address) DO UPDATE SET
                     -- ANTI-SPOOFING: Immutable fields are NOT updated on conflict
@@ -384,7 +707,13 @@ impl PostgresClient {
                     total_volume_usd = EXCLUDED.total_volume_usd,
                     tvl_usd = EXCLUDED.tvl_usd,
                     last_swap_at = EXCLUDED.last_swap_at,
-                    updated_at = EXCLUDED.updated_at
+                    updated_at = EXCLUDED.updated_at,
+                    price_confidence = EXCLUDED.price_confidence,
+                    price_source = EXCLUDED.price_source,
+                    price_method = EXCLUDED.price_method,
+                    price_source_pools = EXCLUDED.price_source_pools,
+                    price_hops = EXCLUDED.price_hops,
+                    price_min_liquidity_usd = EXCLUDED.price_min_liquidity_usd
                 "#,
                 values_clauses.join(", ")
             );
@@ -407,6 +736,12 @@ impl PostgresClient {
             let mut block_numbers: Vec<Option<i64>> = Vec::with_capacity(chunk.len());
             let mut swaps_24h_vec: Vec<Option<i64>> = Vec::with_capacity(chunk.len());
             let mut total_swaps_vec: Vec<Option<i64>> = Vec::with_capacity(chunk.len());
+            let mut reserves_adjusted: Vec<(Option<String>, Option<String>)> =
+                Vec::with_capacity(chunk.len());
+            let provenances: Vec<ProvenanceParams> = chunk
+                .iter()
+                .map(|pool| ProvenanceParams::from(pool.price_provenance.as_ref()))
+                .collect();
 
             for pool in chunk {
                 chain_ids.push(pool.chain_id as i64);
@@ -417,6 +752,10 @@ impl PostgresClient {
                 block_numbers.push(pool.block_number.map(|v| v as i64));
                 swaps_24h_vec.push(pool.swaps_24h.map(|v| v as i64));
                 total_swaps_vec.push(pool.total_swaps.map(|v| v as i64));
+                reserves_adjusted.push((
+                    pool.reserve0_adjusted.map(|v| v.to_string()),
+                    pool.reserve1_adjusted.map(|v| v.to_string()),
+                ));
             }
 
             let mut params: Vec<&(dyn tokio_postgres::types::ToSql + Sync)> =
@@ -446,8 +785,8 @@ impl PostgresClient {
                 params.push(&pool.tx_hash);
                 params.push(&pool.reserve0);
                 params.push(&pool.reserve1);
-                params.push(&pool.reserve0_adjusted);
-                params.push(&pool.reserve1_adjusted);
+                params.push(&reserves_adjusted[i].0);
+                params.push(&reserves_adjusted[i].1);
                 params.push(&pool.sqrt_price_x96);
                 params.push(&pool.tick);
                 params.push(&pool.tick_spacing);
@@ -465,6 +804,7 @@ impl PostgresClient {
                 params.push(&pool.tvl_usd);
                 params.push(&pool.last_swap_at);
                 params.push(&pool.updated_at);
+                provenances[i].push_params(&mut params);
             }
 
             client.execute(&query, &params).await.map_err(|e| {
@@ -527,6 +867,37 @@ impl PostgresClient {
         Ok(())
     }
 
+    // ==================== CHAIN RESET ====================
+
+    /// Delete the indexed state of a chain, or rewind it to a cutoff block
+    ///
+    /// With a cutoff (and the `PurgedPools` read from ClickHouse before its purge),
+    /// pools created from the cutoff on are deleted with the tokens only they used.
+    /// The other pools keep their latest state minus what the purged events added
+    /// (swap counts, V3/V4 reserves and liquidity), and their block is moved below
+    /// the cutoff so the worker applies the re-indexed events again. Absolute state
+    /// (price, tick, V2 reserves) is overwritten by those events. A full reset
+    /// rebuilds everything. See `purge_statements`.
+    pub async fn purge_chain(
+        &self,
+        chain_id: u64,
+        cutoff: Option<(u64, &PurgedPools)>,
+    ) -> anyhow::Result<()> {
+        let mut client = self.pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        for (statement, params) in purge_statements(chain_id as i64, cutoff) {
+            let params: Vec<&(dyn ToSql + Sync)> = params
+                .iter()
+                .map(|p| p.as_ref() as &(dyn ToSql + Sync))
+                .collect();
+            transaction.execute(&statement, &params).await?;
+        }
+
+        transaction.commit().await?;
+        Ok(())
+    }
+
     // ==================== CRON CHECKPOINTS ====================
 
     /// Get last run timestamp for a cron job
@@ -597,13 +968,24 @@ impl PostgresClient {
         chain_id: i64,
     ) -> anyhow::Result<Option<NativeTokenPrice>> {
         let client = self.pool.get().await?;
-        let query = "SELECT chain_id, price_usd, updated_at FROM indexer.native_token_prices WHERE chain_id = $1";
+        let query = r#"
+            SELECT chain_id, price_usd, source, source_pools, confidence, updated_at
+            FROM indexer.native_token_prices
+            WHERE chain_id = $1
+        "#;
 
         let row = client.query_opt(query, &[&chain_id]).await?;
 
         Ok(row.map(|r| NativeTokenPrice {
             chain_id: r.get("chain_id"),
             price_usd: r.get("price_usd"),
+            source: r.get::<_, Option<String>>("source").unwrap_or_default(),
+            source_pools: r
+                .get::<_, Option<Vec<String>>>("source_pools")
+                .unwrap_or_default(),
+            confidence: r.get::<_, Option<f64>>("confidence").unwrap_or(0.0),
+            pending_price: None,
+            pending_updates: 0,
             updated_at: r.get("updated_at"),
         }))
     }
@@ -612,17 +994,29 @@ impl PostgresClient {
     pub async fn set_native_token_price(&self, price: &NativeTokenPrice) -> anyhow::Result<()> {
         let client = self.pool.get().await?;
         let query = r#"
-            INSERT INTO indexer.native_token_prices (chain_id, price_usd, updated_at)
-            VALUES ($1, $2, $3)
+            INSERT INTO indexer.native_token_prices (
+                chain_id, price_usd, source, source_pools, confidence, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
             ON CONFLICT (chain_id) DO UPDATE SET
                 price_usd = EXCLUDED.price_usd,
+                source = EXCLUDED.source,
+                source_pools = EXCLUDED.source_pools,
+                confidence = EXCLUDED.confidence,
                 updated_at = EXCLUDED.updated_at
         "#;
 
         client
             .execute(
                 query,
-                &[&price.chain_id, &price.price_usd, &price.updated_at],
+                &[
+                    &price.chain_id,
+                    &price.price_usd,
+                    &price.source,
+                    &price.source_pools,
+                    &price.confidence,
+                    &price.updated_at,
+                ],
             )
             .await
             .map_err(|e| {
@@ -651,10 +1045,13 @@ impl PostgresClient {
                 token0_decimals, token1_decimals, base_token, quote_token, is_inverted,
                 quote_token_priority, protocol, protocol_version, factory, fee, initial_fee,
                 hook_address, created_at, block_number, tx_hash, reserve0, reserve1,
-                reserve0_adjusted, reserve1_adjusted, sqrt_price_x96, tick, tick_spacing,
+                reserve0_adjusted::TEXT AS reserve0_adjusted,
+                reserve1_adjusted::TEXT AS reserve1_adjusted, sqrt_price_x96, tick, tick_spacing,
                 liquidity, price, token0_price, token1_price, price_usd, price_change_24h,
-                price_change_7d, volume_24h, swaps_24h, total_swaps, total_volume_usd,
-                tvl_usd, last_swap_at, updated_at
+                price_change_7d, volume_24h, swaps_24h, fees_24h, fees_7d, fee_apr, total_swaps,
+                total_volume_usd, tvl_usd, last_swap_at, updated_at, price_confidence,
+                price_source, price_method, price_source_pools, price_hops,
+                price_min_liquidity_usd
             FROM indexer.pools
             WHERE chain_id = $1 AND (token0 = $2 OR token1 = $2)
         "#;
@@ -683,6 +1080,7 @@ fn row_to_token(row: &tokio_postgres::Row) -> Token {
         decimals: decimals as u8,
         price_usd: row.get("price_usd"),
         price_updated_at: row.get("price_updated_at"),
+        price_provenance: row_to_price_provenance(row),
         price_change_24h: row.get("price_change_24h"),
         price_change_7d: row.get("price_change_7d"),
         logo_url: row.get("logo_url"),
@@ -746,8 +1144,14 @@ fn row_to_pool(row: &tokio_postgres::Row) -> Pool {
         tx_hash: row.get("tx_hash"),
         reserve0: row.get("reserve0"),
         reserve1: row.get("reserve1"),
-        reserve0_adjusted: row.get("reserve0_adjusted"),
-        reserve1_adjusted: row.get("reserve1_adjusted"),
+        reserve0_adjusted: row
+            .get::<_, Option<String>>("reserve0_adjusted")
+            .as_deref()
+            .and_then(str_to_decimal),
+        reserve1_adjusted: row
+            .get::<_, Option<String>>("reserve1_adjusted")
+            .as_deref()
+            .and_then(str_to_decimal),
         sqrt_price_x96: row.get("sqrt_price_x96"),
         tick: row.get("tick"),
         tick_spacing: row.get("tick_spacing"),
@@ -756,10 +1160,14 @@ fn row_to_pool(row: &tokio_postgres::Row) -> Pool {
         token0_price: row.get("token0_price"),
         token1_price: row.get("token1_price"),
         price_usd: row.get("price_usd"),
+        price_provenance: row_to_price_provenance(row),
         price_change_24h: row.get("price_change_24h"),
         price_change_7d: row.get("price_change_7d"),
         volume_24h: row.get("volume_24h"),
         swaps_24h: row.get::<_, Option<i64>>("swaps_24h").map(|v| v as u64),
+        fees_24h: row.get("fees_24h"),
+        fees_7d: row.get("fees_7d"),
+        fee_apr: row.get("fee_apr"),
         total_swaps: row.get::<_, Option<i64>>("total_swaps").map(|v| v as u64),
         total_volume_usd: row.get("total_volume_usd"),
         tvl_usd: row.get("tvl_usd"),
@@ -767,3 +1175,254 @@ fn row_to_pool(row: &tokio_postgres::Row) -> Pool {
         updated_at: row.get("updated_at"),
     }
 }
+
+/// Reads the `price_*` provenance columns shared by tokens and pools.
+fn row_to_price_provenance(row: &tokio_postgres::Row) -> Option<PriceProvenance> {
+    let source: Option<String> = row.get("price_source");
+
+    Some(PriceProvenance {
+        confidence: row.get::<_, Option<f64>>("price_confidence").unwrap_or(0.0),
+        source: source?,
+        method: row
+            .get::<_, Option<String>>("price_method")
+            .unwrap_or_default(),
+        source_pools: row
+            .get::<_, Option<Vec<String>>>("price_source_pools")
+            .unwrap_or_default(),
+        hops: row.get::<_, Option<i16>>("price_hops").unwrap_or(0) as u32,
+        min_liquidity_usd: row
+            .get::<_, Option<f64>>("price_min_liquidity_usd")
+            .unwrap_or(0.0),
+    })
+}
+
+/// Statement of a chain purge with its parameters
+type PurgeStatement = (String, Vec<Box<dyn ToSql + Sync + Send>>);
+
+/// Deletes pools created from the cutoff on, then the tokens no kept pool uses
+/// (except the chain's native and stable tokens)
+const DELETE_CREATED_POOLS: &str = r#"
+    WITH deleted AS (
+        DELETE FROM indexer.pools
+        WHERE chain_id = $1 AND address = ANY($2)
+        RETURNING token0, token1
+    )
+    DELETE FROM indexer.tokens t
+    WHERE t.chain_id = $1
+      AND t.address IN (SELECT token0 FROM deleted UNION SELECT token1 FROM deleted)
+      AND NOT EXISTS (
+          SELECT 1 FROM indexer.pools p
+          WHERE p.chain_id = $1
+            AND (p.token0 = t.address OR p.token1 = t.address)
+            AND NOT (p.address = ANY($2))
+      )
+      AND NOT EXISTS (
+          SELECT 1 FROM indexer.chains c
+          WHERE c.chain_id = $1
+            AND t.address IN (lower(c.native_token_address), lower(c.stable_token_address))
+      )
+"#;
+
+/// Subtracts what the purged events accumulated into the kept pools
+const REWIND_POOL_STATE: &str = r#"
+    UPDATE indexer.pools p
+    SET
+        total_swaps = GREATEST(p.total_swaps - data.swaps, 0),
+        reserve0_adjusted = CASE
+            WHEN p.protocol_version = 'v2' OR p.reserve0_adjusted IS NULL THEN p.reserve0_adjusted
+            ELSE GREATEST(p.reserve0_adjusted - data.reserve0_delta::NUMERIC, 0)
+        END,
+        reserve1_adjusted = CASE
+            WHEN p.protocol_version = 'v2' OR p.reserve1_adjusted IS NULL THEN p.reserve1_adjusted
+            ELSE GREATEST(p.reserve1_adjusted - data.reserve1_delta::NUMERIC, 0)
+        END,
+        liquidity = CASE
+            WHEN p.protocol_version = 'v2' OR p.liquidity IS NULL THEN p.liquidity
+            ELSE GREATEST(p.liquidity::NUMERIC - data.liquidity_delta::NUMERIC, 0)::TEXT
+        END,
+        updated_at = NOW()
+    FROM (
+        SELECT * FROM UNNEST(
+            $2::text[],
+            $3::bigint[],
+            $4::float8[],
+            $5::float8[],
+            $6::text[]
+        ) AS t(address, swaps, reserve0_delta, reserve1_delta, liquidity_delta)
+    ) AS data
+    WHERE p.chain_id = $1 AND p.address = data.address
+"#;
+
+/// Moves the kept pools below the cutoff, so the worker applies the re-indexed events
+const REWIND_POOL_BLOCKS: &str = r#"
+    UPDATE indexer.pools
+    SET block_number = $2::BIGINT - 1, updated_at = NOW()
+    WHERE chain_id = $1 AND block_number >= $2
+"#;
+
+const REWIND_CHECKPOINT: &str = r#"
+    UPDATE indexer.sync_checkpoints
+    SET last_indexed_block = LEAST(last_indexed_block, $2), updated_at = NOW()
+    WHERE chain_id = $1
+"#;
+
+/// Statements of `PostgresClient::purge_chain`, in execution order.
+fn purge_statements(chain_id: i64, cutoff: Option<(u64, &PurgedPools)>) -> Vec<PurgeStatement> {
+    let Some((block, purged)) = cutoff else {
+        return ["pools", "tokens", "native_token_prices", "oracle_prices", "sync_checkpoints"]
+            .into_iter()
+            .map(|table| -> PurgeStatement {
+                (
+                    format!("DELETE FROM indexer.{} WHERE chain_id = $1", table),
+                    vec![Box::new(chain_id)],
+                )
+            })
+            .collect();
+    };
+    let block = block as i64;
+    let rewinds = &purged.rewinds;
+
+    vec![
+        (
+            DELETE_CREATED_POOLS.to_string(),
+            vec![Box::new(chain_id), Box::new(purged.created.clone())],
+        ),
+        (
+            REWIND_POOL_STATE.to_string(),
+            vec![
+                Box::new(chain_id),
+                Box::new(
+                    rewinds
+                        .iter()
+                        .map(|r| r.pool_address.clone())
+                        .collect::<Vec<_>>(),
+                ),
+                Box::new(rewinds.iter().map(|r| r.swaps as i64).collect::<Vec<_>>()),
+                Box::new(rewinds.iter().map(|r| r.reserve0_delta).collect::<Vec<_>>()),
+                Box::new(rewinds.iter().map(|r| r.reserve1_delta).collect::<Vec<_>>()),
+                Box::new(
+                    rewinds
+                        .iter()
+                        .map(|r| r.liquidity_delta.clone())
+                        .collect::<Vec<_>>(),
+                ),
+            ],
+        ),
+        (
+            REWIND_POOL_BLOCKS.to_string(),
+            vec![Box::new(chain_id), Box::new(block)],
+        ),
+        (
+            REWIND_CHECKPOINT.to_string(),
+            vec![Box::new(chain_id), Box::new(block)],
+        ),
+    ]
+}
+
+/// Owned `price_*` column values for a batch insert.
+struct ProvenanceParams {
+    confidence: Option<f64>,
+    source: Option<String>,
+    method: Option<String>,
+    source_pools: Option<Vec<String>>,
+    hops: Option<i16>,
+    min_liquidity_usd: Option<f64>,
+}
+
+impl From<Option<&PriceProvenance>> for ProvenanceParams {
+    fn from(provenance: Option<&PriceProvenance>) -> Self {
+        Self {
+            confidence: provenance.map(|p| p.confidence),
+            source: provenance.map(|p| p.source.clone()),
+            method: provenance.map(|p| p.method.clone()),
+            source_pools: provenance.map(|p| p.source_pools.clone()),
+            hops: provenance.map(|p| p.hops.min(i16::MAX as u32) as i16),
+            min_liquidity_usd: provenance.map(|p| p.min_liquidity_usd),
+        }
+    }
+}
+
+impl ProvenanceParams {
+    fn push_params<'a>(&'a self, params: &mut Vec<&'a (dyn tokio_postgres::types::ToSql + Sync)>) {
+        params.push(&self.confidence);
+        params.push(&self.source);
+        params.push(&self.method);
+        params.push(&self.source_pools);
+        params.push(&self.hops);
+        params.push(&self.min_liquidity_usd);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::clickhouse::PoolRewind;
+
+    /// Highest `$n` placeholder of a statement
+    fn placeholders(statement: &str) -> usize {
+        statement
+            .split('$')
+            .skip(1)
+            .filter_map(|rest| {
+                let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+                digits.parse().ok()
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    #[test]
+    fn test_full_purge_deletes_the_chain_rows() {
+        let statements = purge_statements(1, None);
+
+        let sql: Vec<&str> = statements.iter().map(|(sql, _)| sql.as_str()).collect();
+        assert_eq!(
+            sql,
+            [
+                "DELETE FROM indexer.pools WHERE chain_id = $1",
+                "DELETE FROM indexer.tokens WHERE chain_id = $1",
+                "DELETE FROM indexer.native_token_prices WHERE chain_id = $1",
+                "DELETE FROM indexer.oracle_prices WHERE chain_id = $1",
+                "DELETE FROM indexer.sync_checkpoints WHERE chain_id = $1",
+            ]
+        );
+        assert!(statements.iter().all(|(_, params)| params.len() == 1));
+    }
+
+    #[test]
+    fn test_partial_purge_rewinds_pools_before_the_checkpoint() {
+        let purged = PurgedPools {
+            created: vec!["0xnew".into()],
+            rewinds: vec![PoolRewind {
+                pool_address: "0xkept".into(),
+                swaps: 3,
+                reserve0_delta: 1.5,
+                reserve1_delta: -2.0,
+                liquidity_delta: "-100".into(),
+            }],
+        };
+
+        let statements = purge_statements(1, Some((100, &purged)));
+
+        let sql: Vec<&str> = statements.iter().map(|(sql, _)| sql.as_str()).collect();
+        assert_eq!(
+            sql,
+            [DELETE_CREATED_POOLS, REWIND_POOL_STATE, REWIND_POOL_BLOCKS, REWIND_CHECKPOINT]
+        );
+        for (sql, params) in &statements {
+            assert_eq!(placeholders(sql), params.len(), "{}", sql);
+        }
+
+        // Tokens are deleted with their pools, in the same statement
+        assert!(DELETE_CREATED_POOLS.contains("DELETE FROM indexer.pools"));
+        assert!(DELETE_CREATED_POOLS.contains("DELETE FROM indexer.tokens"));
+        // V2 reserves are absolute (Sync), only V3/V4 ones are rewound
+        assert_eq!(
+            REWIND_POOL_STATE
+                .matches("p.protocol_version = 'v2'")
+                .count(),
+            3
+        );
+        assert!(REWIND_POOL_BLOCKS.contains("block_number = $2::BIGINT - 1"));
+    }
+}
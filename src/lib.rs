@@ -1,4 +1,5 @@
 pub mod abis;
+pub mod cli;
 pub mod config;
 pub mod cron;
 pub mod db;
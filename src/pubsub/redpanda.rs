@@ -5,6 +5,7 @@
 
 use std::time::Duration;
 
+use anyhow::Context;
 use log::{error, info, warn};
 use rdkafka::config::ClientConfig;
 use rdkafka::producer::{FutureProducer, FutureRecord, Producer};
@@ -70,6 +71,9 @@ impl RedpandaPublisher {
         let new_pools_topic = format!("{}.new_pools.{}", self.topic_prefix, chain_id);
         let pool_states_topic = format!("{}.pool_states.{}", self.topic_prefix, chain_id);
         let token_states_topic = format!("{}.token_states.{}", self.topic_prefix, chain_id);
+        let mev_sandwiches_topic = format!("{}.mev_sandwiches.{}", self.topic_prefix, chain_id);
+        let arbitrages_topic = format!("{}.arbitrages.{}", self.topic_prefix, chain_id);
+        let trades_topic = format!("{}.trades.{}", self.topic_prefix, chain_id);
 
         // Publish events
         for event in &batch.events {
@@ -94,6 +98,24 @@ impl RedpandaPublisher {
             self.publish_message(&token_states_topic, &token.address, token)
                 .await;
         }
+
+        // Publish detected sandwich attacks
+        for sandwich in &batch.mev_sandwiches {
+            self.publish_message(&mev_sandwiches_topic, &sandwich.pool_address, sandwich)
+                .await;
+        }
+
+        // Publish detected arbitrages
+        for arbitrage in &batch.arbitrages {
+            self.publish_message(&arbitrages_topic, &arbitrage.tx_hash, arbitrage)
+                .await;
+        }
+
+        // Publish reconstructed trades
+        for trade in &batch.trades {
+            self.publish_message(&trades_topic, &trade.tx_hash, trade)
+                .await;
+        }
     }
 
     /// Publish a single message to a topic.
@@ -118,6 +140,19 @@ impl RedpandaPublisher {
         }
     }
 
+    /// Health check - fetch the cluster metadata from the brokers
+    pub async fn health_check(&self) -> anyhow::Result<()> {
+        let producer = self.producer.clone();
+        tokio::task::spawn_blocking(move || {
+            producer
+                .client()
+                .fetch_metadata(None, Duration::from_secs(5))
+        })
+        .await?
+        .context("Redpanda health check failed")?;
+        Ok(())
+    }
+
     /// Flush any pending messages (call on shutdown).
     pub fn flush(&self) {
         self.producer.flush(Duration::from_secs(5)).ok();
@@ -2,13 +2,21 @@
 //!
 //! Functions for converting between different numeric types (U256, f64, BigInt, etc.)
 //! with proper decimal handling and precision preservation.
+//!
+//! Token amounts and reserves are kept as exact `D256` decimals in the models.
+//! f64 is only produced at the ClickHouse and presentation boundary.
 
 use alloy::primitives::{hex, U256};
 use bigdecimal::BigDecimal;
 use clickhouse::types::UInt256;
+use fastnum::{
+    decimal::{Context, Sign},
+    D256,
+};
 use num_bigint::BigInt;
 use num_traits::ToPrimitive;
 use once_cell::sync::Lazy;
+use serde::Serializer;
 use std::str::FromStr;
 
 // ============================================
@@ -110,23 +118,71 @@ pub fn str_to_f64_with_decimals(value_str: &str, decimals: u8) -> Option<f64> {
     }
 }
 
-/// Convert a u128 reserve value to f64 with decimal adjustment using BigDecimal.
+// ============================================
+// Exact Decimal Conversions
+// ============================================
+
+/// Convert a raw U256 token amount to an exact decimal adjusted by `decimals`.
 ///
-/// This avoids precision loss that occurs when directly casting u128 to f64
-/// for values larger than 2^53.
+/// No rounding happens: the raw digits are kept and only the exponent changes.
 ///
-/// # Arguments
-/// * `reserve` - The raw reserve value as u128
-/// * `decimals` - The number of decimal places to adjust by
+/// # Example
+/// ```ignore
+/// let value = U256::from(1_500_000_000_000_000_000u128);
+/// let adjusted = u256_to_decimal(value, 18); // Exactly 1.5
+/// ```
+pub fn u256_to_decimal(value: U256, decimals: u8) -> D256 {
+    D256::from_parts(
+        fastnum::U256::from_digits(*value.as_limbs()),
+        -(decimals as i32),
+        Sign::Plus,
+        Context::default(),
+    )
+}
+
+/// Convert a u128 reserve value to an exact decimal adjusted by `decimals`.
+pub fn reserve_to_decimal(reserve: u128, decimals: u8) -> D256 {
+    u256_to_decimal(U256::from(reserve), decimals)
+}
+
+/// Parse an exact decimal, e.g. a PostgreSQL NUMERIC read as text.
 ///
-/// # Returns
-/// * The adjusted f64 value, or 0.0 if conversion fails
-pub fn reserve_to_f64(reserve: u128, decimals: u8) -> f64 {
-    let big_value = BigDecimal::from(reserve);
+/// Returns None for invalid or non-finite values.
+pub fn str_to_decimal(value_str: &str) -> Option<D256> {
+    D256::from_str(value_str, Context::default())
+        .ok()
+        .filter(|value| value.is_finite())
+}
 
-    let adjusted = if decimals == 0 { big_value } else { big_value / big_pow10(decimals) };
+/// Convert an exact decimal to f64 for USD math, ClickHouse and presentation.
+///
+/// Returns 0.0 if the value does not fit in a finite f64.
+pub fn decimal_to_f64(value: D256) -> f64 {
+    let result = f64::from(value);
+    if result.is_finite() {
+        result
+    } else {
+        0.0
+    }
+}
+
+/// Serialize an exact decimal as f64 (ClickHouse Float64 columns, JSON payloads).
+pub fn serialize_decimal_as_f64<S: Serializer>(
+    value: &D256,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(decimal_to_f64(*value))
+}
 
-    adjusted.to_f64().unwrap_or(0.0)
+/// Serialize an optional exact decimal as an optional f64.
+pub fn serialize_opt_decimal_as_f64<S: Serializer>(
+    value: &Option<D256>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.serialize_some(&decimal_to_f64(*value)),
+        None => serializer.serialize_none(),
+    }
 }
 
 // ============================================
@@ -207,3 +263,41 @@ pub(crate) fn big_pow10(exp: u8) -> BigDecimal {
         BigDecimal::from(BigInt::from(10u32).pow(exp as u32))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_u256_to_decimal_is_exact() {
+        // 123456789.123456789123456789 with 18 decimals, beyond f64 precision
+        let raw = U256::from_str("123456789123456789123456789").unwrap();
+        let adjusted = u256_to_decimal(raw, 18);
+
+        assert_eq!(
+            adjusted,
+            str_to_decimal("123456789.123456789123456789").unwrap()
+        );
+        assert_eq!(
+            u256_to_decimal(U256::MAX, 0).digits().to_string(),
+            U256::MAX.to_string()
+        );
+    }
+
+    #[test]
+    fn test_decimal_accumulation_does_not_drift() {
+        let delta = u256_to_decimal(U256::from(100_000_000_000_000_001u128), 18);
+        let mut reserve = D256::ZERO;
+        for _ in 0..1_000 {
+            reserve = reserve + delta;
+        }
+        for _ in 0..1_000 {
+            reserve = reserve - delta;
+        }
+        assert!(reserve.is_zero());
+
+        // Text round trip, as stored in PostgreSQL NUMERIC columns
+        let stored = str_to_decimal(&delta.to_string()).unwrap();
+        assert_eq!(stored, delta);
+    }
+}
@@ -3,8 +3,8 @@
 //! This module is organized into focused submodules:
 //!
 //! - [`validation`] - Price validation constants and helper functions
-//! - [`conversion`] - Type conversions (U256, f64, BigInt, hex encoding)
-//! - [`tick_math`] - Uniswap V3/V4 tick calculations
+//! - [`conversion`] - Type conversions (U256, exact decimals, f64, BigInt, hex encoding)
+//! - [`tick_math`] - Exact Uniswap V3/V4 TickMath and SqrtPriceMath
 //! - [`price`] - Price conversion utilities (sqrtPriceX96, reserves)
 //! - [`pool_id`] - Uniswap V4 pool ID computation
 
@@ -28,8 +28,9 @@ pub const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
 
 // Conversion utilities
 pub use conversion::{
-    bigint_add, bigint_sub, hex_encode, into_u256, reserve_to_f64, str_to_f64_with_decimals,
-    u256_to_f64,
+    bigint_add, bigint_sub, decimal_to_f64, hex_encode, into_u256, reserve_to_decimal,
+    serialize_decimal_as_f64, serialize_opt_decimal_as_f64, str_to_decimal,
+    str_to_f64_with_decimals, u256_to_decimal, u256_to_f64,
 };
 
 // Pool ID utilities (V4)
@@ -39,7 +40,10 @@ pub use pool_id::{compute_v4_pool_id, compute_v4_pool_id_from_stored};
 pub use price::{calculate_reserves_from_liquidity_subgraph, sqrt_price_x96_str_to_adjusted_price};
 
 // Tick math utilities
-pub use tick_math::calculate_mint_amounts;
+pub use tick_math::{
+    calculate_mint_amounts, get_amount0_delta, get_amount1_delta, get_sqrt_ratio_at_tick,
+    get_tick_at_sqrt_ratio, MAX_SQRT_RATIO, MAX_TICK, MIN_SQRT_RATIO, MIN_TICK,
+};
 
 // Validation utilities
 pub use validation::{
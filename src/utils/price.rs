@@ -15,10 +15,6 @@ use super::validation::validate_price_ratio;
 // Constants
 // ============================================
 
-/// Constant: 2^96 (Q64.96 fixed point scaling factor)
-/// Value: 79228162514264337593543950336.0
-pub(crate) const Q96: f64 = 79228162514264337593543950336.0;
-
 /// 1/Q96 precomputed for faster division (multiply instead of divide)
 pub(crate) const Q96_INV: f64 = 1.0 / 79228162514264337593543950336.0;
 
@@ -1,250 +1,355 @@
 //! Tick math for Uniswap V3/V4 calculations.
 //!
-//! Implements tick-to-price conversions and liquidity amount calculations
-//! following Uniswap V3's TickMath.sol approach.
+//! Exact U256 ports of Uniswap V3's `TickMath.sol` (tick <-> sqrtPriceX96) and
+//! `SqrtPriceMath.sol` (token amounts for a liquidity delta). Results match the
+//! contracts bit for bit, including rounding.
+
+use alloy::primitives::{I256, U256, U512};
 
 // ============================================
-// Precomputed Constants
+// Constants
 // ============================================
 
-use crate::utils::price::{Q96, Q96_INV};
-
-// Precomputed multipliers for tick_to_sqrt_price_x96
-// These are 1/sqrt(1.0001)^(2^i) = (1.0001)^(-0.5 * 2^i) for i = 0..19
-// Used for bit-manipulation approach matching Uniswap V3's TickMath.sol
-// Computed with high precision: pow(1.0001, -0.5 * 2^i)
-const TICK_MULTIPLIERS: [f64; 20] = [
-    0.9999500037496876, // 1/sqrt(1.0001)^1      = 1.0001^(-0.5)
-    0.9999000099990001, // 1/sqrt(1.0001)^2      = 1.0001^(-1)
-    0.9998000299960005, // 1/sqrt(1.0001)^4      = 1.0001^(-2)
-    0.9996000999800035, // 1/sqrt(1.0001)^8      = 1.0001^(-4)
-    0.9992003598800331, // 1/sqrt(1.0001)^16     = 1.0001^(-8)
-    0.9984013591843877, // 1/sqrt(1.0001)^32     = 1.0001^(-16)
-    0.9968052740212325, // 1/sqrt(1.0001)^64     = 1.0001^(-32)
-    0.9936207543165446, // 1/sqrt(1.0001)^128    = 1.0001^(-64)
-    0.9872822034085791, // 1/sqrt(1.0001)^256    = 1.0001^(-128)
-    0.9747261491672988, // 1/sqrt(1.0001)^512    = 1.0001^(-256)
-    0.9500910658705113, // 1/sqrt(1.0001)^1024   = 1.0001^(-512)
-    0.9026730334469643, // 1/sqrt(1.0001)^2048   = 1.0001^(-1024)
-    0.8148186053123443, // 1/sqrt(1.0001)^4096   = 1.0001^(-2048)
-    0.6639293595631539, // 1/sqrt(1.0001)^8192   = 1.0001^(-4096)
-    0.4408021944899397, // 1/sqrt(1.0001)^16384  = 1.0001^(-8192)
-    0.1943065746671466, // 1/sqrt(1.0001)^32768  = 1.0001^(-16384)
-    0.0377550449588794, // 1/sqrt(1.0001)^65536  = 1.0001^(-32768)
-    0.0014254434198470, // 1/sqrt(1.0001)^131072 = 1.0001^(-65536)
-    0.0000020318889432, // 1/sqrt(1.0001)^262144 = 1.0001^(-131072)
-    0.0000000000041286, // 1/sqrt(1.0001)^524288 = 1.0001^(-262144)
+/// Minimum tick supported by Uniswap V3/V4 (log base sqrt(1.0001) of 2^-128)
+pub const MIN_TICK: i32 = -887272;
+
+/// Maximum tick supported by Uniswap V3/V4 (log base sqrt(1.0001) of 2^128)
+pub const MAX_TICK: i32 = 887272;
+
+/// sqrtPriceX96 at `MIN_TICK`
+pub const MIN_SQRT_RATIO: U256 = U256::from_limbs([4295128739, 0, 0, 0]);
+
+/// sqrtPriceX96 at `MAX_TICK`
+pub const MAX_SQRT_RATIO: U256 =
+    U256::from_limbs([0x5d951d5263988d26, 0xefd1fc6a50648849, 0xfffd8963, 0]);
+
+/// Q128 multipliers of TickMath.getSqrtRatioAtTick: 1/sqrt(1.0001)^(2^i) for i = 1..19
+const TICK_MULTIPLIERS: [u128; 19] = [
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x9aa508b5b7a84e1c677de54f3e99bc9,
+    0x5d6af8dedb81196699c329225ee604,
+    0x2216e584f5fa1ea926041bedfe98,
+    0x48a170391f7dc42444e8fa2,
 ];
 
 // ============================================
-// Tick to Price Conversion
+// Full-precision Helpers (FullMath.sol)
 // ============================================
 
-/// Convert a tick to sqrt price ratio (Q64.96 format)
-///
-/// Formula: sqrt(1.0001^tick) * 2^96
-/// Valid tick range: -887272 to 887272 (Uniswap V3 limits)
-#[inline(always)]
-pub fn tick_to_sqrt_price_x96(tick: i32) -> f64 {
-    // Clamp tick to valid range
-    let clamped_tick = tick.clamp(-887272, 887272);
-    let abs_tick = clamped_tick.unsigned_abs() as usize;
-
-    // Start with ratio = 1.0
-    let mut ratio = 1.0_f64;
-
-    // Unrolled loop for the first 10 bits (most common case)
-    // This covers ticks up to 1023 which is very common
-    if abs_tick & 0x1 != 0 {
-        ratio *= TICK_MULTIPLIERS[0];
-    }
-    if abs_tick & 0x2 != 0 {
-        ratio *= TICK_MULTIPLIERS[1];
-    }
-    if abs_tick & 0x4 != 0 {
-        ratio *= TICK_MULTIPLIERS[2];
-    }
-    if abs_tick & 0x8 != 0 {
-        ratio *= TICK_MULTIPLIERS[3];
-    }
-    if abs_tick & 0x10 != 0 {
-        ratio *= TICK_MULTIPLIERS[4];
-    }
-    if abs_tick & 0x20 != 0 {
-        ratio *= TICK_MULTIPLIERS[5];
-    }
-    if abs_tick & 0x40 != 0 {
-        ratio *= TICK_MULTIPLIERS[6];
-    }
-    if abs_tick & 0x80 != 0 {
-        ratio *= TICK_MULTIPLIERS[7];
-    }
-    if abs_tick & 0x100 != 0 {
-        ratio *= TICK_MULTIPLIERS[8];
-    }
-    if abs_tick & 0x200 != 0 {
-        ratio *= TICK_MULTIPLIERS[9];
-    }
-    if abs_tick & 0x400 != 0 {
-        ratio *= TICK_MULTIPLIERS[10];
-    }
-    if abs_tick & 0x800 != 0 {
-        ratio *= TICK_MULTIPLIERS[11];
-    }
-    if abs_tick & 0x1000 != 0 {
-        ratio *= TICK_MULTIPLIERS[12];
+/// floor(a * b / denominator) with a 512-bit intermediate.
+/// Returns None on division by zero or if the result overflows U256.
+fn mul_div(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
     }
-    if abs_tick & 0x2000 != 0 {
-        ratio *= TICK_MULTIPLIERS[13];
-    }
-    if abs_tick & 0x4000 != 0 {
-        ratio *= TICK_MULTIPLIERS[14];
-    }
-    if abs_tick & 0x8000 != 0 {
-        ratio *= TICK_MULTIPLIERS[15];
-    }
-    if abs_tick & 0x10000 != 0 {
-        ratio *= TICK_MULTIPLIERS[16];
-    }
-    if abs_tick & 0x20000 != 0 {
-        ratio *= TICK_MULTIPLIERS[17];
-    }
-    if abs_tick & 0x40000 != 0 {
-        ratio *= TICK_MULTIPLIERS[18];
+    let quotient = U512::from(a) * U512::from(b) / U512::from(denominator);
+    narrow(quotient)
+}
+
+/// ceil(a * b / denominator) with a 512-bit intermediate.
+fn mul_div_rounding_up(a: U256, b: U256, denominator: U256) -> Option<U256> {
+    if denominator.is_zero() {
+        return None;
     }
-    if abs_tick & 0x80000 != 0 {
-        ratio *= TICK_MULTIPLIERS[19];
+    let product = U512::from(a) * U512::from(b);
+    let denominator = U512::from(denominator);
+    let mut quotient = product / denominator;
+    if !(product % denominator).is_zero() {
+        quotient += U512::from(1u8);
     }
+    narrow(quotient)
+}
 
-    // For positive ticks, take reciprocal
-    if clamped_tick > 0 {
-        ratio = 1.0 / ratio;
+fn narrow(value: U512) -> Option<U256> {
+    if value.bit_len() > 256 {
+        return None;
     }
-
-    // Convert to Q64.96 format by multiplying by 2^96
-    ratio * Q96
+    Some(U256::from_limbs_slice(&value.as_limbs()[..4]))
 }
 
 // ============================================
-// Liquidity Amount Calculations
+// TickMath
 // ============================================
 
-/// Calculate amount0 from liquidity and tick range (for V3/V4 Mint/Burn event parsing)
-/// Uniswap V3 formula: amount0 = L * (1/sqrt_a - 1/sqrt_b)
-fn calculate_amount0_from_liquidity(liquidity: f64, tick_lower: i32, tick_upper: i32) -> f64 {
-    if !liquidity.is_finite() || !(0.0..=1e40).contains(&liquidity) {
-        return 0.0;
-    }
-
-    let sqrt_ratio_a_x96 = tick_to_sqrt_price_x96(tick_lower);
-    let sqrt_ratio_b_x96 = tick_to_sqrt_price_x96(tick_upper);
-
-    if sqrt_ratio_a_x96 <= 0.0 || sqrt_ratio_b_x96 <= 0.0 {
-        return 0.0;
+/// sqrt(1.0001^tick) * 2^96, exactly as `TickMath.getSqrtRatioAtTick`.
+///
+/// Returns None if `tick` is outside `MIN_TICK..=MAX_TICK`.
+pub fn get_sqrt_ratio_at_tick(tick: i32) -> Option<U256> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
     }
+    let abs_tick = tick.unsigned_abs();
 
-    let (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
-        (sqrt_ratio_b_x96, sqrt_ratio_a_x96)
+    let mut ratio = if abs_tick & 0x1 != 0 {
+        U256::from(0xfffcb933bd6fad37aa2d162d1a594001u128)
     } else {
-        (sqrt_ratio_a_x96, sqrt_ratio_b_x96)
+        U256::from(1u8) << 128
     };
-
-    let sqrt_ratio_a = sqrt_ratio_a_x96 * Q96_INV;
-    let sqrt_ratio_b = sqrt_ratio_b_x96 * Q96_INV;
-
-    if sqrt_ratio_a < 1e-15 {
-        return 0.0;
+    for (bit, multiplier) in TICK_MULTIPLIERS.iter().enumerate() {
+        if abs_tick & (0x2 << bit) != 0 {
+            ratio = (ratio * U256::from(*multiplier)) >> 128;
+        }
     }
 
-    let inv_sqrt_a = 1.0 / sqrt_ratio_a;
-    let inv_sqrt_b = 1.0 / sqrt_ratio_b;
-
-    if inv_sqrt_a > 1e15 || inv_sqrt_b > 1e15 {
-        return 0.0;
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
     }
 
-    let diff = inv_sqrt_a - inv_sqrt_b;
-    if diff <= 0.0 || !diff.is_finite() {
-        return 0.0;
-    }
+    // Q128.128 -> Q64.96, rounding up so getTickAtSqrtRatio stays consistent
+    let round_up = !(ratio & U256::from(u32::MAX)).is_zero();
+    Some((ratio >> 32) + U256::from(round_up as u8))
+}
 
-    let result = liquidity * diff;
-    if !result.is_finite() || !(0.0..=1e35).contains(&result) {
-        return 0.0;
+/// Greatest tick whose sqrt ratio is <= `sqrt_price_x96`, exactly as
+/// `TickMath.getTickAtSqrtRatio`.
+///
+/// Returns None if `sqrt_price_x96` is outside `MIN_SQRT_RATIO..MAX_SQRT_RATIO`.
+pub fn get_tick_at_sqrt_ratio(sqrt_price_x96: U256) -> Option<i32> {
+    if sqrt_price_x96 < MIN_SQRT_RATIO || sqrt_price_x96 >= MAX_SQRT_RATIO {
+        return None;
+    }
+
+    let ratio: U256 = sqrt_price_x96 << 32;
+    let msb = ratio.bit_len() - 1;
+    let mut r = if msb >= 128 { ratio >> (msb - 127) } else { ratio << (127 - msb) };
+
+    // Integer part of log2, then 14 fractional bits by repeated squaring
+    let mut log_2: i128 = (msb as i128 - 128) << 64;
+    for shift in (50..=63).rev() {
+        r = (r * r) >> 127usize;
+        let f: U256 = r >> 128usize;
+        log_2 |= (f.to::<u8>() as i128) << shift;
+        r >>= f.to::<usize>();
+    }
+
+    // log_sqrt(1.0001)(ratio) as a Q128.128 number
+    let log_sqrt10001 =
+        I256::try_from(log_2).ok()? * I256::from_dec_str("255738958999603826347141").ok()?;
+
+    let tick_low = (log_sqrt10001
+        - I256::from_dec_str("3402992956809132418596140100660247210").ok()?)
+    .asr(128);
+    let tick_high = (log_sqrt10001
+        + I256::from_dec_str("291339464771989622907027621153398088495").ok()?)
+    .asr(128);
+    let tick_low = i32::try_from(tick_low).ok()?;
+    let tick_high = i32::try_from(tick_high).ok()?;
+
+    if tick_low == tick_high || get_sqrt_ratio_at_tick(tick_high)? > sqrt_price_x96 {
+        Some(tick_low)
+    } else {
+        Some(tick_high)
     }
-
-    result
 }
 
-/// Calculate amount1 from liquidity and tick range (for V3/V4 Mint/Burn event parsing)
-/// Uniswap V3 formula: amount1 = L * (sqrtRatioBX96 - sqrtRatioAX96) / Q96
-fn calculate_amount1_from_liquidity(liquidity: f64, tick_lower: i32, tick_upper: i32) -> f64 {
-    if !liquidity.is_finite() || !(0.0..=1e40).contains(&liquidity) {
-        return 0.0;
+// ============================================
+// SqrtPriceMath
+// ============================================
+
+/// Amount of token0 between two sqrt prices for `liquidity`, exactly as
+/// `SqrtPriceMath.getAmount0Delta`: L * (sqrtB - sqrtA) / (sqrtA * sqrtB).
+pub fn get_amount0_delta(
+    sqrt_ratio_a_x96: U256,
+    sqrt_ratio_b_x96: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<U256> {
+    let (sqrt_a, sqrt_b) = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
+        (sqrt_ratio_b_x96, sqrt_ratio_a_x96)
+    } else {
+        (sqrt_ratio_a_x96, sqrt_ratio_b_x96)
+    };
+    if sqrt_a.is_zero() {
+        return None;
     }
 
-    let sqrt_ratio_a_x96 = tick_to_sqrt_price_x96(tick_lower);
-    let sqrt_ratio_b_x96 = tick_to_sqrt_price_x96(tick_upper);
+    let numerator1 = U256::from(liquidity) << 96;
+    let numerator2 = sqrt_b - sqrt_a;
 
-    if !sqrt_ratio_a_x96.is_finite() || !sqrt_ratio_b_x96.is_finite() {
-        return 0.0;
+    if round_up {
+        let ceil = mul_div_rounding_up(numerator1, numerator2, sqrt_b)?;
+        Some(ceil.div_ceil(sqrt_a))
+    } else {
+        Some(mul_div(numerator1, numerator2, sqrt_b)? / sqrt_a)
     }
+}
 
-    let (sqrt_ratio_a_x96, sqrt_ratio_b_x96) = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
+/// Amount of token1 between two sqrt prices for `liquidity`, exactly as
+/// `SqrtPriceMath.getAmount1Delta`: L * (sqrtB - sqrtA) / 2^96.
+pub fn get_amount1_delta(
+    sqrt_ratio_a_x96: U256,
+    sqrt_ratio_b_x96: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> Option<U256> {
+    let (sqrt_a, sqrt_b) = if sqrt_ratio_a_x96 > sqrt_ratio_b_x96 {
         (sqrt_ratio_b_x96, sqrt_ratio_a_x96)
     } else {
         (sqrt_ratio_a_x96, sqrt_ratio_b_x96)
     };
+    let q96 = U256::from(1u8) << 96;
 
-    let diff_x96 = sqrt_ratio_b_x96 - sqrt_ratio_a_x96;
-    if diff_x96 <= 0.0 || !diff_x96.is_finite() {
-        return 0.0;
-    }
-
-    let result = liquidity * diff_x96 * Q96_INV;
-    if !result.is_finite() || !(0.0..=1e50).contains(&result) {
-        return 0.0;
+    if round_up {
+        mul_div_rounding_up(U256::from(liquidity), sqrt_b - sqrt_a, q96)
+    } else {
+        mul_div(U256::from(liquidity), sqrt_b - sqrt_a, q96)
     }
-
-    result
 }
 
-/// Calculate token amounts for a liquidity change based on current tick (for V4 event parsing)
-/// Returns (amount0, amount1)
+// ============================================
+// Liquidity Amount Calculations
+// ============================================
+
+/// Token amounts moved by a liquidity change on `[tick_lower, tick_upper)` at the
+/// pool's current `sqrt_price_x96`, as computed by the pool contract
+/// (for V4 ModifyLiquidity events, which carry no amounts).
 ///
-/// Includes bounds checking to prevent overflow from malformed tick data
+/// Amounts are rounded up when liquidity is added and down when it is removed.
+/// Returns (amount0, amount1), or None for an invalid range.
 pub fn calculate_mint_amounts(
-    liquidity: f64,
-    current_tick: i32,
+    liquidity: u128,
+    sqrt_price_x96: U256,
     tick_lower: i32,
     tick_upper: i32,
-) -> (f64, f64) {
-    // Sanity check: tick_lower must be less than tick_upper
+    is_adding: bool,
+) -> Option<(U256, U256)> {
     if tick_lower >= tick_upper {
-        return (0.0, 0.0);
+        return None;
     }
 
-    // Sanity check: liquidity bounds
-    const MAX_LIQUIDITY: f64 = 1e35;
-    if !liquidity.is_finite() || liquidity <= 0.0 || liquidity > MAX_LIQUIDITY {
-        return (0.0, 0.0);
-    }
+    let sqrt_lower = get_sqrt_ratio_at_tick(tick_lower)?;
+    let sqrt_upper = get_sqrt_ratio_at_tick(tick_upper)?;
 
-    // Valid tick range is -887272 to 887272 (Uniswap V3 limits)
-    // These are already clamped in tick_to_sqrt_price_x96, but check anyway
-    if tick_lower < -887272 || tick_upper > 887272 {
-        return (0.0, 0.0);
+    if sqrt_price_x96 < sqrt_lower {
+        // Range above the current price: only token0
+        let amount0 = get_amount0_delta(sqrt_lower, sqrt_upper, liquidity, is_adding)?;
+        Some((amount0, U256::ZERO))
+    } else if sqrt_price_x96 < sqrt_upper {
+        // Current price inside the range: both tokens
+        let amount0 = get_amount0_delta(sqrt_price_x96, sqrt_upper, liquidity, is_adding)?;
+        let amount1 = get_amount1_delta(sqrt_lower, sqrt_price_x96, liquidity, is_adding)?;
+        Some((amount0, amount1))
+    } else {
+        // Range below the current price: only token1
+        let amount1 = get_amount1_delta(sqrt_lower, sqrt_upper, liquidity, is_adding)?;
+        Some((U256::ZERO, amount1))
     }
+}
 
-    if current_tick < tick_lower {
-        let a0 = calculate_amount0_from_liquidity(liquidity, tick_lower, tick_upper);
-        (a0, 0.0)
-    } else if current_tick >= tick_upper {
-        let a1 = calculate_amount1_from_liquidity(liquidity, tick_lower, tick_upper);
-        (0.0, a1)
-    } else {
-        let a0 = calculate_amount0_from_liquidity(liquidity, current_tick, tick_upper);
-        let a1 = calculate_amount1_from_liquidity(liquidity, tick_lower, current_tick);
-        (a0, a1)
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u256(value: &str) -> U256 {
+        U256::from_str_radix(value, 10).unwrap()
+    }
+
+    #[test]
+    fn test_sqrt_ratio_at_tick_matches_contract() {
+        assert_eq!(get_sqrt_ratio_at_tick(MIN_TICK), Some(MIN_SQRT_RATIO));
+        assert_eq!(get_sqrt_ratio_at_tick(MAX_TICK), Some(MAX_SQRT_RATIO));
+        assert_eq!(
+            MAX_SQRT_RATIO,
+            u256("1461446703485210103287273052203988822378723970342")
+        );
+        assert_eq!(get_sqrt_ratio_at_tick(0), Some(U256::from(1u8) << 96));
+        assert_eq!(
+            get_sqrt_ratio_at_tick(1),
+            Some(u256("79232123823359799118286999568"))
+        );
+        assert_eq!(
+            get_sqrt_ratio_at_tick(-1),
+            Some(u256("79224201403219477170569942574"))
+        );
+        assert_eq!(get_sqrt_ratio_at_tick(MIN_TICK - 1), None);
+        assert_eq!(get_sqrt_ratio_at_tick(MAX_TICK + 1), None);
+    }
+
+    #[test]
+    fn test_tick_at_sqrt_ratio_matches_contract() {
+        assert_eq!(get_tick_at_sqrt_ratio(MIN_SQRT_RATIO), Some(MIN_TICK));
+        assert_eq!(
+            get_tick_at_sqrt_ratio(MAX_SQRT_RATIO - U256::from(1u8)),
+            Some(MAX_TICK - 1)
+        );
+        assert_eq!(get_tick_at_sqrt_ratio(MAX_SQRT_RATIO), None);
+        assert_eq!(
+            get_tick_at_sqrt_ratio(MIN_SQRT_RATIO - U256::from(1u8)),
+            None
+        );
+
+        // Round trip, and the tick is the floor for prices between two ticks
+        for tick in [-887271, -200_000, -50, -1, 0, 1, 50, 201_234, 887_271] {
+            let sqrt = get_sqrt_ratio_at_tick(tick).unwrap();
+            assert_eq!(get_tick_at_sqrt_ratio(sqrt), Some(tick));
+            assert_eq!(
+                get_tick_at_sqrt_ratio(sqrt - U256::from(1u8)),
+                Some(tick - 1)
+            );
+        }
+    }
+
+    #[test]
+    fn test_amount_deltas_match_contract() {
+        // SqrtPriceMath.spec: price 1 -> 1.21, 1e18 liquidity
+        let sqrt_1 = U256::from(1u8) << 96;
+        let sqrt_121 = u256("87150978765690771352898345369");
+        let liquidity = 1_000_000_000_000_000_000u128;
+
+        assert_eq!(
+            get_amount0_delta(sqrt_1, sqrt_121, liquidity, true),
+            Some(u256("90909090909090910"))
+        );
+        assert_eq!(
+            get_amount0_delta(sqrt_1, sqrt_121, liquidity, false),
+            Some(u256("90909090909090909"))
+        );
+        assert_eq!(
+            get_amount1_delta(sqrt_1, sqrt_121, liquidity, true),
+            Some(u256("100000000000000000"))
+        );
+        assert_eq!(
+            get_amount1_delta(sqrt_1, sqrt_121, liquidity, false),
+            Some(u256("99999999999999999"))
+        );
+        assert_eq!(
+            get_amount0_delta(sqrt_1, sqrt_1, liquidity, true),
+            Some(U256::ZERO)
+        );
+    }
+
+    #[test]
+    fn test_mint_amounts_by_range_position() {
+        let liquidity = 1_000_000_000_000_000_000u128;
+        let price = get_sqrt_ratio_at_tick(0).unwrap();
+
+        let (above0, above1) = calculate_mint_amounts(liquidity, price, 60, 120, true).unwrap();
+        assert!(above0 > U256::ZERO && above1.is_zero());
+
+        let (below0, below1) = calculate_mint_amounts(liquidity, price, -120, -60, true).unwrap();
+        assert!(below0.is_zero() && below1 > U256::ZERO);
+
+        let (in0, in1) = calculate_mint_amounts(liquidity, price, -60, 60, true).unwrap();
+        assert!(in0 > U256::ZERO && in1 > U256::ZERO);
+
+        // Removing rounds down, adding rounds up
+        let (out0, out1) = calculate_mint_amounts(liquidity, price, -60, 60, false).unwrap();
+        assert!(out0 <= in0 && out1 <= in1);
+
+        assert_eq!(calculate_mint_amounts(liquidity, price, 60, 60, true), None);
     }
 }
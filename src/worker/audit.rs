@@ -0,0 +1,362 @@
+//! Data-completeness audit of indexed block ranges.
+//!
+//! Compares, per bucket of blocks, the logs HyperSync returns for a chain with the
+//! rows stored in the ClickHouse `events` and `supply_events` tables. A source log
+//! only counts when the worker would have stored a row for it: pool events of pools
+//! known to PostgreSQL, supply changes of known tokens.
+//!
+//! The expected counts are approximate (e.g. a pool event with an unknown token is
+//! counted but never stored), so an incomplete bucket is a candidate for
+//! `ChainWorker::reindex_range`, which is safe on false positives: it only inserts
+//! the rows missing from ClickHouse.
+
+use anyhow::Result;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{utils::hex_encode, worker::parser::ParsedLog, Database};
+
+/// ClickHouse table a source log is stored in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IndexedTable {
+    Events,
+    SupplyEvents,
+}
+
+impl IndexedTable {
+    fn name(self) -> &'static str {
+        match self {
+            IndexedTable::Events => "events",
+            IndexedTable::SupplyEvents => "supply_events",
+        }
+    }
+}
+
+/// Source vs. indexed row counts of one bucket of blocks.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeAudit {
+    pub from_block: u64,
+    /// Exclusive
+    pub to_block: u64,
+    pub expected_events: u64,
+    pub indexed_events: u64,
+    pub expected_supply_events: u64,
+    pub indexed_supply_events: u64,
+}
+
+impl RangeAudit {
+    /// Every expected row is indexed
+    pub fn is_complete(&self) -> bool {
+        self.indexed_events >= self.expected_events
+            && self.indexed_supply_events >= self.expected_supply_events
+    }
+}
+
+/// Rows inserted by `ChainWorker::reindex_range`.
+#[derive(Debug, Clone, Default)]
+pub struct ReindexStats {
+    pub events: usize,
+    pub supply_events: usize,
+}
+
+/// Row counts of one bucket, keyed by bucket index.
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct BucketCounts {
+    pub events: u64,
+    pub supply_events: u64,
+}
+
+/// Table, block and pool (or token) address of the row the worker stores for `log`.
+fn source_row(log: &ParsedLog) -> Option<(IndexedTable, u64, String)> {
+    match log {
+        ParsedLog::V2Mint {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::V3Mint {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::V2Burn {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::V3Burn {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::V3Collect {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::V2Swap {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::V3Swap {
+            log_address,
+            block_number,
+            ..
+        } => Some((IndexedTable::Events, *block_number, log_address.clone())),
+        ParsedLog::V4ModifyLiquidity {
+            event,
+            block_number,
+            ..
+        } => Some((
+            IndexedTable::Events,
+            *block_number,
+            hex_encode(event.id.as_slice()),
+        )),
+        ParsedLog::V4Swap {
+            event,
+            block_number,
+            ..
+        } => Some((
+            IndexedTable::Events,
+            *block_number,
+            hex_encode(event.id.as_slice()),
+        )),
+        ParsedLog::SupplyTransfer {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::SupplyDeposit {
+            log_address,
+            block_number,
+            ..
+        }
+        | ParsedLog::SupplyWithdrawal {
+            log_address,
+            block_number,
+            ..
+        } => Some((
+            IndexedTable::SupplyEvents,
+            *block_number,
+            log_address.clone(),
+        )),
+        _ => None,
+    }
+}
+
+/// Adds the rows the worker stores for `logs` to `expected`, per bucket.
+pub(crate) async fn count_source_rows(
+    db: &Database,
+    chain_id: u64,
+    logs: &[ParsedLog],
+    from_block: u64,
+    bucket_size: u64,
+    expected: &mut FxHashMap<u64, BucketCounts>,
+) -> Result<()> {
+    let rows: Vec<_> = logs.iter().filter_map(source_row).collect();
+
+    let addresses = |table: IndexedTable| -> Vec<String> {
+        let mut addresses: Vec<String> = rows
+            .iter()
+            .filter(|(t, _, _)| *t == table)
+            .map(|(_, _, address)| address.clone())
+            .collect();
+        addresses.sort_unstable();
+        addresses.dedup();
+        addresses
+    };
+
+    let pg = db.postgres.pool.get().await?;
+    let mut known: FxHashSet<(IndexedTable, String)> = FxHashSet::default();
+    for (table, query) in [
+        (
+            IndexedTable::Events,
+            "SELECT address FROM indexer.pools WHERE chain_id = $1 AND address = ANY($2)",
+        ),
+        (
+            IndexedTable::SupplyEvents,
+            "SELECT address FROM indexer.tokens WHERE chain_id = $1 AND address = ANY($2)",
+        ),
+    ] {
+        let addresses = addresses(table);
+        if addresses.is_empty() {
+            continue;
+        }
+        for row in pg.query(query, &[&(chain_id as i64), &addresses]).await? {
+            known.insert((table, row.get("address")));
+        }
+    }
+
+    for (table, block_number, address) in rows {
+        if block_number < from_block || !known.contains(&(table, address)) {
+            continue;
+        }
+        let counts = expected
+            .entry((block_number - from_block) / bucket_size)
+            .or_default();
+        match table {
+            IndexedTable::Events => counts.events += 1,
+            IndexedTable::SupplyEvents => counts.supply_events += 1,
+        }
+    }
+
+    Ok(())
+}
+
+/// Distinct rows per bucket of `[from_block, to_block)` in ClickHouse.
+pub(crate) async fn indexed_counts(
+    db: &Database,
+    chain_id: u64,
+    from_block: u64,
+    to_block: u64,
+    bucket_size: u64,
+) -> Result<FxHashMap<u64, BucketCounts>> {
+    let mut indexed: FxHashMap<u64, BucketCounts> = FxHashMap::default();
+
+    for table in [IndexedTable::Events, IndexedTable::SupplyEvents] {
+        let rows = db
+            .clickhouse
+            .client
+            .query(&format!(
+                "SELECT intDiv(block_number - ?, ?) AS bucket,
+                        uniqExact(tx_hash, log_index) AS row_count
+                 FROM indexer.{}
+                 WHERE chain_id = ? AND block_number >= ? AND block_number < ?
+                 GROUP BY bucket",
+                table.name()
+            ))
+            .bind(from_block)
+            .bind(bucket_size)
+            .bind(chain_id)
+            .bind(from_block)
+            .bind(to_block)
+            .fetch_all::<BucketRows>()
+            .await?;
+
+        for row in rows {
+            let counts = indexed.entry(row.bucket).or_default();
+            match table {
+                IndexedTable::Events => counts.events = row.row_count,
+                IndexedTable::SupplyEvents => counts.supply_events = row.row_count,
+            }
+        }
+    }
+
+    Ok(indexed)
+}
+
+/// (tx_hash, log_index) of the rows of `table` in `[from_block, to_block)`.
+pub(crate) async fn existing_row_keys(
+    db: &Database,
+    chain_id: u64,
+    table: IndexedTable,
+    from_block: u64,
+    to_block: u64,
+) -> Result<FxHashSet<(String, u32)>> {
+    let rows = db
+        .clickhouse
+        .client
+        .query(&format!(
+            "SELECT DISTINCT tx_hash, log_index FROM indexer.{}
+             WHERE chain_id = ? AND block_number >= ? AND block_number < ?",
+            table.name()
+        ))
+        .bind(chain_id)
+        .bind(from_block)
+        .bind(to_block)
+        .fetch_all::<RowKey>()
+        .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|row| (row.tx_hash, row.log_index))
+        .collect())
+}
+
+/// One `RangeAudit` per bucket of `[from_block, to_block)`.
+pub(crate) fn build_report(
+    from_block: u64,
+    to_block: u64,
+    bucket_size: u64,
+    expected: &FxHashMap<u64, BucketCounts>,
+    indexed: &FxHashMap<u64, BucketCounts>,
+) -> Vec<RangeAudit> {
+    let buckets = to_block.saturating_sub(from_block).div_ceil(bucket_size);
+
+    (0..buckets)
+        .map(|bucket| {
+            let expected = expected.get(&bucket).copied().unwrap_or_default();
+            let indexed = indexed.get(&bucket).copied().unwrap_or_default();
+            let start = from_block + bucket * bucket_size;
+
+            RangeAudit {
+                from_block: start,
+                to_block: (start + bucket_size).min(to_block),
+                expected_events: expected.events,
+                indexed_events: indexed.events,
+                expected_supply_events: expected.supply_events,
+                indexed_supply_events: indexed.supply_events,
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, clickhouse::Row, serde::Deserialize)]
+struct BucketRows {
+    bucket: u64,
+    row_count: u64,
+}
+
+#[derive(Debug, clickhouse::Row, serde::Deserialize)]
+struct RowKey {
+    tx_hash: String,
+    log_index: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_report_flags_buckets_with_missing_rows() {
+        let mut expected = FxHashMap::default();
+        expected.insert(
+            0,
+            BucketCounts {
+                events: 3,
+                supply_events: 1,
+            },
+        );
+        expected.insert(
+            2,
+            BucketCounts {
+                events: 2,
+                supply_events: 0,
+            },
+        );
+
+        let mut indexed = FxHashMap::default();
+        indexed.insert(
+            0,
+            BucketCounts {
+                events: 3,
+                supply_events: 1,
+            },
+        );
+        indexed.insert(
+            2,
+            BucketCounts {
+                events: 1,
+                supply_events: 0,
+            },
+        );
+
+        let report = build_report(100, 125, 10, &expected, &indexed);
+
+        assert_eq!(report.len(), 3);
+        assert!(report[0].is_complete());
+        assert!(report[1].is_complete()); // No source logs, nothing indexed
+        assert!(!report[2].is_complete());
+        assert_eq!((report[2].from_block, report[2].to_block), (120, 125));
+    }
+}
@@ -0,0 +1,565 @@
+//! MEV analysis over the swaps of a batch.
+//!
+//! Runs after USD pricing, on the events of each batch. Swaps are ordered by
+//! `log_index`, which is the execution order, and grouped per block and pool for
+//! sandwiches, per transaction for arbitrages.
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::db::models::{Arbitrage, Event, MevSandwich};
+
+/// Maximum relative difference between the amount bought by the front-run and
+/// the amount sold back by the back-run (bots may keep dust or pay transfer taxes).
+const BACKRUN_AMOUNT_TOLERANCE: f64 = 0.10;
+
+/// `Event::mev_role` values
+pub const ROLE_SANDWICH_FRONTRUN: &str = "sandwich_frontrun";
+pub const ROLE_SANDWICH_VICTIM: &str = "sandwich_victim";
+pub const ROLE_SANDWICH_BACKRUN: &str = "sandwich_backrun";
+pub const ROLE_ARBITRAGE: &str = "arbitrage";
+
+/// Which token the swapper received: 0 for token0, 1 for token1.
+///
+/// Direction convention: 1 = pool loss (swapper receives), -1 = pool gain.
+pub(super) fn bought_side(event: &Event) -> Option<u8> {
+    match (event.amount0_direction, event.amount1_direction) {
+        (1, -1) => Some(0),
+        (-1, 1) => Some(1),
+        _ => None,
+    }
+}
+
+/// Amount of token `side` moved by the swap (f64, for USD math).
+fn side_amount(event: &Event, side: u8) -> f64 {
+    if side == 0 {
+        event.amount0_f64()
+    } else {
+        event.amount1_f64()
+    }
+}
+
+fn side_token(event: &Event, side: u8) -> &str {
+    if side == 0 {
+        &event.token0
+    } else {
+        &event.token1
+    }
+}
+
+/// Token the swapper paid with.
+pub(super) fn sold_token(event: &Event) -> Option<&str> {
+    bought_side(event).map(|side| side_token(event, 1 - side))
+}
+
+/// Token the swapper received.
+pub(super) fn bought_token(event: &Event) -> Option<&str> {
+    bought_side(event).map(|side| side_token(event, side))
+}
+
+/// Detect sandwich attacks and flag the events involved.
+///
+/// A sandwich is a front-run swap, one or more victim swaps buying the same token
+/// in other transactions, then a back-run sent by the same `tx_from` selling that
+/// token back, all on the same pool in the same block. Each swap belongs to at most
+/// one sandwich.
+///
+/// Actors are transaction senders, not swap makers: the maker is the router or
+/// contract calling the pool, shared by unrelated users of the same router.
+/// Swaps whose sender is unknown are skipped.
+///
+/// `token_price_usd` resolves USD prices for profit and loss (0.0 when unknown).
+pub fn detect_sandwiches(
+    chain_id: u64,
+    events: &mut [Event],
+    mut token_price_usd: impl FnMut(&str) -> f64,
+) -> Vec<MevSandwich> {
+    // Swaps grouped by (block, pool), in execution order
+    let mut groups: FxHashMap<(u64, &str), Vec<usize>> = FxHashMap::default();
+    for (i, event) in events.iter().enumerate() {
+        if event.event_type == "swap" && !event.tx_from.is_empty() && bought_side(event).is_some() {
+            groups
+                .entry((event.block_number, event.pool_address.as_str()))
+                .or_default()
+                .push(i);
+        }
+    }
+
+    let mut found: Vec<(usize, Vec<usize>, usize)> = Vec::new();
+    for swaps in groups.values_mut() {
+        if swaps.len() < 3 {
+            continue;
+        }
+        swaps.sort_by_key(|&i| events[i].log_index);
+
+        let mut used: FxHashSet<usize> = FxHashSet::default();
+        for (pos, &front) in swaps.iter().enumerate() {
+            if used.contains(&front) {
+                continue;
+            }
+            if let Some((victims, back)) = match_sandwich(events, &swaps[pos..], &used) {
+                used.insert(front);
+                used.insert(back);
+                used.extend(victims.iter().copied());
+                found.push((front, victims, back));
+            }
+        }
+    }
+
+    let mut sandwiches = Vec::new();
+    for (front, victims, back) in found {
+        sandwiches.extend(build_sandwiches(
+            chain_id,
+            events,
+            front,
+            &victims,
+            back,
+            &mut token_price_usd,
+        ));
+
+        events[front].mev_role = ROLE_SANDWICH_FRONTRUN.to_string();
+        events[back].mev_role = ROLE_SANDWICH_BACKRUN.to_string();
+        for &victim in &victims {
+            events[victim].mev_role = ROLE_SANDWICH_VICTIM.to_string();
+        }
+    }
+
+    sandwiches.sort_by_key(|s| (s.block_number, s.frontrun_log_index, s.victim_log_index));
+    sandwiches
+}
+
+/// Find the back-run and victims for the front-run `swaps[0]`.
+fn match_sandwich(
+    events: &[Event],
+    swaps: &[usize],
+    used: &FxHashSet<usize>,
+) -> Option<(Vec<usize>, usize)> {
+    let front = &events[swaps[0]];
+    let side = bought_side(front)?;
+    let bought = side_amount(front, side);
+    if bought <= 0.0 {
+        return None;
+    }
+
+    for (pos, &back_idx) in swaps.iter().enumerate().skip(2) {
+        let back = &events[back_idx];
+        if used.contains(&back_idx)
+            || back.tx_from != front.tx_from
+            || back.tx_hash == front.tx_hash
+            || bought_side(back) != Some(1 - side)
+        {
+            continue;
+        }
+        let sold = side_amount(back, side);
+        if (sold - bought).abs() > bought * BACKRUN_AMOUNT_TOLERANCE {
+            continue;
+        }
+
+        let victims: Vec<usize> = swaps[1..pos]
+            .iter()
+            .copied()
+            .filter(|&i| {
+                let event = &events[i];
+                !used.contains(&i)
+                    && event.tx_from != front.tx_from
+                    && event.tx_hash != front.tx_hash
+                    && event.tx_hash != back.tx_hash
+                    && bought_side(event) == Some(side)
+            })
+            .collect();
+
+        if !victims.is_empty() {
+            return Some((victims, back_idx));
+        }
+    }
+
+    None
+}
+
+/// One `MevSandwich` row per victim.
+///
+/// - Profit: the attacker's net flow of both tokens between front-run and back-run.
+/// - Victim loss: what the victim would have received at the front-run's average
+///   execution price, minus what they received. The front-run already moved the
+///   price, so this is a lower bound.
+fn build_sandwiches(
+    chain_id: u64,
+    events: &[Event],
+    front: usize,
+    victims: &[usize],
+    back: usize,
+    token_price_usd: &mut impl FnMut(&str) -> f64,
+) -> Vec<MevSandwich> {
+    let (front, back) = (&events[front], &events[back]);
+    let Some(side) = bought_side(front) else {
+        return Vec::new();
+    };
+    let other = 1 - side;
+
+    let bought_price = token_price_usd(side_token(front, side));
+    let other_price = token_price_usd(side_token(front, other));
+
+    let profit_usd = (side_amount(back, other) - side_amount(front, other)) * other_price
+        + (side_amount(front, side) - side_amount(back, side)) * bought_price;
+
+    // Other token paid per bought token by the front-run
+    let front_price = side_amount(front, other) / side_amount(front, side);
+
+    let losses: Vec<f64> = victims
+        .iter()
+        .map(|&i| {
+            let victim = &events[i];
+            let expected = side_amount(victim, other) / front_price;
+            ((expected - side_amount(victim, side)) * bought_price).max(0.0)
+        })
+        .map(|loss| if loss.is_finite() { loss } else { 0.0 })
+        .collect();
+    let total_loss: f64 = losses.iter().sum();
+
+    victims
+        .iter()
+        .zip(&losses)
+        .map(|(&i, &victim_loss_usd)| {
+            let victim = &events[i];
+            // Split the profit by victim loss, evenly if no loss could be measured
+            let share = if total_loss > 0.0 {
+                victim_loss_usd / total_loss
+            } else {
+                1.0 / victims.len() as f64
+            };
+
+            MevSandwich {
+                chain_id,
+                block_number: front.block_number,
+                timestamp: front.timestamp,
+                pool_address: front.pool_address.clone(),
+                attacker: front.tx_from.clone(),
+                victim: victim.tx_from.clone(),
+                frontrun_tx_hash: front.tx_hash.clone(),
+                frontrun_log_index: front.log_index,
+                victim_tx_hash: victim.tx_hash.clone(),
+                victim_log_index: victim.log_index,
+                backrun_tx_hash: back.tx_hash.clone(),
+                backrun_log_index: back.log_index,
+                token_bought: side_token(front, side).to_string(),
+                profit_usd: if profit_usd.is_finite() { profit_usd * share } else { 0.0 },
+                victim_loss_usd,
+            }
+        })
+        .collect()
+}
+
+/// Detect atomic arbitrages and flag the events involved.
+///
+/// An arbitrage is a closed token cycle (A→B→…→A) swapped within one transaction
+/// across at least two pools. Swaps already flagged by another detector are
+/// skipped, and each swap belongs to at most one cycle.
+///
+/// `token_price_usd` resolves USD prices for the profit (0.0 when unknown).
+pub fn detect_arbitrages(
+    chain_id: u64,
+    events: &mut [Event],
+    mut token_price_usd: impl FnMut(&str) -> f64,
+) -> Vec<Arbitrage> {
+    // Swaps grouped by transaction, in execution order
+    let mut txs: FxHashMap<&str, Vec<usize>> = FxHashMap::default();
+    for (i, event) in events.iter().enumerate() {
+        if event.event_type == "swap" && event.mev_role.is_empty() && bought_side(event).is_some() {
+            txs.entry(event.tx_hash.as_str()).or_default().push(i);
+        }
+    }
+
+    let mut found: Vec<Vec<usize>> = Vec::new();
+    for swaps in txs.values_mut() {
+        if swaps.len() < 2 {
+            continue;
+        }
+        swaps.sort_by_key(|&i| events[i].log_index);
+
+        let mut used: FxHashSet<usize> = FxHashSet::default();
+        for pos in 0..swaps.len() {
+            if used.contains(&swaps[pos]) {
+                continue;
+            }
+            if let Some(cycle) = match_cycle(events, &swaps[pos..], &used) {
+                used.extend(cycle.iter().copied());
+                found.push(cycle);
+            }
+        }
+    }
+
+    let mut arbitrages: Vec<Arbitrage> = found
+        .iter()
+        .filter_map(|cycle| build_arbitrage(chain_id, events, cycle, &mut token_price_usd))
+        .collect();
+
+    for &i in found.iter().flatten() {
+        events[i].mev_role = ROLE_ARBITRAGE.to_string();
+    }
+
+    arbitrages.sort_by_key(|a| (a.block_number, a.first_log_index));
+    arbitrages
+}
+
+/// Follow the tokens from `swaps[0]` until they return to the token it sold.
+///
+/// Each next leg is the first unused swap selling the token the previous leg bought.
+fn match_cycle(events: &[Event], swaps: &[usize], used: &FxHashSet<usize>) -> Option<Vec<usize>> {
+    let first = &events[swaps[0]];
+    let start = sold_token(first)?;
+    let mut held = bought_token(first)?;
+    let mut cycle = vec![swaps[0]];
+
+    for &i in &swaps[1..] {
+        let event = &events[i];
+        if used.contains(&i) || sold_token(event) != Some(held) {
+            continue;
+        }
+        cycle.push(i);
+        held = bought_token(event)?;
+
+        if held == start {
+            // A round trip on a single pool is not an arbitrage
+            let pools: FxHashSet<&str> = cycle
+                .iter()
+                .map(|&i| events[i].pool_address.as_str())
+                .collect();
+            return (pools.len() >= 2).then_some(cycle);
+        }
+    }
+
+    None
+}
+
+fn build_arbitrage(
+    chain_id: u64,
+    events: &[Event],
+    cycle: &[usize],
+    token_price_usd: &mut impl FnMut(&str) -> f64,
+) -> Option<Arbitrage> {
+    let (first, last) = (&events[*cycle.first()?], &events[*cycle.last()?]);
+    let token = sold_token(first)?;
+
+    let amount_in = side_amount(first, 1 - bought_side(first)?);
+    let amount_out = side_amount(last, bought_side(last)?);
+    let profit_usd = (amount_out - amount_in) * token_price_usd(token);
+
+    Some(Arbitrage {
+        chain_id,
+        block_number: first.block_number,
+        timestamp: first.timestamp,
+        tx_hash: first.tx_hash.clone(),
+        first_log_index: first.log_index,
+        last_log_index: last.log_index,
+        // The maker is the arbitrage contract, the searcher is the tx sender
+        trader: if first.tx_from.is_empty() { &first.maker } else { &first.tx_from }.clone(),
+        token: token.to_string(),
+        path: cycle
+            .iter()
+            .filter_map(|&i| sold_token(&events[i]).map(str::to_string))
+            .collect(),
+        pools: cycle
+            .iter()
+            .map(|&i| events[i].pool_address.clone())
+            .collect(),
+        hops: cycle.len().min(u8::MAX as usize) as u8,
+        amount_in,
+        amount_out,
+        profit_usd: if profit_usd.is_finite() { profit_usd } else { 0.0 },
+        volume_usd: cycle.iter().map(|&i| events[i].volume_usd).sum(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abis::v2, db::models::Token};
+    use alloy::primitives::{Address, U256};
+
+    const ATTACKER: Address = Address::repeat_byte(0xaa);
+    const VICTIM: Address = Address::repeat_byte(0xbb);
+    const ROUTER: Address = Address::repeat_byte(0xcc);
+
+    /// V2 swap on one pool; `buy0` buys token0 with token1, otherwise sells it.
+    fn swap(
+        maker: Address,
+        tx: &str,
+        log_index: u32,
+        buy0: bool,
+        amount0: u64,
+        amount1: u64,
+    ) -> Event {
+        let token0 = Token::new(
+            1,
+            "0x0000000000000000000000000000000000000001".into(),
+            "A".into(),
+            "A".into(),
+            0,
+        );
+        let token1 = Token::new(
+            1,
+            "0x0000000000000000000000000000000000000002".into(),
+            "B".into(),
+            "B".into(),
+            0,
+        );
+        let (a0, a1) = (U256::from(amount0), U256::from(amount1));
+        let event = v2::Swap {
+            sender: maker,
+            amount0In: if buy0 { U256::ZERO } else { a0 },
+            amount1In: if buy0 { a1 } else { U256::ZERO },
+            amount0Out: if buy0 { a0 } else { U256::ZERO },
+            amount1Out: if buy0 { U256::ZERO } else { a1 },
+            to: maker,
+        };
+        let mut event = Event::from_v2_swap(
+            1,
+            event,
+            &token0,
+            &token1,
+            100,
+            tx.into(),
+            log_index,
+            "0xpool".into(),
+            0,
+        );
+        event.tx_from = format!("{maker:#x}");
+        event
+    }
+
+    /// Same as `swap`, sent by `sender` through a shared router.
+    fn routed_swap(
+        sender: Address,
+        tx: &str,
+        log_index: u32,
+        buy0: bool,
+        amount0: u64,
+        amount1: u64,
+    ) -> Event {
+        let mut event = swap(ROUTER, tx, log_index, buy0, amount0, amount1);
+        event.tx_from = format!("{sender:#x}");
+        event
+    }
+
+    /// Same as `swap`, on another pool.
+    fn swap_on(
+        pool: &str,
+        tx: &str,
+        log_index: u32,
+        buy0: bool,
+        amount0: u64,
+        amount1: u64,
+    ) -> Event {
+        let mut event = swap(ATTACKER, tx, log_index, buy0, amount0, amount1);
+        event.pool_address = pool.to_string();
+        event
+    }
+
+    #[test]
+    fn test_detects_sandwich_and_flags_events() {
+        let mut events = vec![
+            swap(ATTACKER, "0x01", 1, true, 1_000, 1_000),
+            swap(VICTIM, "0x02", 2, true, 900, 1_000),
+            swap(ATTACKER, "0x03", 3, false, 1_000, 1_100),
+        ];
+
+        let sandwiches = detect_sandwiches(1, &mut events, |_| 1.0);
+
+        assert_eq!(sandwiches.len(), 1);
+        let sandwich = &sandwiches[0];
+        assert_eq!(sandwich.victim_log_index, 2);
+        assert_eq!(sandwich.backrun_log_index, 3);
+        assert!((sandwich.profit_usd - 100.0).abs() < 1e-9);
+        // At the front-run's price the victim would have received 1000, not 900
+        assert!((sandwich.victim_loss_usd - 100.0).abs() < 1e-9);
+
+        assert_eq!(events[0].mev_role, ROLE_SANDWICH_FRONTRUN);
+        assert_eq!(events[1].mev_role, ROLE_SANDWICH_VICTIM);
+        assert_eq!(events[2].mev_role, ROLE_SANDWICH_BACKRUN);
+    }
+
+    #[test]
+    fn test_ignores_round_trip_without_victim() {
+        let mut events = vec![
+            swap(ATTACKER, "0x01", 1, true, 1_000, 1_000),
+            swap(VICTIM, "0x02", 2, false, 500, 450),
+            swap(ATTACKER, "0x03", 3, false, 1_000, 1_000),
+        ];
+
+        assert!(detect_sandwiches(1, &mut events, |_| 1.0).is_empty());
+        assert!(events.iter().all(|event| event.mev_role.is_empty()));
+    }
+
+    #[test]
+    fn test_sandwich_actors_are_transaction_senders() {
+        // Attacker and victim both go through the same router
+        let mut events = vec![
+            routed_swap(ATTACKER, "0x01", 1, true, 1_000, 1_000),
+            routed_swap(VICTIM, "0x02", 2, true, 900, 1_000),
+            routed_swap(ATTACKER, "0x03", 3, false, 1_000, 1_100),
+        ];
+
+        let sandwiches = detect_sandwiches(1, &mut events, |_| 1.0);
+
+        assert_eq!(sandwiches.len(), 1);
+        assert_eq!(sandwiches[0].attacker, format!("{ATTACKER:#x}"));
+        assert_eq!(sandwiches[0].victim, format!("{VICTIM:#x}"));
+    }
+
+    #[test]
+    fn test_unrelated_users_of_one_router_are_not_linked() {
+        let third = Address::repeat_byte(0xdd);
+        let mut events = vec![
+            routed_swap(ATTACKER, "0x01", 1, true, 1_000, 1_000),
+            routed_swap(VICTIM, "0x02", 2, true, 900, 1_000),
+            routed_swap(third, "0x03", 3, false, 1_000, 1_100),
+        ];
+
+        assert!(detect_sandwiches(1, &mut events, |_| 1.0).is_empty());
+    }
+
+    #[test]
+    fn test_detects_two_pool_arbitrage() {
+        // Sell 1000 A for 1000 B on pool 1, buy 1050 A back with the B on pool 2
+        let mut events = vec![
+            swap_on("0xpool1", "0x01", 1, false, 1_000, 1_000),
+            swap_on("0xpool2", "0x01", 2, true, 1_050, 1_000),
+        ];
+
+        let arbitrages = detect_arbitrages(1, &mut events, |_| 2.0);
+
+        assert_eq!(arbitrages.len(), 1);
+        let arbitrage = &arbitrages[0];
+        assert_eq!(arbitrage.hops, 2);
+        assert_eq!(arbitrage.pools, vec!["0xpool1", "0xpool2"]);
+        assert_eq!(arbitrage.token, events[0].token0);
+        assert!((arbitrage.profit_usd - 100.0).abs() < 1e-9);
+        assert!(events.iter().all(|event| event.mev_role == ROLE_ARBITRAGE));
+    }
+
+    #[test]
+    fn test_arbitrage_trader_is_the_transaction_sender() {
+        // The searcher's contract makes both swaps
+        let mut events = vec![
+            swap_on("0xpool1", "0x01", 1, false, 1_000, 1_000),
+            swap_on("0xpool2", "0x01", 2, true, 1_050, 1_000),
+        ];
+        for event in &mut events {
+            event.maker = format!("{ROUTER:#x}");
+        }
+
+        let arbitrages = detect_arbitrages(1, &mut events, |_| 2.0);
+
+        assert_eq!(arbitrages[0].trader, format!("{ATTACKER:#x}"));
+    }
+
+    #[test]
+    fn test_ignores_single_pool_round_trip() {
+        let mut events = vec![
+            swap_on("0xpool1", "0x01", 1, false, 1_000, 1_000),
+            swap_on("0xpool1", "0x01", 2, true, 990, 1_000),
+        ];
+
+        assert!(detect_arbitrages(1, &mut events, |_| 1.0).is_empty());
+        assert!(events.iter().all(|event| event.mev_role.is_empty()));
+    }
+}
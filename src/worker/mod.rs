@@ -1,11 +1,19 @@
+pub mod audit;
 pub mod chains;
+pub mod mev;
 pub mod parser;
 pub mod price_resolver;
+pub mod pricing_graph;
+pub mod pricing_strategy;
 pub mod token_fetcher;
+pub mod trades;
 pub mod worker;
 
+pub use audit::{RangeAudit, ReindexStats};
 pub use chains::ChainManager;
 pub use parser::{parse_logs, ParseResult, ParsedLog};
 pub use price_resolver::PriceResolver;
+pub use pricing_graph::PricingGraph;
+pub use pricing_strategy::{DefaultPricingStrategy, PricingStrategy};
 pub use token_fetcher::TokenFetcher;
-pub use worker::ChainWorker;
+pub use worker::{hypersync_client, ChainWorker};
@@ -4,13 +4,13 @@
 //! in multiple processing passes.
 
 use alloy::{
-    primitives::{LogData, B256},
+    primitives::{LogData, B256, U256},
     sol_types::SolEvent,
 };
 use rustc_hash::FxHashMap;
 
 use crate::{
-    abis::{erc20, v2, v3, v4},
+    abis::{chainlink, erc20, v2, v3, v4},
     db::models::ChainTokens,
     utils::{hex_encode, ZERO_ADDRESS},
 };
@@ -125,6 +125,15 @@ pub enum ParsedLog {
         tx_hash: String,
         block_timestamp: u64,
     },
+    // Price feed answers (AnswerUpdated / NewTransmission) from configured aggregators
+    OracleAnswer {
+        aggregator: String,
+        /// Positive answer in feed decimals
+        answer: U256,
+        round_id: U256,
+        block_number: u64,
+        block_timestamp: u64,
+    },
     // Supply events
     SupplyTransfer {
         event: erc20::Transfer,
@@ -467,6 +476,21 @@ pub fn parse_logs(
                     }
                 }
             },
+            // Price feeds: only answers from configured aggregators, non-positive answers are ignored
+            t if (*t == chainlink::AnswerUpdated::SIGNATURE_HASH.0
+                || *t == chainlink::NewTransmission::SIGNATURE_HASH.0)
+                && chain_tokens.price_feed(&log_address).is_some() =>
+            {
+                if let Some((answer, round_id)) = decode_oracle_answer(t, &log_data) {
+                    parsed_logs.push(ParsedLog::OracleAnswer {
+                        aggregator: log_address,
+                        answer,
+                        round_id,
+                        block_number,
+                        block_timestamp,
+                    });
+                }
+            },
             _ => {},
         }
     }
@@ -477,3 +501,113 @@ pub fn parse_logs(
         modified_pools_addresses,
     }
 }
+
+/// Decode a Chainlink `AnswerUpdated` or `NewTransmission` log into `(answer, round_id)`.
+///
+/// Returns None for other topics, undecodable data and non-positive answers.
+fn decode_oracle_answer(topic0: &[u8; 32], log_data: &LogData) -> Option<(U256, U256)> {
+    if *topic0 == chainlink::AnswerUpdated::SIGNATURE_HASH.0 {
+        let event = chainlink::AnswerUpdated::decode_log_data(log_data).ok()?;
+        event
+            .current
+            .is_positive()
+            .then(|| (event.current.into_raw(), event.roundId))
+    } else if *topic0 == chainlink::NewTransmission::SIGNATURE_HASH.0 {
+        let event = chainlink::NewTransmission::decode_log_data(log_data).ok()?;
+        event.answer.is_positive().then(|| {
+            (
+                U256::from_limbs_slice(event.answer.into_raw().as_limbs()),
+                U256::from(event.aggregatorRoundId),
+            )
+        })
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloy::primitives::{aliases::I192, Address, Bytes, FixedBytes, I256};
+
+    use super::*;
+    use crate::db::models::PriceFeed;
+
+    fn feed(decimals: u8) -> PriceFeed {
+        PriceFeed {
+            chain_id: 1,
+            aggregator_address: "0xaggregator".to_string(),
+            token_address: "0xtoken".to_string(),
+            decimals,
+            denomination: "usd".to_string(),
+            heartbeat_secs: 3600,
+            description: None,
+        }
+    }
+
+    fn new_transmission(answer: i64) -> chainlink::NewTransmission {
+        chainlink::NewTransmission {
+            aggregatorRoundId: 42,
+            answer: I192::try_from(answer).unwrap(),
+            transmitter: Address::ZERO,
+            observationsTimestamp: 1_700_000_000,
+            observations: vec![],
+            observers: Bytes::new(),
+            juelsPerFeeCoin: I192::ZERO,
+            configDigest: FixedBytes::ZERO,
+            epochAndRound: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_decode_answer_updated() {
+        let event = chainlink::AnswerUpdated {
+            current: I256::try_from(312_345_678_901_i64).unwrap(),
+            roundId: U256::from(7),
+            updatedAt: U256::from(1_700_000_000),
+        };
+        let topic0 = chainlink::AnswerUpdated::SIGNATURE_HASH.0;
+
+        let (answer, round_id) = decode_oracle_answer(&topic0, &event.encode_log_data()).unwrap();
+
+        assert_eq!(answer, U256::from(312_345_678_901_u64));
+        assert_eq!(round_id, U256::from(7));
+        // 8-decimal USD feed
+        assert!((feed(8).answer_to_price(answer) - 3123.45678901).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decode_new_transmission() {
+        let topic0 = chainlink::NewTransmission::SIGNATURE_HASH.0;
+
+        let (answer, round_id) = decode_oracle_answer(
+            &topic0,
+            &new_transmission(1_000_250_000_000_000_000).encode_log_data(),
+        )
+        .unwrap();
+
+        assert_eq!(answer, U256::from(1_000_250_000_000_000_000_u64));
+        assert_eq!(round_id, U256::from(42));
+        // 18-decimal native-denominated feed
+        assert!((feed(18).answer_to_price(answer) - 1.00025).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_non_positive_answers_are_ignored() {
+        let answer_updated = chainlink::AnswerUpdated {
+            current: I256::try_from(-1).unwrap(),
+            roundId: U256::from(1),
+            updatedAt: U256::ZERO,
+        };
+
+        assert!(decode_oracle_answer(
+            &chainlink::AnswerUpdated::SIGNATURE_HASH.0,
+            &answer_updated.encode_log_data()
+        )
+        .is_none());
+        assert!(decode_oracle_answer(
+            &chainlink::NewTransmission::SIGNATURE_HASH.0,
+            &new_transmission(0).encode_log_data()
+        )
+        .is_none());
+    }
+}
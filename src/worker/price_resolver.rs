@@ -1,8 +1,11 @@
-use rustc_hash::FxHashMap;
+use log::debug;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::sync::Arc;
 
+use super::pricing_graph::{PricingGraph, RoutedPrice};
+use super::pricing_strategy::PricingStrategy;
 use crate::{
-    db::models::{ChainTokens, Event, Pool},
+    db::models::{ChainTokens, Event, NativeTokenPrice, Pool, PriceProvenance},
     utils::{
         calculate_reserves_from_liquidity_subgraph, is_suspicious_volume_to_tvl,
         str_to_f64_with_decimals, validate_price_against_volume, validate_price_ratio,
@@ -11,25 +14,21 @@ use crate::{
     },
 };
 
-/// Maximum allowed divergence between sqrtPriceX96-derived price and implied price from swap amounts.
-/// If divergence exceeds this threshold, we use the implied price instead.
-/// This handles V4 pools with custom curve hooks that modify pricing.
-/// 10% = 0.10
-const MAX_PRICE_DIVERGENCE: f64 = 0.10;
-
-/// Minimum TVL for a pool to be considered for pricing/volume (dev safeguard).
-/// Helps drop illiquid pools that can create outsized USD volumes from tiny swaps.
-const MIN_POOL_TVL_USD: f64 = 50_000.0;
-
-const MIN_USD_THRESHOLD: f64 = 10_000.0;
+/// Fresh USD price of a token from a Chainlink feed.
+#[derive(Debug, Clone)]
+pub struct OracleQuote {
+    pub price_usd: f64,
+    pub aggregator_address: String,
+}
 
 /// Price resolution for USD calculations.
 ///
 /// Resolves token prices using a priority-based approach:
 /// 1. Stablecoins → $1.00 (direct)
 /// 2. Native token → native_price_usd (direct)
-/// 3. Major tokens → derive via native/stable pools (1-hop)
-/// 4. Generic tokens → derive via 2-hop path finding
+/// 3. Tokens with a fresh Chainlink answer → oracle price (direct)
+/// 4. Other tokens → route through the chain-wide `PricingGraph` to any of the above,
+///    via the deepest path of whitelisted tokens
 ///
 /// Uses Uniswap's whitelist approach for volume tracking:
 /// - Only tracks USD volume when at least one token is whitelisted
@@ -37,30 +36,89 @@ const MIN_USD_THRESHOLD: f64 = 10_000.0;
 ///
 /// For V4 pools with hooks, validates sqrtPriceX96-derived prices against
 /// implied prices from swap amounts to detect custom curve hooks.
+///
+/// Anchors, the whitelist and all thresholds come from the chain's `PricingStrategy`.
+///
+/// Every USD price comes with a `PriceProvenance` (source pools, hops, weakest
+/// liquidity, sqrtPrice vs implied) and the confidence score derived from it.
 pub struct PriceResolver {
     chain_tokens: Arc<ChainTokens>,
+    strategy: Arc<dyn PricingStrategy>,
     native_price_usd: f64,
+    native_provenance: PriceProvenance,
+    /// Fresh USD prices from Chainlink feeds, authoritative over pool-derived prices
+    oracle_prices: FxHashMap<String, OracleQuote>,
+    /// Strategy anchors (stablecoins, native) plus oracle prices
+    anchors: FxHashMap<String, f64>,
+    /// Prices routed through the pricing graph for the batch's tokens, computed once per batch
+    routed_prices: FxHashMap<String, RoutedPrice>,
     /// In-memory price cache for tokens resolved in this batch
     token_prices: FxHashMap<String, f64>,
 }
 
 impl PriceResolver {
-    pub fn new(chain_tokens: Arc<ChainTokens>, native_price_usd: f64) -> Self {
+    /// Resolver for one batch. Only `tokens` (lowercase, the tokens of the batch's
+    /// pools) are routed through the graph; other non-anchor tokens resolve to 0.0.
+    pub fn new(
+        chain_tokens: Arc<ChainTokens>,
+        strategy: Arc<dyn PricingStrategy>,
+        native_price: &NativeTokenPrice,
+        oracle_prices: FxHashMap<String, OracleQuote>,
+        graph: &PricingGraph,
+        tokens: &FxHashSet<String>,
+    ) -> Self {
+        let native_price_usd = native_price.price_usd;
+        let native_provenance = PriceProvenance::native(
+            native_price.confidence,
+            &native_price.source,
+            &native_price.source_pools,
+        );
+
+        // Anchors: every token with a USD price known without routing
+        let mut anchors = strategy.anchor_prices(&chain_tokens, native_price_usd);
+        for (token, quote) in &oracle_prices {
+            anchors.entry(token.clone()).or_insert(quote.price_usd);
+        }
+
+        let routed_prices = graph.route_prices(
+            tokens.iter().map(String::as_str),
+            &anchors,
+            native_price_usd,
+            strategy.thresholds().min_liquidity_usd,
+            |token| {
+                strategy.is_whitelisted(&chain_tokens, token) || oracle_prices.contains_key(token)
+            },
+        );
+
         Self {
             chain_tokens,
+            strategy,
             native_price_usd,
+            native_provenance,
+            oracle_prices,
+            anchors,
+            routed_prices,
             token_prices: FxHashMap::default(),
         }
     }
 
-    /// Check if a token is on the whitelist (stablecoin, wrapped native, or major token).
+    /// Check if a token is on the strategy's whitelist.
     ///
     /// Uniswap subgraph only tracks USD values for pools with whitelisted tokens.
     /// This prevents garbage prices from scam tokens from polluting volume metrics.
     fn is_whitelisted(&self, token: &str) -> bool {
-        self.chain_tokens.is_stable(token)
-            || self.chain_tokens.is_wrapped_native(token)
-            || self.chain_tokens.is_major_token(token)
+        self.strategy.is_whitelisted(&self.chain_tokens, token)
+    }
+
+    /// USD price of a whitelisted token, 0.0 for any other token.
+    ///
+    /// Used for the quote side of a pool so prices are never derived through scam tokens.
+    fn whitelisted_token_price_usd(&mut self, token: &str) -> f64 {
+        if self.is_whitelisted(token) {
+            self.get_token_price_usd(token)
+        } else {
+            0.0
+        }
     }
 
     /// Check if pool has sufficient native token liquidity for trusted pricing.
@@ -76,35 +134,38 @@ impl PriceResolver {
     /// Check if pool has sufficient trusted token liquidity for pricing.
     ///
     /// Returns true if:
-    /// - Pool contains a Whitelisted (Trusted) token with >= MIN_USD_THRESHOLD worth of Active Liquidity AND Real Reserves.
+    /// - Pool contains a Whitelisted (Trusted) token with >= `min_liquidity_usd` worth of Active Liquidity AND Real Reserves.
     ///
     /// This validates the "Trusted Side" liquidity.
     /// - For V2: Uses Reserve Balance * Price.
     /// - For V3/V4: Uses Active Virtual Reserves (from L) * Price AND Real Accumulated Reserves.
-    fn check_pool_liquidity(&mut self, pool: &Pool, pools: &FxHashMap<String, Pool>) -> bool {
+    fn check_pool_liquidity(&mut self, pool: &Pool) -> bool {
         // Determine trusted token (Quote token priority)
-        // We select the token with the highest priority (Stable > Native > Major)
+        // We select the token with the highest priority (Stable > Native > Whitelisted)
         // to be the "Cash" side for liquidity valuation.
 
-        let (trusted_token, trusted_price) = if self.chain_tokens.is_stable(&pool.token0) {
-            (&pool.token0, 1.0)
+        let trusted_token = if self.chain_tokens.is_stable(&pool.token0) {
+            Some(&pool.token0)
         } else if self.chain_tokens.is_stable(&pool.token1) {
-            (&pool.token1, 1.0)
+            Some(&pool.token1)
         } else if self.chain_tokens.is_wrapped_native(&pool.token0) {
-            (&pool.token0, self.native_price_usd)
+            Some(&pool.token0)
         } else if self.chain_tokens.is_wrapped_native(&pool.token1) {
-            (&pool.token1, self.native_price_usd)
-        } else if self.chain_tokens.is_major_token(&pool.token0) {
-            let p = self.get_token_price_usd(&pool.token0, pools);
-            (&pool.token0, p)
-        } else if self.chain_tokens.is_major_token(&pool.token1) {
-            let p = self.get_token_price_usd(&pool.token1, pools);
-            (&pool.token1, p)
+            Some(&pool.token1)
+        } else if self.is_whitelisted(&pool.token0) {
+            Some(&pool.token0)
+        } else if self.is_whitelisted(&pool.token1) {
+            Some(&pool.token1)
         } else {
-            // Pool doesn't contain ANY whitelisted token.
-            // We cannot trust the liquidity value if we can't price either side.
+            None
+        };
+
+        // Pool doesn't contain ANY whitelisted token.
+        // We cannot trust the liquidity value if we can't price either side.
+        let Some(trusted_token) = trusted_token else {
             return false;
         };
+        let trusted_price = self.get_token_price_usd(trusted_token);
 
         // If we found a trusted token but its price is 0 (unresolved), we can't use it.
         if trusted_price <= 0.0 {
@@ -114,7 +175,8 @@ impl PriceResolver {
         // 1. Calculate Active Liquidity Value of the trusted side (Virtual / Depth)
         let liquidity_value = self.get_active_liquidity_value(pool, trusted_token, trusted_price);
 
-        if liquidity_value < MIN_USD_THRESHOLD {
+        let min_liquidity_usd = self.strategy.thresholds().min_liquidity_usd;
+        if liquidity_value < min_liquidity_usd {
             return false;
         }
 
@@ -123,15 +185,15 @@ impl PriceResolver {
         if pool.protocol_version.as_deref() != Some("v2") {
             let is_token0 = pool.token0.to_lowercase() == trusted_token.to_lowercase();
             let real_reserve = if is_token0 {
-                pool.reserve0_adjusted.unwrap_or(0.0)
+                pool.reserve0_f64().unwrap_or(0.0)
             } else {
-                pool.reserve1_adjusted.unwrap_or(0.0)
+                pool.reserve1_f64().unwrap_or(0.0)
             };
 
             let real_value = real_reserve * trusted_price;
 
             // If we have tracked reserves (value > 0), ensure they also meet a threshold.
-            if real_value > 0.0 && real_value < MIN_USD_THRESHOLD {
+            if real_value > 0.0 && real_value < min_liquidity_usd {
                 return false;
             }
         }
@@ -181,7 +243,7 @@ impl PriceResolver {
 
         // Logic for V2 pools (or V3 without L?? should not happen if V3 initialized)
         // Fallback to Reserves if L is missing (V2 logic)
-        if let (Some(r0), Some(r1)) = (pool.reserve0_adjusted, pool.reserve1_adjusted) {
+        if let (Some(r0), Some(r1)) = (pool.reserve0_f64(), pool.reserve1_f64()) {
             let balance = if is_token0 { r0 } else { r1 };
             return balance * token_price_usd;
         }
@@ -196,8 +258,8 @@ impl PriceResolver {
     /// This reflects the actual execution price of the swap, which may differ
     /// from sqrtPriceX96-derived price if a V4 hook modifies the swap.
     fn calculate_implied_price(&self, event: &Event) -> Option<f64> {
-        let amount0 = event.amount0_adjusted.abs();
-        let amount1 = event.amount1_adjusted.abs();
+        let amount0 = event.amount0_f64();
+        let amount1 = event.amount1_f64();
 
         if amount0 > 1e-18 && amount1 > 0.0 {
             let implied = amount1 / amount0;
@@ -223,12 +285,11 @@ impl PriceResolver {
     ///
     /// Resolution priority:
     /// 1. Cache hit → return cached price
-    /// 2. Stablecoin → return 1.0
-    /// 3. Native token → return native_price_usd
-    /// 4. Major/Generic → find path via pools
+    /// 2. Anchor (strategy anchors such as stablecoins and native, then fresh oracle answers)
+    /// 3. Major/Generic → route through the pricing graph
     ///
     /// Returns 0.0 if no price path can be found.
-    pub fn get_token_price_usd(&mut self, token: &str, pools: &FxHashMap<String, Pool>) -> f64 {
+    pub fn get_token_price_usd(&mut self, token: &str) -> f64 {
         let token_lower = token.to_lowercase();
 
         // 1. Check cache
@@ -236,141 +297,63 @@ impl PriceResolver {
             return price;
         }
 
-        // 2. Stablecoin = $1.00
-        if self.chain_tokens.is_stable(&token_lower) {
-            self.token_prices.insert(token_lower, 1.0);
-            return 1.0;
-        }
-
-        // 3. Native token = native_price_usd
-        if self.chain_tokens.is_wrapped_native(&token_lower) {
-            self.token_prices.insert(token_lower, self.native_price_usd);
-            return self.native_price_usd;
+        // 2. Anchor price (stablecoins, native token, fresh Chainlink answers)
+        if let Some(&price) = self.anchors.get(&token_lower) {
+            self.token_prices.insert(token_lower, price);
+            return price;
         }
 
-        // 4. Try to find price via pools (major tokens and generic tokens)
-        let price = self.derive_token_price(&token_lower, pools, 0);
+        // 3. Route through the pricing graph (major tokens and generic tokens)
+        let price = self.derive_token_price(&token_lower);
         self.token_prices.insert(token_lower, price);
         price
     }
 
-    /// Derive token price by finding a path through pools.
+    /// Derive token price from the route computed through the pricing graph.
     ///
-    /// Attempts to find a pool where:
-    /// - The token is paired with a stablecoin → price directly
-    /// - The token is paired with native → price via native_price_usd
-    /// - The token is paired with another token → recursive lookup (limited depth)
-    ///
-    /// Uses the new Uniswap-style token0_price/token1_price fields.
-    /// `depth` parameter prevents infinite recursion (max 2 hops).
-    /// Derive token price by finding a path through pools.
-    ///
-    /// Attempts to find a pool where:
-    /// - The token is paired with a stablecoin → price directly
-    /// - The token is paired with native → price via native_price_usd
-    /// - The token is paired with another token → recursive lookup (limited depth)
-    ///
-    /// Uses the new Uniswap-style token0_price/token1_price fields.
-    /// `depth` parameter prevents infinite recursion (max 2 hops).
-    ///
-    /// IMPROVEMENT: Now iterates ALL candidate pools and picks the one with the
-    /// highest liquidity (value on the paired/known side) to avoid "fake" or
-    /// illiquid pools from setting bad prices.
-    fn derive_token_price(
-        &mut self,
-        token: &str,
-        pools: &FxHashMap<String, Pool>,
-        depth: u8,
-    ) -> f64 {
-        // Prevent infinite recursion - max 2 hops
-        if depth > 1 {
-            return 0.0;
-        }
-
-        let mut best_price = 0.0;
-        let mut max_liquidity_value = 0.0;
-
-        // Search through pools for one containing this token
-        for pool in pools.values() {
-            // Use the new token0_price/token1_price fields (Uniswap style)
-            // token0_price = how much token0 per 1 token1
-            // token1_price = how much token1 per 1 token0
-            let (paired_token, token_price_in_paired, _) = if pool.token0 == token {
-                // Token is token0, paired with token1
-                // We want: how much token1 per 1 token0 = token1_price
-                (&pool.token1, pool.token1_price, true)
-            } else if pool.token1 == token {
-                // Token is token1, paired with token0
-                // We want: how much token0 per 1 token1 = token0_price
-                (&pool.token0, pool.token0_price, false)
-            } else {
-                continue;
-            };
-
-            // Validate the price ratio from the pool
-            let Some(price_in_paired) = token_price_in_paired.and_then(validate_price_ratio) else {
-                continue;
-            };
+    /// Unlike searching only the pools of the current batch, the graph contains every
+    /// priced pool on the chain, so deep pools that did not trade recently still count.
+    fn derive_token_price(&self, token: &str) -> f64 {
+        self.routed_prices
+            .get(token)
+            .map(|route| route.price_usd)
+            .unwrap_or(0.0)
+    }
 
-            // Try to get paired token's USD price - ONLY for whitelisted tokens
-            // This prevents deriving prices through garbage pools
-            let paired_price_usd = if self.chain_tokens.is_stable(&paired_token) {
-                1.0
-            } else if self.chain_tokens.is_wrapped_native(&paired_token) {
-                self.native_price_usd
-            } else if self.chain_tokens.is_major_token(&paired_token) && depth == 0 {
-                // Only allow 1 more hop for major tokens (they have reliable pools)
-                self.derive_token_price(&paired_token, pools, depth + 1)
-            } else {
-                // Paired token is not whitelisted - skip this pool
-                continue;
-            };
+    /// Provenance of the USD price returned by `get_token_price_usd`.
+    ///
+    /// Returns None for tokens without a price.
+    pub fn token_provenance(&self, token: &str) -> Option<PriceProvenance> {
+        let token_lower = token.to_lowercase();
 
-            if paired_price_usd > 0.0 {
-                // price_in_paired = how much paired_token per 1 token
-                // token_usd = price_in_paired * paired_price_usd
-                let token_usd = price_in_paired * paired_price_usd;
-                // Validate with absolute bounds first
-                let bounded = validate_usd_price(token_usd);
-                // Then validate relative to native price (catches inversion errors)
-                let validated = validate_usd_price_relative(bounded, self.native_price_usd);
-
-                if validated > 0.0 {
-                    // Calculate "Liquidity Value" of the paired side to weight this price
-                    // We trust the paired side (Quote) value, so AmountQuote * PriceQuote = LiquidityValue
-
-                    // Use the unified active liquidity value calculator
-                    let weight =
-                        self.get_active_liquidity_value(pool, &paired_token, paired_price_usd);
-
-                    // If this pool has more liquidity (value) than previous best, use it
-                    // Filter out low-liquidity pools
-                    if weight > max_liquidity_value && weight >= MIN_USD_THRESHOLD {
-                        // Additional check: Real Reserves for V3/V4
-                        let passes_real_check = if pool.protocol_version.as_deref() != Some("v2") {
-                            let is_token0 = pool.token0 == *paired_token;
-                            let real_reserve = if is_token0 {
-                                pool.reserve0_adjusted.unwrap_or(0.0)
-                            } else {
-                                pool.reserve1_adjusted.unwrap_or(0.0)
-                            };
-                            let real_value = real_reserve * paired_price_usd;
-                            // If we have reserve data, enforce threshold on Real Capital too
-                            real_value <= 0.0 || real_value >= MIN_USD_THRESHOLD
-                        } else {
-                            true
-                        };
+        if self.chain_tokens.is_stable(&token_lower) {
+            return Some(PriceProvenance::stable());
+        }
+        if self.chain_tokens.is_wrapped_native(&token_lower) {
+            return Some(self.native_provenance.clone());
+        }
+        if let Some(quote) = self.oracle_prices.get(&token_lower) {
+            return Some(PriceProvenance::oracle(&quote.aggregator_address));
+        }
 
-                        if passes_real_check {
-                            max_liquidity_value = weight;
-                            best_price = validated;
-                        }
-                    }
-                }
-            }
+        let route = self.routed_prices.get(&token_lower)?;
+        if route.hops() == 0 {
+            return None;
         }
+        let anchor = self.token_provenance(&route.anchor)?;
+        Some(route.path.iter().fold(anchor, |provenance, hop| {
+            provenance.through_pool(&hop.pool_address, hop.liquidity_usd, hop.method)
+        }))
+    }
+
+    /// Provenance of a base token price derived from `pool`'s own exchange rate.
+    fn pool_provenance(&self, pool: &Pool, quote_token_usd: f64) -> Option<PriceProvenance> {
+        let quote = self.token_provenance(&pool.quote_token)?;
+        let liquidity = self.get_active_liquidity_value(pool, &pool.quote_token, quote_token_usd);
+        let method =
+            if pool.protocol_version.as_deref() == Some("v2") { "reserves" } else { "sqrt_price" };
 
-        best_price
+        Some(quote.through_pool(&pool.address, liquidity, method))
     }
 
     /// Calculate price_usd and volume_usd for a swap event.
@@ -385,16 +368,11 @@ impl PriceResolver {
     /// implied price instead (handles custom curve hooks).
     ///
     /// This prevents scam tokens from generating fake volume.
-    pub fn price_swap_event(
-        &mut self,
-        event: &mut Event,
-        pool: &Pool,
-        pools: &FxHashMap<String, Pool>,
-    ) {
+    pub fn price_swap_event(&mut self, event: &mut Event, pool: &Pool) {
         // Drop illiquid pools (when we have a TVL reading)
         if let Some(tvl_raw) = pool.tvl_usd {
             let tvl = validate_usd_tvl(tvl_raw);
-            if tvl <= 0.0 || tvl < MIN_POOL_TVL_USD {
+            if tvl <= 0.0 || tvl < self.strategy.thresholds().min_pool_tvl_usd {
                 event.price_usd = 0.0;
                 event.volume_usd = 0.0;
                 event.is_suspicious = true;
@@ -404,9 +382,9 @@ impl PriceResolver {
             // If TVL is missing (common for new pools in same batch), try to estimate it
             // from native token liquidity if available. This catches V2 pools.
             let native_amount = if self.chain_tokens.is_wrapped_native(&pool.token0) {
-                pool.reserve0_adjusted.unwrap_or(0.0)
+                pool.reserve0_f64().unwrap_or(0.0)
             } else if self.chain_tokens.is_wrapped_native(&pool.token1) {
-                pool.reserve1_adjusted.unwrap_or(0.0)
+                pool.reserve1_f64().unwrap_or(0.0)
             } else {
                 0.0
             };
@@ -420,21 +398,16 @@ impl PriceResolver {
                     // Check if volume is suspicious relative to this estimated TVL
                     // We calculate approximate volume_usd here just for the check
                     // actual volume_usd calculation happens later
-                    let quote_token_usd = if self.chain_tokens.is_stable(&pool.quote_token) {
-                        1.0
-                    } else if self.chain_tokens.is_wrapped_native(&pool.quote_token) {
-                        self.native_price_usd
-                    } else {
-                        0.0
-                    };
+                    let quote_token_usd = self
+                        .anchors
+                        .get(&pool.quote_token.to_lowercase())
+                        .copied()
+                        .unwrap_or(0.0);
 
                     if quote_token_usd > 0.0 {
                         let base_is_token0 = pool.base_token == pool.token0;
-                        let quote_amount = if base_is_token0 {
-                            event.amount1_adjusted.abs()
-                        } else {
-                            event.amount0_adjusted.abs()
-                        };
+                        let quote_amount =
+                            if base_is_token0 { event.amount1_f64() } else { event.amount0_f64() };
 
                         let approx_volume_usd = quote_amount * quote_token_usd;
 
@@ -452,7 +425,7 @@ impl PriceResolver {
         // Check minimum native token liquidity (chain-agnostic $10000 threshold)
         // Check minimum active liquidity for trusted tokens (Native OR Stable)
         // This prevents pools with high TVL but low Active Liquidity (V3 spoofing) from setting prices
-        if !self.check_pool_liquidity(pool, pools) {
+        if !self.check_pool_liquidity(pool) {
             event.price_usd = 0.0;
             event.volume_usd = 0.0;
             event.is_suspicious = true;
@@ -473,17 +446,7 @@ impl PriceResolver {
 
         // Get quote token's USD price - ONLY for whitelisted tokens
         // This prevents garbage prices from being derived through scam token pools
-        let quote_token_usd = if self.chain_tokens.is_stable(&pool.quote_token) {
-            1.0
-        } else if self.chain_tokens.is_wrapped_native(&pool.quote_token) {
-            self.native_price_usd
-        } else if self.chain_tokens.is_major_token(&pool.quote_token) {
-            // Only derive price for major tokens (they have reliable pools)
-            self.get_token_price_usd(&pool.quote_token, pools)
-        } else {
-            // Quote token is not whitelisted - can't reliably price this pool
-            0.0
-        };
+        let quote_token_usd = self.whitelisted_token_price_usd(&pool.quote_token);
 
         // If we can't price the quote token, we can't calculate values
         if quote_token_usd <= 0.0 || !quote_token_usd.is_finite() {
@@ -511,38 +474,40 @@ impl PriceResolver {
         let pool_price_token1_per_token0 = pool.price.and_then(validate_price_ratio);
 
         // Divergence-aware selection: apply to all pools (not just V4 hooks)
-        let final_rate = match (pool_based_rate, implied_price, pool_price_token1_per_token0) {
-            // Have both pool price and implied price: use implied if divergence is large
-            (Some(pool_rate), Some(implied), Some(pool_price_raw)) => {
-                let divergence = self.calculate_divergence(implied, pool_price_raw);
-                if divergence > MAX_PRICE_DIVERGENCE {
-                    // Convert implied (token1/token0) to the needed direction
+        // The flag records whether the implied price was used, for provenance
+        let (final_rate, mut used_implied) =
+            match (pool_based_rate, implied_price, pool_price_token1_per_token0) {
+                // Have both pool price and implied price: use implied if divergence is large
+                (Some(pool_rate), Some(implied), Some(pool_price_raw)) => {
+                    let divergence = self.calculate_divergence(implied, pool_price_raw);
+                    if divergence > self.strategy.thresholds().max_price_divergence {
+                        // Convert implied (token1/token0) to the needed direction
+                        if base_is_token0 {
+                            (Some(implied), true)
+                        } else if implied > 0.0 {
+                            (validate_price_ratio(1.0 / implied), true)
+                        } else {
+                            (Some(pool_rate), false)
+                        }
+                    } else {
+                        (Some(pool_rate), false)
+                    }
+                },
+                // Have implied but no validated pool price: use implied
+                (None, Some(implied), _) => {
                     if base_is_token0 {
-                        Some(implied)
+                        (validate_price_ratio(implied), true)
                     } else if implied > 0.0 {
-                        validate_price_ratio(1.0 / implied)
+                        (validate_price_ratio(1.0 / implied), true)
                     } else {
-                        Some(pool_rate)
+                        (None, false)
                     }
-                } else {
-                    Some(pool_rate)
-                }
-            },
-            // Have implied but no validated pool price: use implied
-            (None, Some(implied), _) => {
-                if base_is_token0 {
-                    validate_price_ratio(implied)
-                } else if implied > 0.0 {
-                    validate_price_ratio(1.0 / implied)
-                } else {
-                    None
-                }
-            },
-            // No implied, but have pool-based rate
-            (Some(pool_rate), _, _) => Some(pool_rate),
-            // Nothing valid
-            _ => None,
-        };
+                },
+                // No implied, but have pool-based rate
+                (Some(pool_rate), _, _) => (Some(pool_rate), false),
+                // Nothing valid
+                _ => (None, false),
+            };
 
         // Calculate base token USD price with multi-layer validation
         let raw_base_token_usd = final_rate.map(|r| r * quote_token_usd).unwrap_or(0.0);
@@ -568,19 +533,13 @@ impl PriceResolver {
         // The implied price from swap amounts should roughly match our calculated price
         if self.calculate_implied_price(event).is_some() {
             // Get quote amount USD value for comparison
-            let quote_amount = if base_is_token0 {
-                event.amount1_adjusted.abs()
-            } else {
-                event.amount0_adjusted.abs()
-            };
+            let quote_amount =
+                if base_is_token0 { event.amount1_f64() } else { event.amount0_f64() };
             let quote_value_usd = quote_amount * quote_token_usd;
 
             // Get base amount
-            let base_amount = if base_is_token0 {
-                event.amount0_adjusted.abs()
-            } else {
-                event.amount1_adjusted.abs()
-            };
+            let base_amount =
+                if base_is_token0 { event.amount0_f64() } else { event.amount1_f64() };
 
             // Check: base_amount * base_token_usd should ≈ quote_value_usd
             if base_amount > 1e-10 && quote_value_usd > 1e-10 {
@@ -592,6 +551,7 @@ impl PriceResolver {
                     // Use the implied price instead (more reliable for actual swap execution)
                     event.price_usd =
                         validate_usd_price_relative(implied_base_usd, self.native_price_usd);
+                    used_implied = true;
                     if event.price_usd <= 0.0 {
                         event.volume_usd = 0.0;
                         return;
@@ -611,16 +571,8 @@ impl PriceResolver {
         let final_base_usd = event.price_usd;
 
         // Plausibility check: base side vs quote side volume
-        let quote_amount = if base_is_token0 {
-            event.amount1_adjusted.abs()
-        } else {
-            event.amount0_adjusted.abs()
-        };
-        let base_amount = if base_is_token0 {
-            event.amount0_adjusted.abs()
-        } else {
-            event.amount1_adjusted.abs()
-        };
+        let quote_amount = if base_is_token0 { event.amount1_f64() } else { event.amount0_f64() };
+        let base_amount = if base_is_token0 { event.amount0_f64() } else { event.amount1_f64() };
         let quote_value_usd = quote_amount * quote_token_usd;
         if !validate_price_against_volume(final_base_usd, base_amount, quote_value_usd, 0.5) {
             // If the implied USD values disagree by >50%, drop the volume to avoid spikes
@@ -629,6 +581,11 @@ impl PriceResolver {
             return;
         }
 
+        if let Some(provenance) = self.pool_provenance(pool, quote_token_usd) {
+            let provenance = if used_implied { provenance.implied() } else { provenance };
+            event.set_price_provenance(&provenance);
+        }
+
         // Get token0 and token1 USD prices
         let (token0_usd, token1_usd) = if base_is_token0 {
             (final_base_usd, quote_token_usd)
@@ -641,16 +598,16 @@ impl PriceResolver {
         // - One whitelisted: double that token's USD value
         let volume = if token0_whitelisted && token1_whitelisted {
             // Both tokens whitelisted - sum both sides (standard calculation)
-            let amount0_usd = event.amount0_adjusted.abs() * token0_usd;
-            let amount1_usd = event.amount1_adjusted.abs() * token1_usd;
+            let amount0_usd = event.amount0_f64() * token0_usd;
+            let amount1_usd = event.amount1_f64() * token1_usd;
             // Use max to avoid double counting (Uniswap style)
             amount0_usd.max(amount1_usd)
         } else if token0_whitelisted {
             // Only token0 is whitelisted - double its value
-            event.amount0_adjusted.abs() * token0_usd * 2.0
+            event.amount0_f64() * token0_usd * 2.0
         } else {
             // Only token1 is whitelisted - double its value
-            event.amount1_adjusted.abs() * token1_usd * 2.0
+            event.amount1_f64() * token1_usd * 2.0
         };
 
         // Validate volume with bounds
@@ -669,6 +626,7 @@ impl PriceResolver {
         if let Some(tvl) = pool.tvl_usd {
             if is_suspicious_volume_to_tvl(event.volume_usd, tvl) {
                 event.is_suspicious = true;
+                event.price_confidence = 0.0;
             }
         }
     }
@@ -678,12 +636,7 @@ impl PriceResolver {
     /// Liquidity events do NOT generate trading volume.
     /// - price_usd = base token's USD price derived from pool's exchange rate
     /// - volume_usd = 0 (liquidity events are not trades)
-    pub fn price_liquidity_event(
-        &mut self,
-        event: &mut Event,
-        pool: &Pool,
-        pools: &FxHashMap<String, Pool>,
-    ) {
+    pub fn price_liquidity_event(&mut self, event: &mut Event, pool: &Pool) {
         // Liquidity events don't generate trading volume
         event.volume_usd = 0.0;
 
@@ -694,7 +647,13 @@ impl PriceResolver {
         }
 
         // price_usd = base token's USD price derived from pool's exchange rate
-        event.price_usd = self.derive_base_token_usd(pool, pools);
+        event.price_usd = self.derive_base_token_usd(pool);
+        if event.price_usd > 0.0 {
+            let quote_token_usd = self.get_token_price_usd(&pool.quote_token);
+            if let Some(provenance) = self.pool_provenance(pool, quote_token_usd) {
+                event.set_price_provenance(&provenance);
+            }
+        }
     }
 
     /// Derive base token's USD price from a pool's own exchange rate.
@@ -707,19 +666,9 @@ impl PriceResolver {
     /// This uses the pool's quote token (stablecoin/native/major) as reference
     /// and the pool's exchange rate to calculate the base token's USD value.
     /// Only works for pools where quote token is whitelisted.
-    fn derive_base_token_usd(&mut self, pool: &Pool, pools: &FxHashMap<String, Pool>) -> f64 {
+    fn derive_base_token_usd(&mut self, pool: &Pool) -> f64 {
         // Get quote token's USD price - ONLY for whitelisted tokens
-        let quote_token_usd = if self.chain_tokens.is_stable(&pool.quote_token) {
-            1.0
-        } else if self.chain_tokens.is_wrapped_native(&pool.quote_token) {
-            self.native_price_usd
-        } else if self.chain_tokens.is_major_token(&pool.quote_token) {
-            // Only derive price for major tokens (they have reliable pools)
-            self.get_token_price_usd(&pool.quote_token, pools)
-        } else {
-            // Quote token is not whitelisted - can't reliably price
-            0.0
-        };
+        let quote_token_usd = self.whitelisted_token_price_usd(&pool.quote_token);
 
         if quote_token_usd <= 0.0 {
             return 0.0;
@@ -748,11 +697,11 @@ impl PriceResolver {
     }
 
     /// Price an event based on its type.
-    pub fn price_event(&mut self, event: &mut Event, pool: &Pool, pools: &FxHashMap<String, Pool>) {
+    pub fn price_event(&mut self, event: &mut Event, pool: &Pool) {
         match event.event_type.as_str() {
-            "swap" => self.price_swap_event(event, pool, pools),
+            "swap" => self.price_swap_event(event, pool),
             "mint" | "burn" | "collect" | "modify_liquidity" => {
-                self.price_liquidity_event(event, pool, pools)
+                self.price_liquidity_event(event, pool)
             },
             _ => {},
         }
@@ -764,12 +713,11 @@ impl PriceResolver {
     /// - Only calculates USD values if at least one token is whitelisted
     /// - This prevents garbage values from scam token pools
     ///
-    /// Returns (price_usd, tvl_usd)
+    /// Returns (price_usd, tvl_usd, provenance of price_usd)
     pub fn calculate_pool_pricing(
         &mut self,
         pool: &Pool,
-        pools: &FxHashMap<String, Pool>,
-    ) -> (Option<f64>, Option<f64>) {
+    ) -> (Option<f64>, Option<f64>, Option<PriceProvenance>) {
         let mut price_usd = None;
         let mut tvl_usd = None;
 
@@ -779,27 +727,17 @@ impl PriceResolver {
 
         if !token0_whitelisted && !token1_whitelisted {
             // Neither token is whitelisted - don't set USD values
-            return (None, None);
+            return (None, None, None);
         }
 
         // Check Liquidity Depth first!
         // This prevents low-liquidity pools from setting the canonical price.
-        if !self.check_pool_liquidity(pool, pools) {
-            return (None, None);
+        if !self.check_pool_liquidity(pool) {
+            return (None, None, None);
         }
 
         // Get quote token's USD price - ONLY for whitelisted tokens
-        let quote_token_usd = if self.chain_tokens.is_stable(&pool.quote_token) {
-            1.0
-        } else if self.chain_tokens.is_wrapped_native(&pool.quote_token) {
-            self.native_price_usd
-        } else if self.chain_tokens.is_major_token(&pool.quote_token) {
-            // Only derive price for major tokens (they have reliable pools)
-            self.get_token_price_usd(&pool.quote_token, pools)
-        } else {
-            // Quote token is not whitelisted - can't reliably price
-            0.0
-        };
+        let quote_token_usd = self.whitelisted_token_price_usd(&pool.quote_token);
 
         // Calculate base token's USD price from THIS pool's exchange rate
         if quote_token_usd > 0.0 {
@@ -827,13 +765,13 @@ impl PriceResolver {
         // Calculate tvl_usd (total value locked)
         // Only use whitelisted token prices for TVL calculation
         let token0_usd =
-            if token0_whitelisted { self.get_token_price_usd(&pool.token0, pools) } else { 0.0 };
+            if token0_whitelisted { self.get_token_price_usd(&pool.token0) } else { 0.0 };
         let token1_usd =
-            if token1_whitelisted { self.get_token_price_usd(&pool.token1, pools) } else { 0.0 };
+            if token1_whitelisted { self.get_token_price_usd(&pool.token1) } else { 0.0 };
 
         // For V2: use reserves
         // For V3/V4: use virtual reserves from liquidity + sqrtPriceX96
-        if let (Some(r0), Some(r1)) = (pool.reserve0_adjusted, pool.reserve1_adjusted) {
+        if let (Some(r0), Some(r1)) = (pool.reserve0_f64(), pool.reserve1_f64()) {
             // Check protocol version
             let is_v2 = pool.protocol_version.as_deref() == Some("v2");
 
@@ -908,7 +846,9 @@ impl PriceResolver {
             }
         }
 
-        (price_usd, tvl_usd)
+        let provenance = price_usd.and_then(|_| self.pool_provenance(pool, quote_token_usd));
+
+        (price_usd, tvl_usd, provenance)
     }
 
     /// Calculate TVL-weighted average price for a token across all pools
@@ -917,14 +857,21 @@ impl PriceResolver {
     /// Formula: SUM(pool.price_usd * pool.tvl_usd) / SUM(pool.tvl_usd)
     ///
     /// Returns None if no valid pools are found (avoids division by zero).
+    /// The provenance of an average is that of its deepest pool.
+    ///
+    /// Tokens with a fresh oracle answer always return the oracle price; the
+    /// TVL-weighted pool price is only used to report divergence. Otherwise the
+    /// price routed through the pricing graph is used, since it also sees deep
+    /// pools outside this batch; the batch average is the fallback when no route exists.
     pub fn calculate_token_price(
         &self,
         token_address: &str,
         pools: &FxHashMap<String, Pool>,
-    ) -> Option<f64> {
+    ) -> Option<(f64, PriceProvenance)> {
         let token_lower = token_address.to_lowercase();
         let mut weighted_sum = 0.0;
         let mut total_tvl = 0.0;
+        let mut deepest: Option<(f64, &PriceProvenance)> = None;
 
         for pool in pools.values() {
             // Only include pools where this token is the base token
@@ -950,10 +897,46 @@ impl PriceResolver {
                 continue;
             }
 
+            // Every pool priced in this batch has a provenance
+            let Some(provenance) = &pool.price_provenance else {
+                continue;
+            };
+            if deepest.is_none_or(|(deepest_tvl, _)| tvl_usd > deepest_tvl) {
+                deepest = Some((tvl_usd, provenance));
+            }
+
             weighted_sum += price_usd * tvl_usd;
             total_tvl += tvl_usd;
         }
 
+        if let Some(quote) = self.oracle_prices.get(&token_lower) {
+            let oracle_price = quote.price_usd;
+            if total_tvl > 0.0 {
+                let dex_price = weighted_sum / total_tvl;
+                let divergence = (dex_price / oracle_price - 1.0).abs();
+                if divergence > self.strategy.thresholds().max_price_divergence {
+                    debug!(
+                        "Token {}: DEX price ${:.6} diverges {:.1}% from oracle ${:.6}",
+                        token_lower,
+                        dex_price,
+                        divergence * 100.0,
+                        oracle_price
+                    );
+                }
+            }
+            return Some((
+                oracle_price,
+                PriceProvenance::oracle(&quote.aggregator_address),
+            ));
+        }
+
+        if let Some(route) = self.routed_prices.get(&token_lower) {
+            if let Some(provenance) = self.token_provenance(&token_lower) {
+                return Some((route.price_usd, provenance));
+            }
+        }
+
+        let (_, provenance) = deepest?;
         if total_tvl <= 0.0 {
             return None;
         }
@@ -968,7 +951,7 @@ impl PriceResolver {
 
         let rel_validated = validate_usd_price_relative(validated, self.native_price_usd);
         if rel_validated > 0.0 {
-            Some(rel_validated)
+            Some((rel_validated, provenance.clone()))
         } else {
             None
         }
@@ -0,0 +1,691 @@
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    db::models::Pool,
+    utils::{
+        calculate_reserves_from_liquidity_subgraph, str_to_f64_with_decimals, validate_price_ratio,
+        validate_usd_price, validate_usd_price_relative,
+    },
+};
+
+/// A pool as seen by the pricing graph: the two tokens, their exchange rates and
+/// the balances used to value each side.
+#[derive(Debug, Clone)]
+struct PoolEdge {
+    token0: String,
+    token1: String,
+    /// How much token0 per 1 token1
+    token0_price: f64,
+    /// How much token1 per 1 token0
+    token1_price: f64,
+    /// Active balances (virtual reserves for V3/V4, reserves for V2), decimal-adjusted
+    active0: f64,
+    active1: f64,
+    /// Accumulated real balances for V3/V4, used to reject spoofed concentrated liquidity
+    real0: f64,
+    real1: f64,
+    is_v2: bool,
+}
+
+impl PoolEdge {
+    fn from_pool(pool: &Pool) -> Option<Self> {
+        let token0_price = pool.token0_price.and_then(validate_price_ratio)?;
+        let token1_price = pool.token1_price.and_then(validate_price_ratio)?;
+        let (active0, active1) = active_balances(pool)?;
+
+        Some(Self {
+            token0: pool.token0.to_lowercase(),
+            token1: pool.token1.to_lowercase(),
+            token0_price,
+            token1_price,
+            active0,
+            active1,
+            real0: pool.reserve0_f64().unwrap_or(0.0),
+            real1: pool.reserve1_f64().unwrap_or(0.0),
+            is_v2: pool.protocol_version.as_deref() == Some("v2"),
+        })
+    }
+
+    /// How the exchange rate of this pool is derived.
+    fn method(&self) -> &'static str {
+        if self.is_v2 {
+            "reserves"
+        } else {
+            "sqrt_price"
+        }
+    }
+
+    /// Given the USD price of `known`, return the other token, its USD price and the
+    /// USD liquidity on the known side. Returns None if the known side holds less than
+    /// `min_liquidity_usd`.
+    fn traverse(
+        &self,
+        known: &str,
+        known_price_usd: f64,
+        min_liquidity_usd: f64,
+    ) -> Option<(&str, f64, f64)> {
+        let (other, other_in_known, active, real) = if self.token0 == known {
+            (&self.token1, self.token0_price, self.active0, self.real0)
+        } else if self.token1 == known {
+            (&self.token0, self.token1_price, self.active1, self.real1)
+        } else {
+            return None;
+        };
+
+        let liquidity = active * known_price_usd;
+        if liquidity < min_liquidity_usd {
+            return None;
+        }
+
+        // For V3/V4, tracked real capital must also meet the threshold
+        let real_value = real * known_price_usd;
+        if !self.is_v2 && real_value > 0.0 && real_value < min_liquidity_usd {
+            return None;
+        }
+
+        Some((other, other_in_known * known_price_usd, liquidity))
+    }
+}
+
+/// Balances of each side at the current price, decimal-adjusted.
+///
+/// - For V2: reserves
+/// - For V3/V4: active virtual reserves from L and sqrtPriceX96
+fn active_balances(pool: &Pool) -> Option<(f64, f64)> {
+    if let (Some(liquidity), Some(sqrt_price)) = (&pool.liquidity, &pool.sqrt_price_x96) {
+        let liquidity = str_to_f64_with_decimals(liquidity, 0)?;
+        let sqrt_price_x96 = str_to_f64_with_decimals(sqrt_price, 0)?;
+        if liquidity <= 0.0 || sqrt_price_x96 <= 0.0 {
+            return None;
+        }
+
+        let (r0, r1) = calculate_reserves_from_liquidity_subgraph(liquidity, sqrt_price_x96);
+        return Some((
+            r0 / 10_f64.powi(pool.token0_decimals as i32),
+            r1 / 10_f64.powi(pool.token1_decimals as i32),
+        ));
+    }
+
+    match (pool.reserve0_f64(), pool.reserve1_f64()) {
+        (Some(r0), Some(r1)) if r0 > 0.0 && r1 > 0.0 => Some((r0, r1)),
+        _ => None,
+    }
+}
+
+/// One pool along a routed price.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteHop {
+    pub pool_address: String,
+    /// USD value of the side of the pool whose price was already known
+    pub liquidity_usd: f64,
+    /// `reserves` (V2) or `sqrt_price` (V3/V4)
+    pub method: &'static str,
+}
+
+/// USD price of a token resolved through the graph.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RoutedPrice {
+    pub price_usd: f64,
+    /// Smallest USD liquidity along the route (the bottleneck)
+    pub liquidity_usd: f64,
+    /// Token with a known USD price the route starts from
+    pub anchor: String,
+    /// Pools from the anchor to the token
+    pub path: Vec<RouteHop>,
+}
+
+impl RoutedPrice {
+    /// Number of pools between the token and its anchor
+    pub fn hops(&self) -> usize {
+        self.path.len()
+    }
+}
+
+/// Chain-wide graph of pools used to route token prices.
+///
+/// Tokens are nodes and pools are edges. The graph is seeded from PostgreSQL when a
+/// worker starts and updated with every pool touched in a batch, so a token whose
+/// deep pool did not trade recently is still priced through it.
+///
+/// Prices are routed from anchors (tokens with a known USD price: stablecoins, the
+/// wrapped native token, fresh oracle answers) along the path whose thinnest pool
+/// is deepest. Only whitelisted tokens are used as intermediate hops, so garbage
+/// tokens can be priced but never price anything else.
+///
+/// Since intermediate hops are whitelisted, every route is a path through hub tokens
+/// (the tokens named in the chain configuration) followed by at most one pool to the
+/// priced token. Pools between two hubs are indexed separately, so routing only
+/// searches that small subgraph plus the pools of the tokens being priced.
+#[derive(Debug, Default)]
+pub struct PricingGraph {
+    /// Pool address -> edge
+    edges: FxHashMap<String, PoolEdge>,
+    /// Token -> addresses of pools containing it
+    adjacency: FxHashMap<String, FxHashSet<String>>,
+    /// Tokens that may anchor or carry routes
+    hubs: FxHashSet<String>,
+    /// Hub -> addresses of pools pairing it with another hub
+    hub_adjacency: FxHashMap<String, FxHashSet<String>>,
+}
+
+impl PricingGraph {
+    /// Empty graph whose routes may only pass through `hubs`.
+    pub fn new(hubs: impl IntoIterator<Item = String>) -> Self {
+        Self {
+            hubs: hubs.into_iter().map(|token| token.to_lowercase()).collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn from_pools<'a>(
+        hubs: impl IntoIterator<Item = String>,
+        pools: impl IntoIterator<Item = &'a Pool>,
+    ) -> Self {
+        let mut graph = Self::new(hubs);
+        for pool in pools {
+            graph.upsert_pool(pool);
+        }
+        graph
+    }
+
+    /// Number of pools in the graph.
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    /// Insert or refresh a pool. Pools without a usable price or balance are removed.
+    pub fn upsert_pool(&mut self, pool: &Pool) {
+        let address = pool.address.to_lowercase();
+
+        match PoolEdge::from_pool(pool) {
+            Some(edge) => self.insert_edge(address, edge),
+            None => self.remove_pool(&address),
+        }
+    }
+
+    fn insert_edge(&mut self, address: String, edge: PoolEdge) {
+        let between_hubs = self.hubs.contains(&edge.token0) && self.hubs.contains(&edge.token1);
+
+        for token in [&edge.token0, &edge.token1] {
+            self.adjacency
+                .entry(token.clone())
+                .or_default()
+                .insert(address.clone());
+            if between_hubs {
+                self.hub_adjacency
+                    .entry(token.clone())
+                    .or_default()
+                    .insert(address.clone());
+            }
+        }
+        self.edges.insert(address, edge);
+    }
+
+    pub fn remove_pool(&mut self, address: &str) {
+        let Some(edge) = self.edges.remove(address) else {
+            return;
+        };
+
+        for token in [&edge.token0, &edge.token1] {
+            for index in [&mut self.adjacency, &mut self.hub_adjacency] {
+                if let Some(pools) = index.get_mut(token) {
+                    pools.remove(address);
+                    if pools.is_empty() {
+                        index.remove(token);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Route USD prices from `anchors` to each of `targets`.
+    ///
+    /// Widest-path search: each token takes the price of the route whose smallest pool
+    /// liquidity is largest, with fewer hops breaking ties. Routes only continue through
+    /// tokens for which `is_whitelisted` returns true, and only through pools holding at
+    /// least `min_liquidity_usd` on the known side. Anchors are not routed.
+    ///
+    /// The search runs over the hub subgraph; each target that is not a hub then takes
+    /// the widest route through one of its own pools.
+    pub fn route_prices<'a>(
+        &self,
+        targets: impl IntoIterator<Item = &'a str>,
+        anchors: &FxHashMap<String, f64>,
+        native_price_usd: f64,
+        min_liquidity_usd: f64,
+        is_whitelisted: impl Fn(&str) -> bool,
+    ) -> FxHashMap<String, RoutedPrice> {
+        let hub_routes = self.route_hubs(
+            anchors,
+            native_price_usd,
+            min_liquidity_usd,
+            &is_whitelisted,
+        );
+        let mut routed = FxHashMap::default();
+
+        for target in targets {
+            if anchors.contains_key(target) || routed.contains_key(target) {
+                continue;
+            }
+
+            let last_hop = match hub_routes.get(target) {
+                Some(_) => None,
+                None => match self.last_hop(
+                    target,
+                    &hub_routes,
+                    native_price_usd,
+                    min_liquidity_usd,
+                    &is_whitelisted,
+                ) {
+                    Some(last_hop) => Some(last_hop),
+                    None => continue,
+                },
+            };
+            routed.insert(
+                target.to_string(),
+                routed_price(&hub_routes, target, last_hop),
+            );
+        }
+
+        routed
+    }
+
+    /// Widest routes from the anchors to every hub reachable through hub pools.
+    fn route_hubs(
+        &self,
+        anchors: &FxHashMap<String, f64>,
+        native_price_usd: f64,
+        min_liquidity_usd: f64,
+        is_whitelisted: impl Fn(&str) -> bool,
+    ) -> FxHashMap<String, HubRoute> {
+        let mut routes: FxHashMap<String, HubRoute> = FxHashMap::default();
+        let mut heap = BinaryHeap::new();
+
+        for (token, &price_usd) in anchors {
+            if price_usd <= 0.0 || !price_usd.is_finite() {
+                continue;
+            }
+            routes.insert(
+                token.clone(),
+                HubRoute {
+                    price_usd,
+                    liquidity_usd: f64::INFINITY,
+                    hops: 0,
+                    parent: None,
+                },
+            );
+            heap.push(Candidate {
+                token: token.clone(),
+                liquidity_usd: f64::INFINITY,
+                hops: 0,
+            });
+        }
+
+        while let Some(Candidate {
+            token,
+            liquidity_usd,
+            hops,
+        }) = heap.pop()
+        {
+            let Some(route) = routes.get(&token) else {
+                continue;
+            };
+            // Skip entries superseded by a better route
+            if route.liquidity_usd != liquidity_usd || route.hops != hops {
+                continue;
+            }
+            if hops > 0 && !is_whitelisted(&token) {
+                continue;
+            }
+            let Some(pool_addresses) = self.hub_adjacency.get(&token) else {
+                continue;
+            };
+            let price_usd = route.price_usd;
+
+            for address in pool_addresses {
+                let Some((other, hop, other_price_usd)) = self.hop(
+                    address,
+                    &token,
+                    price_usd,
+                    native_price_usd,
+                    min_liquidity_usd,
+                ) else {
+                    continue;
+                };
+                if anchors.contains_key(other) {
+                    continue;
+                }
+
+                let candidate = HubRoute {
+                    price_usd: other_price_usd,
+                    liquidity_usd: liquidity_usd.min(hop.liquidity_usd),
+                    hops: hops + 1,
+                    parent: Some((token.clone(), hop)),
+                };
+                let improves = routes
+                    .get(other)
+                    .is_none_or(|current| candidate.is_wider(current));
+                if improves {
+                    heap.push(Candidate {
+                        token: other.to_string(),
+                        liquidity_usd: candidate.liquidity_usd,
+                        hops: candidate.hops,
+                    });
+                    routes.insert(other.to_string(), candidate);
+                }
+            }
+        }
+
+        routes
+    }
+
+    /// Widest route to a non-hub `target` through one of its pools: the routed hub it
+    /// is priced from, the pool leading to it and its USD price.
+    fn last_hop(
+        &self,
+        target: &str,
+        hub_routes: &FxHashMap<String, HubRoute>,
+        native_price_usd: f64,
+        min_liquidity_usd: f64,
+        is_whitelisted: impl Fn(&str) -> bool,
+    ) -> Option<(String, RouteHop, f64)> {
+        let mut best: Option<(String, RouteHop, f64, HubRoute)> = None;
+
+        for address in self.adjacency.get(target)? {
+            let Some(edge) = self.edges.get(address) else {
+                continue;
+            };
+            let hub = if edge.token0 == target { &edge.token1 } else { &edge.token0 };
+            let Some(hub_route) = hub_routes.get(hub) else {
+                continue;
+            };
+            if hub_route.hops > 0 && !is_whitelisted(hub) {
+                continue;
+            }
+            let Some((_, hop, price_usd)) = self.hop(
+                address,
+                hub,
+                hub_route.price_usd,
+                native_price_usd,
+                min_liquidity_usd,
+            ) else {
+                continue;
+            };
+
+            let candidate = HubRoute {
+                price_usd,
+                liquidity_usd: hub_route.liquidity_usd.min(hop.liquidity_usd),
+                hops: hub_route.hops + 1,
+                parent: None,
+            };
+            if best
+                .as_ref()
+                .is_none_or(|(_, _, _, current)| candidate.is_wider(current))
+            {
+                best = Some((hub.clone(), hop, price_usd, candidate));
+            }
+        }
+
+        best.map(|(hub, hop, price_usd, _)| (hub, hop, price_usd))
+    }
+
+    /// Cross pool `address` from `known` priced at `known_price_usd`: the other token,
+    /// the hop and the other token's validated USD price.
+    fn hop(
+        &self,
+        address: &str,
+        known: &str,
+        known_price_usd: f64,
+        native_price_usd: f64,
+        min_liquidity_usd: f64,
+    ) -> Option<(&str, RouteHop, f64)> {
+        let edge = self.edges.get(address)?;
+        let (other, other_usd, liquidity) =
+            edge.traverse(known, known_price_usd, min_liquidity_usd)?;
+
+        // Same bounds as single-pool derivation: absolute, then relative to native
+        let price_usd =
+            validate_usd_price_relative(validate_usd_price(other_usd), native_price_usd);
+        if price_usd <= 0.0 {
+            return None;
+        }
+
+        Some((
+            other,
+            RouteHop {
+                pool_address: address.to_string(),
+                liquidity_usd: liquidity,
+                method: edge.method(),
+            },
+            price_usd,
+        ))
+    }
+}
+
+/// Best known route to a hub, linked to the previous token through `parent`.
+#[derive(Debug, Clone)]
+struct HubRoute {
+    price_usd: f64,
+    /// Smallest USD liquidity along the route
+    liquidity_usd: f64,
+    hops: usize,
+    /// Previous token on the route and the pool leading here (None for anchors)
+    parent: Option<(String, RouteHop)>,
+}
+
+impl HubRoute {
+    /// Wider bottleneck wins; fewer hops break ties.
+    fn is_wider(&self, other: &HubRoute) -> bool {
+        match self.liquidity_usd.total_cmp(&other.liquidity_usd) {
+            Ordering::Greater => true,
+            Ordering::Less => false,
+            Ordering::Equal => self.hops < other.hops,
+        }
+    }
+}
+
+/// Build the route to `token` by walking the parent pointers of the hub routes,
+/// optionally extended by a final `(hub, hop, price)` from a hub to `token`.
+fn routed_price(
+    hub_routes: &FxHashMap<String, HubRoute>,
+    token: &str,
+    last_hop: Option<(String, RouteHop, f64)>,
+) -> RoutedPrice {
+    let (mut current, mut path, price_usd, mut liquidity_usd) = match last_hop {
+        Some((hub, hop, price_usd)) => {
+            let liquidity_usd = hop.liquidity_usd;
+            (hub, vec![hop], price_usd, liquidity_usd)
+        },
+        None => {
+            let route = &hub_routes[token];
+            (
+                token.to_string(),
+                Vec::new(),
+                route.price_usd,
+                f64::INFINITY,
+            )
+        },
+    };
+
+    while let Some((previous, hop)) = hub_routes.get(&current).and_then(|r| r.parent.as_ref()) {
+        liquidity_usd = liquidity_usd.min(hop.liquidity_usd);
+        path.push(hop.clone());
+        current = previous.clone();
+    }
+    path.reverse();
+
+    RoutedPrice {
+        price_usd,
+        liquidity_usd,
+        anchor: current,
+        path,
+    }
+}
+
+/// Heap entry ordered so the widest route is popped first.
+struct Candidate {
+    token: String,
+    liquidity_usd: f64,
+    hops: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.liquidity_usd
+            .total_cmp(&other.liquidity_usd)
+            .then_with(|| other.hops.cmp(&self.hops))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const USDC: &str = "usdc";
+    const WETH: &str = "weth";
+    const WBTC: &str = "wbtc";
+    const TOKEN: &str = "token";
+
+    /// V2 pool where 1 token0 = `price` token1, with `reserve0` token0 in the pool
+    fn edge(token0: &str, token1: &str, price: f64, reserve0: f64) -> PoolEdge {
+        PoolEdge {
+            token0: token0.to_string(),
+            token1: token1.to_string(),
+            token0_price: 1.0 / price,
+            token1_price: price,
+            active0: reserve0,
+            active1: reserve0 * price,
+            real0: reserve0,
+            real1: reserve0 * price,
+            is_v2: true,
+        }
+    }
+
+    fn anchors() -> FxHashMap<String, f64> {
+        FxHashMap::from_iter([(USDC.to_string(), 1.0), (WETH.to_string(), 2000.0)])
+    }
+
+    fn whitelisted(token: &str) -> bool {
+        [USDC, WETH, WBTC].contains(&token)
+    }
+
+    fn graph() -> PricingGraph {
+        PricingGraph::new([USDC, WETH, WBTC].map(str::to_string))
+    }
+
+    #[test]
+    fn test_routes_beyond_two_hops_through_whitelisted_tokens() {
+        let mut graph = graph();
+        // WBTC is only paired with WETH, TOKEN only with WBTC
+        graph.insert_edge("a".to_string(), edge(WBTC, WETH, 30.0, 100.0));
+        graph.insert_edge("b".to_string(), edge(TOKEN, WBTC, 0.0001, 1_000_000.0));
+
+        let routed = graph.route_prices(
+            [WBTC, TOKEN, "other"],
+            &anchors(),
+            2000.0,
+            10_000.0,
+            whitelisted,
+        );
+
+        assert!((routed[WBTC].price_usd - 60_000.0).abs() < 1e-6);
+        assert!((routed[TOKEN].price_usd - 6.0).abs() < 1e-6);
+        assert_eq!(routed[TOKEN].hops(), 2);
+        assert_eq!(routed[TOKEN].anchor, WETH);
+    }
+
+    #[test]
+    fn test_prefers_deepest_route() {
+        let mut graph = graph();
+        // Shallow USDC pool says $2, deep WETH pool says $1
+        graph.insert_edge("shallow".to_string(), edge(TOKEN, USDC, 2.0, 10_000.0));
+        graph.insert_edge("deep".to_string(), edge(TOKEN, WETH, 0.0005, 1_000_000.0));
+
+        let routed = graph.route_prices(
+            [WBTC, TOKEN, "other"],
+            &anchors(),
+            2000.0,
+            10_000.0,
+            whitelisted,
+        );
+
+        assert!((routed[TOKEN].price_usd - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_does_not_route_through_unlisted_tokens() {
+        let mut graph = graph();
+        graph.insert_edge("a".to_string(), edge(TOKEN, USDC, 1.0, 1_000_000.0));
+        graph.insert_edge("b".to_string(), edge("other", TOKEN, 1.0, 1_000_000.0));
+
+        let routed = graph.route_prices(
+            [WBTC, TOKEN, "other"],
+            &anchors(),
+            2000.0,
+            10_000.0,
+            whitelisted,
+        );
+
+        assert!(routed.contains_key(TOKEN));
+        assert!(!routed.contains_key("other"));
+    }
+
+    #[test]
+    fn test_remove_pool_drops_route() {
+        let mut graph = graph();
+        graph.insert_edge("a".to_string(), edge(TOKEN, USDC, 1.0, 1_000_000.0));
+        graph.remove_pool("a");
+
+        assert!(graph.is_empty());
+        assert!(!graph
+            .route_prices([TOKEN], &anchors(), 2000.0, 10_000.0, whitelisted)
+            .contains_key(TOKEN));
+    }
+
+    #[test]
+    fn test_only_targets_are_routed() {
+        let mut graph = graph();
+        graph.insert_edge("a".to_string(), edge(WBTC, WETH, 30.0, 100.0));
+        graph.insert_edge("b".to_string(), edge(TOKEN, USDC, 1.0, 1_000_000.0));
+        graph.insert_edge("c".to_string(), edge("other", USDC, 1.0, 1_000_000.0));
+
+        let routed = graph.route_prices([TOKEN], &anchors(), 2000.0, 10_000.0, whitelisted);
+
+        assert_eq!(routed.len(), 1);
+        assert_eq!(routed[TOKEN].path[0].pool_address, "b");
+    }
+
+    #[test]
+    fn test_pools_between_hubs_are_indexed() {
+        let mut graph = graph();
+        graph.insert_edge("a".to_string(), edge(WBTC, WETH, 30.0, 100.0));
+        graph.insert_edge("b".to_string(), edge(TOKEN, WBTC, 0.0001, 1_000_000.0));
+
+        assert_eq!(graph.hub_adjacency.len(), 2);
+        assert!(!graph.hub_adjacency.contains_key(TOKEN));
+
+        graph.remove_pool("a");
+        assert!(graph.hub_adjacency.is_empty());
+    }
+}
@@ -0,0 +1,123 @@
+use std::fmt::Debug;
+use std::sync::Arc;
+
+use anyhow::bail;
+use rustc_hash::FxHashMap;
+
+use crate::db::models::{ChainTokens, NativeTokenPrice, Pool, PricingThresholds};
+
+/// How a chain's prices are anchored and which thresholds gate them.
+///
+/// `PriceResolver` and the worker delegate every policy decision to the chain's
+/// strategy, so alternative approaches can be tried per chain (see
+/// `DatabaseChain::pricing_strategy`) without touching the pricing pipeline.
+pub trait PricingStrategy: Debug + Send + Sync {
+    /// Name used to select the strategy in chain configuration.
+    fn name(&self) -> &'static str;
+
+    /// Thresholds used when validating pools and swap prices.
+    fn thresholds(&self) -> &PricingThresholds;
+
+    /// Tokens with a USD price known without routing through pools.
+    ///
+    /// Oracle answers are added on top of these by the resolver.
+    fn anchor_prices(
+        &self,
+        chain_tokens: &ChainTokens,
+        native_price_usd: f64,
+    ) -> FxHashMap<String, f64>;
+
+    /// Whether a token can price the other side of its pools and carry routes
+    /// through the pricing graph. Pools with no whitelisted token are not priced.
+    fn is_whitelisted(&self, chain_tokens: &ChainTokens, token: &str) -> bool;
+
+    /// Update the native token price from the chain's reference pools.
+    ///
+    /// Returns true if the price changed.
+    fn update_native_price(
+        &self,
+        native_price: &mut NativeTokenPrice,
+        reference_pools: &[&Pool],
+        wrapped_native: &str,
+    ) -> bool;
+}
+
+/// Uniswap-subgraph style pricing, the behaviour of every chain unless configured otherwise.
+///
+/// - Stablecoins are fixed at $1.00
+/// - The native price is the liquidity-weighted median of the reference pools
+/// - Stablecoins, the wrapped native token and major tokens are whitelisted
+#[derive(Debug, Clone, Default)]
+pub struct DefaultPricingStrategy {
+    thresholds: PricingThresholds,
+}
+
+impl DefaultPricingStrategy {
+    pub const NAME: &'static str = "default";
+
+    pub fn new(thresholds: PricingThresholds) -> Self {
+        Self {
+            thresholds,
+        }
+    }
+}
+
+impl PricingStrategy for DefaultPricingStrategy {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    fn thresholds(&self) -> &PricingThresholds {
+        &self.thresholds
+    }
+
+    fn anchor_prices(
+        &self,
+        chain_tokens: &ChainTokens,
+        native_price_usd: f64,
+    ) -> FxHashMap<String, f64> {
+        let mut anchors = FxHashMap::default();
+
+        for stable in chain_tokens
+            .stablecoins
+            .iter()
+            .chain(std::iter::once(&chain_tokens.stable_token))
+        {
+            if chain_tokens.is_stable(stable) {
+                anchors.insert(stable.to_lowercase(), 1.0);
+            }
+        }
+        anchors.insert(
+            chain_tokens.wrapped_native_token.to_lowercase(),
+            native_price_usd,
+        );
+
+        anchors
+    }
+
+    fn is_whitelisted(&self, chain_tokens: &ChainTokens, token: &str) -> bool {
+        chain_tokens.is_stable(token)
+            || chain_tokens.is_wrapped_native(token)
+            || chain_tokens.is_major_token(token)
+    }
+
+    fn update_native_price(
+        &self,
+        native_price: &mut NativeTokenPrice,
+        reference_pools: &[&Pool],
+        wrapped_native: &str,
+    ) -> bool {
+        native_price.update_from_reference_pools(reference_pools, wrapped_native)
+    }
+}
+
+/// Build the pricing strategy configured for a chain.
+pub fn pricing_strategy(
+    name: &str,
+    thresholds: PricingThresholds,
+) -> anyhow::Result<Arc<dyn PricingStrategy>> {
+    match name {
+        DefaultPricingStrategy::NAME | "" => Ok(Arc::new(DefaultPricingStrategy::new(thresholds))),
+        _ => bail!("Unknown pricing strategy '{}'", name),
+    }
+}
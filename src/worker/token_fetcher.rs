@@ -1,15 +1,18 @@
 use crate::abis::erc20::IERC20;
 use crate::abis::multicall::Call3;
 use crate::db::models::DatabaseChain;
+use crate::utils::ZERO_ADDRESS;
 use crate::Database;
 use crate::{abis::multicall::IMulticall3, db::models::Token};
+use alloy::eips::BlockId;
+use alloy::primitives::{Address, U256};
 use alloy::providers::MULTICALL3_ADDRESS;
 use alloy::{
-    providers::{DynProvider, ProviderBuilder},
+    providers::{DynProvider, Provider, ProviderBuilder},
     sol_types::SolCall,
 };
 use anyhow::{Context, Result};
-use log::info;
+use log::{info, warn};
 use moka::future::Cache;
 use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
@@ -27,6 +30,21 @@ pub struct TokenFetcher {
     invalid_tokens: Cache<String, ()>,
 }
 
+/// On-chain supply figures for a single token, in raw (undivided) units
+#[derive(Debug, Clone, Default)]
+pub struct TokenSupply {
+    pub total_supply: U256,
+    /// Sum of the balances held by excluded addresses (treasury, burn addresses)
+    pub excluded_balance: U256,
+}
+
+impl TokenSupply {
+    /// Total supply minus excluded balances, floored at zero
+    pub fn circulating_supply(&self) -> U256 {
+        self.total_supply.saturating_sub(self.excluded_balance)
+    }
+}
+
 /// Maximum retries for multicall
 const MAX_RETRIES: u32 = 3;
 
@@ -128,7 +146,9 @@ impl TokenFetcher {
     /// Batch size for multicall requests to avoid RPC congestion/timeouts
     const MULTICALL_BATCH_SIZE: usize = 20;
 
-    async fn fetch_metadata_batch(&self, addresses: &[String]) -> Vec<Option<Token>> {
+    /// Fetches token metadata from the RPC only, without the database or the
+    /// invalid-token cache (`None` where a token doesn't decode).
+    pub(crate) async fn fetch_metadata_batch(&self, addresses: &[String]) -> Vec<Option<Token>> {
         let mut all_tokens: Vec<Option<Token>> = Vec::with_capacity(addresses.len());
 
         // Process addresses in batches to avoid RPC congestion
@@ -301,6 +321,222 @@ impl TokenFetcher {
         Ok(tokens)
     }
 
+    /// Fetches `totalSupply()` for each token, plus `balanceOf()` of every excluded holder.
+    ///
+    /// Tokens whose `totalSupply()` call fails are omitted from the result.
+    /// Failed `balanceOf()` calls count as a zero balance.
+    pub async fn fetch_supplies(
+        &self,
+        addresses: &[String],
+        excluded_holders: &[String],
+    ) -> HashMap<String, TokenSupply> {
+        let holders: Vec<Address> = excluded_holders
+            .iter()
+            .filter_map(|addr| addr.parse().ok())
+            .collect();
+
+        let mut supplies = HashMap::with_capacity(addresses.len());
+
+        for chunk in addresses.chunks(Self::MULTICALL_BATCH_SIZE) {
+            for attempt in 0..MAX_RETRIES {
+                match self.fetch_supply_chunk(chunk, &holders).await {
+                    Ok(chunk_supplies) => {
+                        supplies.extend(chunk_supplies);
+                        break;
+                    },
+                    Err(_) if attempt < MAX_RETRIES - 1 => {
+                        let delay = Duration::from_millis(RETRY_DELAY_MS * 2_u64.pow(attempt));
+                        tokio::time::sleep(delay).await;
+                    },
+                    Err(e) => {
+                        warn!(
+                            "Chain {}: Failed to fetch supply for {} tokens: {:#}",
+                            self.chain_id,
+                            chunk.len(),
+                            e
+                        );
+                    },
+                }
+            }
+        }
+
+        supplies
+    }
+
+    async fn fetch_supply_chunk(
+        &self,
+        addresses: &[String],
+        holders: &[Address],
+    ) -> Result<Vec<(String, TokenSupply)>> {
+        let multicall = IMulticall3::new(MULTICALL3_ADDRESS, &self.provider);
+        let calls_per_token = 1 + holders.len();
+        let mut calls = Vec::with_capacity(addresses.len() * calls_per_token);
+
+        for addr in addresses {
+            let address = addr.parse().context("Invalid address")?;
+            let token = IERC20::new(address, &self.provider);
+
+            // totalSupply()
+            calls.push(Call3 {
+                target: address,
+                allowFailure: true,
+                callData: token.totalSupply().calldata().to_vec().into(),
+            });
+            // balanceOf(holder) for each excluded holder
+            for holder in holders {
+                calls.push(Call3 {
+                    target: address,
+                    allowFailure: true,
+                    callData: token.balanceOf(*holder).calldata().to_vec().into(),
+                });
+            }
+        }
+
+        let results = tokio::time::timeout(RPC_CALL_TIMEOUT, multicall.aggregate3(calls).call())
+            .await
+            .context("Multicall timeout")?
+            .context("Multicall aggregate3 failed")?;
+
+        let mut supplies = Vec::with_capacity(addresses.len());
+
+        for (i, addr) in addresses.iter().enumerate() {
+            let base_idx = i * calls_per_token;
+            let Some(token_results) = results.get(base_idx..base_idx + calls_per_token) else {
+                break;
+            };
+
+            let total_res = &token_results[0];
+            if !total_res.success {
+                continue;
+            }
+            let Ok(total_supply) =
+                IERC20::totalSupplyCall::abi_decode_returns(&total_res.returnData)
+            else {
+                continue;
+            };
+
+            let excluded_balance = token_results[1..]
+                .iter()
+                .filter(|res| res.success)
+                .filter_map(|res| IERC20::balanceOfCall::abi_decode_returns(&res.returnData).ok())
+                .fold(U256::ZERO, |acc, balance| acc.saturating_add(balance));
+
+            supplies.push((
+                addr.clone(),
+                TokenSupply {
+                    total_supply,
+                    excluded_balance,
+                },
+            ));
+        }
+
+        Ok(supplies)
+    }
+
+    /// Latest block number of the chain.
+    pub async fn latest_block(&self) -> Result<u64> {
+        tokio::time::timeout(RPC_CALL_TIMEOUT, self.provider.get_block_number())
+            .await
+            .context("eth_blockNumber timeout")?
+            .context("eth_blockNumber failed")
+    }
+
+    /// Fetches `balanceOf(holder)` for each (token, holder) pair at `block`.
+    ///
+    /// A `ZERO_ADDRESS` token reads the holder's native balance instead.
+    /// Pairs whose call fails are omitted from the result.
+    pub async fn fetch_balances(
+        &self,
+        pairs: &[(String, String)],
+        block: u64,
+    ) -> HashMap<(String, String), U256> {
+        let mut balances = HashMap::with_capacity(pairs.len());
+
+        for chunk in pairs.chunks(Self::BALANCE_BATCH_SIZE) {
+            for attempt in 0..MAX_RETRIES {
+                match self.fetch_balance_chunk(chunk, block).await {
+                    Ok(chunk_balances) => {
+                        balances.extend(chunk_balances);
+                        break;
+                    },
+                    Err(_) if attempt < MAX_RETRIES - 1 => {
+                        let delay = Duration::from_millis(RETRY_DELAY_MS * 2_u64.pow(attempt));
+                        tokio::time::sleep(delay).await;
+                    },
+                    Err(e) => {
+                        warn!(
+                            "Chain {}: Failed to fetch {} balances at block {}: {:#}",
+                            self.chain_id,
+                            chunk.len(),
+                            block,
+                            e
+                        );
+                    },
+                }
+            }
+        }
+
+        balances
+    }
+
+    /// Batch size for balance multicalls (one cheap call per pair)
+    const BALANCE_BATCH_SIZE: usize = 200;
+
+    async fn fetch_balance_chunk(
+        &self,
+        pairs: &[(String, String)],
+        block: u64,
+    ) -> Result<Vec<((String, String), U256)>> {
+        let multicall = IMulticall3::new(MULTICALL3_ADDRESS, &self.provider);
+        let mut calls = Vec::with_capacity(pairs.len());
+
+        for (token, holder) in pairs {
+            let holder: Address = holder.parse().context("Invalid holder address")?;
+            let call = if token == ZERO_ADDRESS {
+                Call3 {
+                    target: MULTICALL3_ADDRESS,
+                    allowFailure: true,
+                    callData: multicall.getEthBalance(holder).calldata().to_vec().into(),
+                }
+            } else {
+                let address = token.parse().context("Invalid token address")?;
+                Call3 {
+                    target: address,
+                    allowFailure: true,
+                    callData: IERC20::new(address, &self.provider)
+                        .balanceOf(holder)
+                        .calldata()
+                        .to_vec()
+                        .into(),
+                }
+            };
+            calls.push(call);
+        }
+
+        let results = tokio::time::timeout(
+            RPC_CALL_TIMEOUT,
+            multicall
+                .aggregate3(calls)
+                .block(BlockId::number(block))
+                .call(),
+        )
+        .await
+        .context("Multicall timeout")?
+        .context("Multicall aggregate3 failed")?;
+
+        Ok(pairs
+            .iter()
+            .zip(results)
+            .filter(|(_, res)| res.success)
+            .filter_map(|(pair, res)| {
+                // balanceOf and getEthBalance both return a single uint256
+                IERC20::balanceOfCall::abi_decode_returns(&res.returnData)
+                    .ok()
+                    .map(|balance| (pair.clone(), balance))
+            })
+            .collect())
+    }
+
     /// Ensures the wrapped native token exists in the database.
     /// This should be called once at worker startup to guarantee the token exists
     /// before any batches run, since pools with zero addresses get normalized to wrapped native.
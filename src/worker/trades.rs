@@ -0,0 +1,217 @@
+//! Multi-hop trade reconstruction.
+//!
+//! A trade routed through several pools emits one `swap` event per hop. This
+//! module chains the hops of each transaction back into single trades, so that
+//! volume is counted once per trade rather than once per pool.
+
+use fastnum::D256;
+use rustc_hash::{FxHashMap, FxHashSet};
+
+use crate::{
+    db::models::{Event, Trade},
+    utils::decimal_to_f64,
+    worker::mev::{bought_side, bought_token, sold_token},
+};
+
+/// Decimal-adjusted amount of token `side` moved by the swap.
+fn side_amount(event: &Event, side: u8) -> D256 {
+    if side == 0 {
+        event.amount0_adjusted
+    } else {
+        event.amount1_adjusted
+    }
+}
+
+/// Build one `Trade` per chain of hops in each transaction.
+///
+/// Hops are taken in `log_index` order: a swap continues the current trade when it
+/// sells the token the previous hop bought, otherwise it starts a new trade. A swap
+/// that joins no other hop is a single-hop trade.
+///
+/// `token_price_usd` resolves USD prices for the trade value (0.0 when unknown).
+pub fn build_trades(
+    chain_id: u64,
+    events: &[Event],
+    mut token_price_usd: impl FnMut(&str) -> f64,
+) -> Vec<Trade> {
+    // Swaps grouped by transaction, in execution order
+    let mut txs: FxHashMap<&str, Vec<usize>> = FxHashMap::default();
+    for (i, event) in events.iter().enumerate() {
+        if event.event_type == "swap" && bought_side(event).is_some() {
+            txs.entry(event.tx_hash.as_str()).or_default().push(i);
+        }
+    }
+
+    let mut trades = Vec::new();
+    for swaps in txs.values_mut() {
+        swaps.sort_by_key(|&i| events[i].log_index);
+
+        let mut used: FxHashSet<usize> = FxHashSet::default();
+        for pos in 0..swaps.len() {
+            if used.contains(&swaps[pos]) {
+                continue;
+            }
+            let hops = chain_hops(events, &swaps[pos..], &used);
+            used.extend(hops.iter().copied());
+            trades.extend(build_trade(chain_id, events, &hops, &mut token_price_usd));
+        }
+    }
+
+    trades.sort_by_key(|t| (t.block_number, t.first_log_index));
+    trades
+}
+
+/// Follow the bought token from `swaps[0]` through the later unused swaps.
+fn chain_hops(events: &[Event], swaps: &[usize], used: &FxHashSet<usize>) -> Vec<usize> {
+    let mut hops = vec![swaps[0]];
+    let Some(mut held) = bought_token(&events[swaps[0]]) else {
+        return hops;
+    };
+
+    for &i in &swaps[1..] {
+        let event = &events[i];
+        if used.contains(&i) || sold_token(event) != Some(held) {
+            continue;
+        }
+        hops.push(i);
+        match bought_token(event) {
+            Some(token) => held = token,
+            None => break,
+        }
+    }
+
+    hops
+}
+
+fn build_trade(
+    chain_id: u64,
+    events: &[Event],
+    hops: &[usize],
+    token_price_usd: &mut impl FnMut(&str) -> f64,
+) -> Option<Trade> {
+    let (first, last) = (&events[*hops.first()?], &events[*hops.last()?]);
+    let token_in = sold_token(first)?;
+    let token_out = bought_token(last)?;
+
+    let amount_in = side_amount(first, 1 - bought_side(first)?);
+    let amount_out = side_amount(last, bought_side(last)?);
+
+    let usd = |value: f64| if value.is_finite() { value } else { 0.0 };
+    let amount_in_usd = usd(decimal_to_f64(amount_in) * token_price_usd(token_in));
+    let amount_out_usd = usd(decimal_to_f64(amount_out) * token_price_usd(token_out));
+
+    let mut path: Vec<String> = hops
+        .iter()
+        .filter_map(|&i| sold_token(&events[i]).map(str::to_string))
+        .collect();
+    path.push(token_out.to_string());
+
+    Some(Trade {
+        chain_id,
+        block_number: first.block_number,
+        timestamp: first.timestamp,
+        tx_hash: first.tx_hash.clone(),
+        first_log_index: first.log_index,
+        last_log_index: last.log_index,
+        // The maker is the router or the pool caller, the wallet is the tx sender
+        trader: if first.tx_from.is_empty() { &first.maker } else { &first.tx_from }.clone(),
+        token_in: token_in.to_string(),
+        token_out: token_out.to_string(),
+        amount_in,
+        amount_out,
+        route: hops
+            .iter()
+            .map(|&i| events[i].pool_address.clone())
+            .collect(),
+        path,
+        hops: hops.len().min(u8::MAX as usize) as u8,
+        amount_in_usd,
+        amount_out_usd,
+        volume_usd: if amount_in_usd > 0.0 { amount_in_usd } else { amount_out_usd },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{abis::v2, db::models::Token};
+    use alloy::primitives::{Address, U256};
+
+    fn token(address: &str) -> Token {
+        Token::new(1, address.into(), "T".into(), "T".into(), 0)
+    }
+
+    /// V2 swap selling `amount0` of token0 for `amount1` of token1.
+    fn sell0(
+        pool: &str,
+        token0: &str,
+        token1: &str,
+        log_index: u32,
+        amount0: u64,
+        amount1: u64,
+    ) -> Event {
+        let maker = Address::repeat_byte(0xaa);
+        let event = v2::Swap {
+            sender: maker,
+            amount0In: U256::from(amount0),
+            amount1In: U256::ZERO,
+            amount0Out: U256::ZERO,
+            amount1Out: U256::from(amount1),
+            to: maker,
+        };
+        Event::from_v2_swap(
+            1,
+            event,
+            &token(token0),
+            &token(token1),
+            100,
+            "0x01".into(),
+            log_index,
+            pool.into(),
+            0,
+        )
+    }
+
+    #[test]
+    fn test_chains_router_hops_into_one_trade() {
+        let (a, b, c) = ("0xa", "0xb", "0xc");
+        let events = vec![sell0("0xpool1", a, b, 1, 100, 50), sell0("0xpool2", b, c, 2, 50, 20)];
+
+        let trades = build_trades(1, &events, |token| if token == a { 1.0 } else { 0.0 });
+
+        assert_eq!(trades.len(), 1);
+        let trade = &trades[0];
+        assert_eq!((trade.token_in.as_str(), trade.token_out.as_str()), (a, c));
+        assert_eq!(trade.route, vec!["0xpool1", "0xpool2"]);
+        assert_eq!(trade.path, vec![a, b, c]);
+        assert_eq!(trade.amount_in, D256::from(100));
+        assert_eq!(trade.amount_out, D256::from(20));
+        assert_eq!(trade.volume_usd, 100.0);
+    }
+
+    #[test]
+    fn test_unrelated_swaps_stay_separate_trades() {
+        let events = vec![
+            sell0("0xpool1", "0xa", "0xb", 1, 100, 50),
+            sell0("0xpool2", "0xc", "0xd", 2, 10, 5),
+        ];
+
+        let trades = build_trades(1, &events, |_| 1.0);
+
+        assert_eq!(trades.len(), 2);
+        assert!(trades.iter().all(|trade| trade.hops == 1));
+    }
+
+    #[test]
+    fn test_trader_is_the_tx_sender() {
+        let mut routed = sell0("0xpool1", "0xa", "0xb", 1, 100, 50);
+        routed.tx_from = "0xwallet".into();
+        let direct = sell0("0xpool2", "0xc", "0xd", 2, 10, 5);
+
+        let trades = build_trades(1, &[routed.clone(), direct.clone()], |_| 1.0);
+
+        assert_ne!(routed.maker, routed.tx_from);
+        assert_eq!(trades[0].trader, "0xwallet");
+        assert_eq!(trades[1].trader, direct.maker);
+    }
+}
@@ -2,11 +2,11 @@ use alloy::{primitives::U256, sol_types::SolEvent};
 use anyhow::Context;
 use chrono::Utc;
 use hypersync_client::{
-    net_types::{BlockField, LogField, LogFilter, Query},
-    Client, ClientConfig, SerializationFormat, StreamConfig,
+    net_types::{types::AnyOf, BlockField, LogField, LogFilter, Query, TransactionField},
+    Client, ClientConfig, QueryResponse, SerializationFormat, StreamConfig,
 };
 use log::{info, warn};
-use rustc_hash::FxHashMap;
+use rustc_hash::{FxHashMap, FxHashSet};
 use std::{
     sync::Arc,
     time::{Duration, Instant},
@@ -15,20 +15,25 @@ use tokio::sync::mpsc;
 use tokio_util::sync::CancellationToken;
 
 use crate::{
-    abis::{erc20, v2, v3, v4},
+    abis::{chainlink, erc20, v2, v3, v4},
     db::{
         clickhouse::ops::BatchDataMessage,
         models::{
-            ChainTokens, DatabaseChain, Event, NativeTokenPrice, NewPool, Pool, SupplyEvent,
-            SyncCheckpoint,
+            ChainTokens, DatabaseChain, Event, NativeTokenPrice, NewPool, OraclePrice, Pool,
+            SupplyEvent, SyncCheckpoint,
         },
         IngestMessage,
     },
     utils::{compute_v4_pool_id, compute_v4_pool_id_from_stored, hex_encode},
     worker::{
+        audit::{self, BucketCounts, IndexedTable, RangeAudit, ReindexStats},
+        mev,
         parser::{self, ParsedLog},
-        price_resolver::PriceResolver,
+        price_resolver::{OracleQuote, PriceResolver},
+        pricing_graph::PricingGraph,
+        pricing_strategy::{pricing_strategy, PricingStrategy},
         token_fetcher::TokenFetcher,
+        trades,
     },
     Database,
 };
@@ -54,7 +59,11 @@ pub struct ChainWorker {
     client: Arc<Client>,
     db: Arc<Database>,
     filters: LogFilter,
+    /// Answers from the chain's configured Chainlink aggregators, if any
+    price_feed_filter: Option<LogFilter>,
     chain_tokens: Arc<ChainTokens>,
+    /// Anchors, whitelist and thresholds used to price this chain
+    pricing_strategy: Arc<dyn PricingStrategy>,
     token_fetcher: TokenFetcher,
     tip_poll_interval: Duration,
     factories: Vec<String>,
@@ -66,6 +75,45 @@ pub struct ChainWorker {
 /// across multiple event batches within a single indexer run.
 struct BatchState {
     native_token_price: NativeTokenPrice,
+    /// Latest known state of every reference pool, including those not touched in a batch
+    reference_pools: FxHashMap<String, Pool>,
+    /// Latest answer per price feed aggregator
+    oracle_prices: FxHashMap<String, OraclePrice>,
+    /// Every priced pool on the chain, used to route token prices
+    pricing_graph: PricingGraph,
+}
+
+/// How a batch is processed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BatchMode {
+    /// Main sync: pool and token state is flushed to PostgreSQL
+    Sync,
+    /// Re-index of an already indexed range: only ClickHouse rows are produced
+    Reindex,
+}
+
+/// Output of `ChainWorker::process_batch`.
+struct ProcessedBatch {
+    batch: BatchDataMessage,
+    latest_block_timestamp: u64,
+    /// Aggregators with a new answer in this batch
+    oracle_updates: Vec<String>,
+}
+
+/// HyperSync client for the chain at `url`.
+pub fn hypersync_client(url: &str, hypersync_token: String) -> anyhow::Result<Client> {
+    let client_config = ClientConfig {
+        serialization_format: SerializationFormat::CapnProto {
+            should_cache_queries: false,
+        },
+        http_req_timeout_millis: 120_000,
+        url: url.parse().context("Invalid HyperSync URL")?,
+        api_token: hypersync_token,
+        max_num_retries: 5,
+        ..Default::default()
+    };
+
+    Client::new(client_config).context("Failed to create HyperSync client")
 }
 
 impl ChainWorker {
@@ -77,24 +125,7 @@ impl ChainWorker {
         db: Arc<Database>,
         tip_poll_interval_milliseconds: u64,
     ) -> anyhow::Result<Self> {
-        let url = config
-            .hypersync_url
-            .parse()
-            .context("Invalid HyperSync URL")?;
-
-        let client_config = ClientConfig {
-            serialization_format: SerializationFormat::CapnProto {
-                should_cache_queries: false,
-            },
-            http_req_timeout_millis: 120_000,
-            url,
-            api_token: hypersync_token,
-            max_num_retries: 5,
-            ..Default::default()
-        };
-
-        let client =
-            Arc::new(Client::new(client_config).context("Failed to create HyperSync client")?);
+        let client = Arc::new(hypersync_client(&config.hypersync_url, hypersync_token)?);
 
         let token_fetcher =
             TokenFetcher::new(config.rpc_url.clone(), config.chain_id as i64, db.clone());
@@ -105,8 +136,32 @@ impl ChainWorker {
             config.major_tokens.clone(),
             config.stablecoins.clone(),
             config.stable_pool_address.clone(),
+            config.reference_pools.clone(),
+            config.price_feeds.clone(),
         );
 
+        let pricing_strategy =
+            pricing_strategy(&config.pricing_strategy, config.pricing_thresholds)
+                .with_context(|| format!("Chain {}: invalid pricing strategy", config.chain_id))?;
+
+        let price_feed_filter = if config.price_feeds.is_empty() {
+            None
+        } else {
+            Some(
+                LogFilter::all()
+                    .and_address(
+                        config
+                            .price_feeds
+                            .iter()
+                            .map(|feed| feed.aggregator_address.as_str()),
+                    )?
+                    .and_topic0([
+                        chainlink::AnswerUpdated::SIGNATURE_HASH.0,
+                        chainlink::NewTransmission::SIGNATURE_HASH.0,
+                    ])?,
+            )
+        };
+
         let worker = Self {
             historical_sender,
             live_sender,
@@ -132,7 +187,9 @@ impl ChainWorker {
                 v3::Swap::SIGNATURE_HASH.0,
                 v4::Swap::SIGNATURE_HASH.0,
             ])?,
+            price_feed_filter,
             chain_tokens: Arc::new(chain_tokens),
+            pricing_strategy,
             token_fetcher,
             tip_poll_interval: Duration::from_millis(tip_poll_interval_milliseconds),
             factories: config.factories.clone(),
@@ -152,17 +209,7 @@ impl ChainWorker {
     pub async fn run(&self, cancellation_token: CancellationToken) -> anyhow::Result<()> {
         let mut last_progress_log = Instant::now();
 
-        // Initialize batch state with native token price from DB or default
-        let native_token_price = self
-            .db
-            .postgres
-            .get_native_token_price(self.chain_id as i64)
-            .await?
-            .unwrap_or_else(|| NativeTokenPrice::new(self.chain_id as i64, 0.0));
-
-        let mut batch_state = BatchState {
-            native_token_price,
-        };
+        let mut batch_state = self.initial_batch_state().await?;
 
         loop {
             // Check cancellation at the start of each loop
@@ -196,21 +243,7 @@ impl ChainWorker {
                 ..Default::default()
             };
 
-            let query = Query::new()
-                .from_block(last_synced_block)
-                .where_logs(self.filters.clone())
-                .select_block_fields([BlockField::Number, BlockField::Timestamp])
-                .select_log_fields([
-                    LogField::BlockNumber,
-                    LogField::TransactionHash,
-                    LogField::LogIndex,
-                    LogField::Address,
-                    LogField::Data,
-                    LogField::Topic0,
-                    LogField::Topic1,
-                    LogField::Topic2,
-                    LogField::Topic3,
-                ]);
+            let query = self.log_query(last_synced_block);
 
             let mut stream = self.client.stream(query, config).await?;
 
@@ -222,838 +255,20 @@ impl ChainWorker {
                 })?
             {
                 let res = res.context("Stream error")?;
+                let next_block = res.next_block;
 
-                // Get block timestamps for the log batch
-                let block_timestamps: FxHashMap<u64, u64> = res
-                    .data
-                    .blocks
-                    .iter()
-                    .flatten()
-                    .filter_map(|b| {
-                        let n = b.number?;
-                        let t = U256::from_be_slice(b.timestamp.as_ref()?).to::<u64>();
-                        Some((n, t))
-                    })
-                    .collect();
-
-                // Phase 1 -> Pre-parse all logs in a SINGLE PASS
-                // This eliminates repeated parsing in phases 3 and 4.
-                // We collect token addresses and parsed logs simultaneously.
-
-                // Estimate log count for capacity hints (avoid reallocations)
-                let log_count_estimate = res.data.logs.iter().flatten().count();
-
-                // Phase 1: Parse all logs using the parser module (single-pass)
-                // Returns parsed logs in sequential order + token/pool addresses
-                let parse_result = parser::parse_logs(
-                    res.data.logs.into_iter().flatten(),
-                    &block_timestamps,
-                    &self.chain_tokens,
-                    log_count_estimate,
-                );
-
-                let parsed_logs = parse_result.parsed_logs;
-                let mut token_addresses = parse_result.token_addresses;
-                let mut modified_pools_addresses = parse_result.modified_pools_addresses;
-
-                // Phase 1.5 -> Fetch existing pools that will be modified in this batch
-                // We need to do this BEFORE fetching tokens so we can include their tokens
-                modified_pools_addresses.sort();
-                modified_pools_addresses.dedup();
-
-                let mut updated_pools: FxHashMap<String, Pool> = FxHashMap::default();
-
-                // Batch fetch all modified pools from PostgreSQL
-                match self
-                    .db
-                    .postgres
-                    .get_pools(self.chain_id as i64, &modified_pools_addresses)
-                    .await
-                {
-                    Ok(pools) => {
-                        for pool in pools {
-                            // Add this pool's tokens to the token_addresses list
-                            token_addresses.push(pool.token0.clone());
-                            token_addresses.push(pool.token1.clone());
-                            updated_pools.insert(pool.address.clone(), pool);
-                        }
-                    },
-                    Err(e) => {
-                        warn!("Failed to fetch pools from DB: {:?}", e);
-                    },
-                }
-
-                // Deduplicate token addresses before fetching
-                token_addresses.sort();
-                token_addresses.dedup();
-
-                // Phase 2 -> We fetch all the tokens required through the token_fetcher and create new ones.
-                let mut tokens = self.token_fetcher.get_tokens(&token_addresses).await?;
-
-                // Phase 3 -> Process Pool creation events from pre-parsed logs
-                // (no re-parsing needed - we use the ParsedLog enum)
-
-                // Create pools map for pools created during this batch
-                let mut new_pools: FxHashMap<String, Pool> = FxHashMap::default();
-                // NewPool records for ClickHouse (tracking new pool discoveries)
-                let mut new_pool_records: Vec<NewPool> =
-                    Vec::with_capacity(log_count_estimate / 50);
-
-                for parsed_log in &parsed_logs {
-                    match parsed_log {
-                        // V2 Mint
-                        ParsedLog::V2PairCreated {
-                            event,
-                            log_address,
-                            block_number,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            // ANTI-SPOOFING: Validate pool address is not zero
-                            let event_pool_address = hex_encode(event.pair.as_slice());
-                            if event_pool_address == "0x0000000000000000000000000000000000000000" {
-                                continue;
-                            }
-
-                            // FACTORY FILTER: Only index pools from allowed factories
-                            if !self.factories.is_empty()
-                                && !self.factories.contains(&log_address.to_lowercase())
-                            {
-                                continue;
-                            }
-
-                            if let (Some(token0), Some(token1)) = (
-                                tokens.get(&hex_encode(event.token0.as_slice())),
-                                tokens.get(&hex_encode(event.token1.as_slice())),
-                            ) {
-                                let pool = Pool::from_v2_pool_created(
-                                    self.chain_id,
-                                    log_address.clone(),
-                                    event.clone(),
-                                    token0,
-                                    token1,
-                                    *block_number,
-                                    tx_hash.clone(),
-                                    *block_timestamp,
-                                    &self.chain_tokens,
-                                );
-
-                                let new_pool = NewPool::from_pool_created(
-                                    self.chain_id as u64,
-                                    pool.address.clone(),
-                                    *block_number,
-                                    tx_hash.clone(),
-                                    *block_timestamp,
-                                    pool.token0.clone(),
-                                    pool.token1.clone(),
-                                    pool.token0_symbol.clone(),
-                                    pool.token1_symbol.clone(),
-                                    pool.protocol.clone().unwrap_or_default(),
-                                    pool.protocol_version.clone().unwrap_or_default(),
-                                    pool.fee.unwrap_or(0),
-                                );
-
-                                new_pool_records.push(new_pool);
-                                new_pools.insert(pool.address.clone(), pool);
-                            }
-                        },
-                        ParsedLog::V3PoolCreated {
-                            event,
-                            log_address,
-                            block_number,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            // ANTI-SPOOFING: Validate pool address is not zero
-                            let event_pool_address = hex_encode(event.pool.as_slice());
-                            if event_pool_address == "0x0000000000000000000000000000000000000000" {
-                                continue;
-                            }
-
-                            // FACTORY FILTER: Only index pools from allowed factories
-                            if !self.factories.is_empty()
-                                && !self.factories.contains(&log_address.to_lowercase())
-                            {
-                                continue;
-                            }
-
-                            if let (Some(token0), Some(token1)) = (
-                                tokens.get(&hex_encode(event.token0.as_slice())),
-                                tokens.get(&hex_encode(event.token1.as_slice())),
-                            ) {
-                                let pool = Pool::from_v3_pool_created(
-                                    self.chain_id,
-                                    log_address.clone(),
-                                    event.clone(),
-                                    token0,
-                                    token1,
-                                    *block_number,
-                                    tx_hash.clone(),
-                                    *block_timestamp,
-                                    &self.chain_tokens,
-                                );
-
-                                let new_pool = NewPool::from_pool_created(
-                                    self.chain_id as u64,
-                                    pool.address.clone(),
-                                    *block_number,
-                                    tx_hash.clone(),
-                                    *block_timestamp,
-                                    pool.token0.clone(),
-                                    pool.token1.clone(),
-                                    pool.token0_symbol.clone(),
-                                    pool.token1_symbol.clone(),
-                                    pool.protocol.clone().unwrap_or_default(),
-                                    pool.protocol_version.clone().unwrap_or_default(),
-                                    pool.fee.unwrap_or(0),
-                                );
-
-                                new_pool_records.push(new_pool);
-                                new_pools.insert(pool.address.clone(), pool);
-                            }
-                        },
-                        ParsedLog::V4Initialize {
-                            event,
-                            log_address,
-                            block_number,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            // ANTI-SPOOFING: Validate pool ID by computing it from event fields
-                            let event_pool_id = hex_encode(event.id.as_slice());
-                            let computed_pool_id = compute_v4_pool_id(
-                                &hex_encode(event.currency0.as_slice()),
-                                &hex_encode(event.currency1.as_slice()),
-                                event.fee.to::<u32>(),
-                                event.tickSpacing.as_i32(),
-                                &event.hooks.to_string(),
-                            );
-
-                            if event_pool_id != computed_pool_id {
-                                warn!(
-                                    "V4 Initialize: Pool ID mismatch! Event ID: {}, Computed: {}",
-                                    event_pool_id, computed_pool_id
-                                );
-                                continue;
-                            }
-
-                            // FACTORY FILTER: Only index pools from allowed factories
-                            if !self.factories.is_empty()
-                                && !self.factories.contains(&log_address.to_lowercase())
-                            {
-                                continue;
-                            }
-
-                            // FACTORY FILTER: Only index pools from allowed factories
-                            if !self.factories.is_empty()
-                                && !self.factories.contains(&log_address.to_lowercase())
-                            {
-                                continue;
-                            }
-
-                            if let (Some(token0), Some(token1)) = (
-                                tokens.get(&hex_encode(event.currency0.as_slice())),
-                                tokens.get(&hex_encode(event.currency1.as_slice())),
-                            ) {
-                                let pool = Pool::from_v4_pool_created(
-                                    self.chain_id,
-                                    log_address.clone(),
-                                    event.clone(),
-                                    token0,
-                                    token1,
-                                    *block_number,
-                                    tx_hash.clone(),
-                                    *block_timestamp,
-                                    &self.chain_tokens,
-                                );
-
-                                let new_pool = NewPool::from_pool_created(
-                                    self.chain_id as u64,
-                                    pool.address.clone(),
-                                    *block_number,
-                                    tx_hash.clone(),
-                                    *block_timestamp,
-                                    pool.token0.clone(),
-                                    pool.token1.clone(),
-                                    pool.token0_symbol.clone(),
-                                    pool.token1_symbol.clone(),
-                                    pool.protocol.clone().unwrap_or_default(),
-                                    pool.protocol_version.clone().unwrap_or_default(),
-                                    pool.fee.unwrap_or(0),
-                                );
-
-                                new_pool_records.push(new_pool);
-                                new_pools.insert(pool.address.clone(), pool);
-                            }
-                        },
-                        _ => {}, // Other event types handled in Phase 4
-                    }
-                }
-
-                // Collect all pools in a single hashmap for easy access.
-                // new_pools takes precedence (comes second in chain) to handle pools created earlier in the same batch
-                let mut pools: FxHashMap<String, Pool> = updated_pools
-                    .into_iter()
-                    .chain(new_pools.into_iter())
-                    .collect();
-
-                // Phase 4 -> Process all swap/liquidity/transfer events from pre-parsed logs
-                // IMPORTANT: parsed_logs maintains sequential order from original log stream
-                // This is critical for correct pool state updates and native token price tracking
-
-                // Initialize storage vectors with capacity hints
-                let mut events: Vec<Event> = Vec::with_capacity(log_count_estimate / 2);
-                let mut supply_events: Vec<SupplyEvent> =
-                    Vec::with_capacity(log_count_estimate / 10);
-
-                for parsed_log in parsed_logs {
-                    match parsed_log {
-                        // V2 Mint
-                        ParsedLog::V2Mint {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v2_mint(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    if self.chain_tokens.is_stable_pool(&log_address) {
-                                        batch_state.native_token_price.update_from_pool(
-                                            pool,
-                                            &self.chain_tokens.wrapped_native_token,
-                                        );
-                                    }
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V3 Mint
-                        ParsedLog::V3Mint {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v3_mint(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V2 Burn
-                        ParsedLog::V2Burn {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v2_burn(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    if self.chain_tokens.is_stable_pool(&log_address) {
-                                        batch_state.native_token_price.update_from_pool(
-                                            pool,
-                                            &self.chain_tokens.wrapped_native_token,
-                                        );
-                                    }
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V3 Burn
-                        ParsedLog::V3Burn {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v3_burn(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V2 Sync
-                        ParsedLog::V2Sync {
-                            event,
-                            log_address,
-                            block_number,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                pool.update_v2_sync(&event, block_number, block_timestamp);
-                                if self.chain_tokens.is_stable_pool(&log_address) {
-                                    batch_state.native_token_price.update_from_pool(
-                                        pool,
-                                        &self.chain_tokens.wrapped_native_token,
-                                    );
-                                }
-                            }
-                        },
-                        // V3 Initialize
-                        ParsedLog::V3Initialize {
-                            event,
-                            log_address,
-                            block_number,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                pool.update_v3_initialize(&event, block_number, block_timestamp);
-                                if self.chain_tokens.is_stable_pool(&log_address) {
-                                    batch_state.native_token_price.update_from_pool(
-                                        pool,
-                                        &self.chain_tokens.wrapped_native_token,
-                                    );
-                                }
-                            }
-                        },
-                        // V3 Collect
-                        ParsedLog::V3Collect {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v3_collect(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V4 ModifyLiquidity
-                        ParsedLog::V4ModifyLiquidity {
-                            event,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            let event_pool_id = hex_encode(event.id.as_slice());
-                            if let Some(pool) = pools.get_mut(&event_pool_id) {
-                                // ANTI-SPOOFING: Validate pool ID
-                                let computed_pool_id = compute_v4_pool_id_from_stored(
-                                    &pool.token0,
-                                    &pool.token1,
-                                    pool.initial_fee.unwrap_or(0) as u32,
-                                    pool.tick_spacing.unwrap_or(0),
-                                    pool.hook_address
-                                        .as_deref()
-                                        .unwrap_or("0x0000000000000000000000000000000000000000"),
-                                );
-                                if event_pool_id != computed_pool_id {
-                                    warn!("V4 ModifyLiquidity: Pool ID mismatch! Event ID: {}, Computed: {}", event_pool_id, computed_pool_id);
-                                    continue;
-                                }
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v4_modify_liquidity(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                        pool,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V2 Swap
-                        ParsedLog::V2Swap {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v2_swap(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    if self.chain_tokens.is_stable_pool(&log_address) {
-                                        batch_state.native_token_price.update_from_pool(
-                                            pool,
-                                            &self.chain_tokens.wrapped_native_token,
-                                        );
-                                    }
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V3 Swap
-                        ParsedLog::V3Swap {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(pool) = pools.get_mut(&log_address) {
-                                // ANTI-SPOOFING: Require initialization
-                                if pool.sqrt_price_x96.is_none() {
-                                    continue;
-                                }
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v3_swap(
-                                        self.chain_id,
-                                        event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    if self.chain_tokens.is_stable_pool(&log_address) {
-                                        batch_state.native_token_price.update_from_pool(
-                                            pool,
-                                            &self.chain_tokens.wrapped_native_token,
-                                        );
-                                    }
-                                    events.push(ev);
-                                }
-                            }
-                        },
-                        // V4 Swap
-                        ParsedLog::V4Swap {
-                            event: swap_event,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            let event_pool_id = hex_encode(swap_event.id.as_slice());
-                            if let Some(pool) = pools.get_mut(&event_pool_id) {
-                                // ANTI-SPOOFING: Validate pool ID
-                                let computed_pool_id = compute_v4_pool_id_from_stored(
-                                    &pool.token0,
-                                    &pool.token1,
-                                    pool.initial_fee.unwrap_or(0) as u32,
-                                    pool.tick_spacing.unwrap_or(0),
-                                    pool.hook_address
-                                        .as_deref()
-                                        .unwrap_or("0x0000000000000000000000000000000000000000"),
-                                );
-                                if event_pool_id != computed_pool_id {
-                                    warn!(
-                                        "V4 Swap: Pool ID mismatch! Event ID: {}, Computed: {}",
-                                        event_pool_id, computed_pool_id
-                                    );
-                                    continue;
-                                }
-                                // ANTI-SPOOFING: Require initialization
-                                if pool.sqrt_price_x96.is_none() {
-                                    continue;
-                                }
-                                pool.update_v4_fee(&swap_event);
-                                if let (Some(token0), Some(token1)) =
-                                    (tokens.get(&pool.token0), tokens.get(&pool.token1))
-                                {
-                                    let ev = Event::from_v4_swap(
-                                        self.chain_id as u64,
-                                        swap_event,
-                                        token0,
-                                        token1,
-                                        block_number,
-                                        tx_hash,
-                                        log_index,
-                                        pool.address.clone(),
-                                        block_timestamp,
-                                    );
-                                    pool.update_from_event(&ev);
-                                    if self.chain_tokens.is_stable_pool(&event_pool_id) {
-                                        batch_state.native_token_price.update_from_pool(
-                                            pool,
-                                            &self.chain_tokens.wrapped_native_token,
-                                        );
-                                    }
-                                    events.push(ev);
-                                }
-                            }
-                        },
-
-                        // Supply: Transfer (Mint/Burn)
-                        ParsedLog::SupplyTransfer {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                            is_mint,
-                        } => {
-                            if let Some(token) = tokens.get(&log_address) {
-                                let event_type = if is_mint { "mint" } else { "burn" }.to_string();
-
-                                let event = SupplyEvent::new(
-                                    self.chain_id as u64,
-                                    block_number,
-                                    block_timestamp,
-                                    tx_hash,
-                                    log_index,
-                                    log_address,
-                                    event_type,
-                                    event.value,
-                                    token.decimals as u8,
-                                );
-
-                                supply_events.push(event);
-                            }
-                        },
-                        // Supply: Deposit (Mint for Wrapped)
-                        ParsedLog::SupplyDeposit {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(token) = tokens.get(&log_address) {
-                                let event = SupplyEvent::new(
-                                    self.chain_id as u64,
-                                    block_number,
-                                    block_timestamp,
-                                    tx_hash,
-                                    log_index,
-                                    log_address,
-                                    "mint".to_string(),
-                                    event.amount,
-                                    token.decimals as u8,
-                                );
-
-                                supply_events.push(event);
-                            }
-                        },
-                        // Supply: Withdrawal (Burn for Wrapped)
-                        ParsedLog::SupplyWithdrawal {
-                            event,
-                            log_address,
-                            block_number,
-                            log_index,
-                            tx_hash,
-                            block_timestamp,
-                        } => {
-                            if let Some(token) = tokens.get(&log_address) {
-                                let event = SupplyEvent::new(
-                                    self.chain_id as u64,
-                                    block_number,
-                                    block_timestamp,
-                                    tx_hash,
-                                    log_index,
-                                    log_address,
-                                    "burn".to_string(),
-                                    event.amount,
-                                    token.decimals as u8,
-                                );
-
-                                supply_events.push(event);
-                            }
-                        },
-                        _ => {},
-                    }
-                }
-
-                // Phase 4.5 -> Price all events and pools in USD
-                // This happens after all events are created and pool states are updated
-                //
-                // IMPORTANT: Create price resolver HERE, after all events processed,
-                // so it has the latest native token price from Sync/Swap events on the stable pool.
-                let mut price_resolver = PriceResolver::new(
-                    self.chain_tokens.clone(),
-                    batch_state.native_token_price.price_usd,
-                );
-
-                // Price all events
-                for event in &mut events {
-                    if let Some(pool) = pools.get(&event.pool_address) {
-                        price_resolver.price_event(event, pool, &pools);
-                    }
-                }
-
-                // Price all pools (calculate price_usd and tvl_usd)
-                // Use 2-pass approach to avoid cloning the entire pools map
-                // 1. Calculate updates (immutable access to pools)
-                let pool_updates: Vec<(String, Option<f64>, Option<f64>)> = pools
-                    .values()
-                    .map(|pool| {
-                        let (price, tvl) = price_resolver.calculate_pool_pricing(pool, &pools);
-                        (pool.address.clone(), price, tvl)
-                    })
-                    .collect();
-
-                // 2. Apply updates (mutable access to pools)
-                for (address, price, tvl) in pool_updates {
-                    if let Some(pool) = pools.get_mut(&address) {
-                        pool.price_usd = price;
-                        pool.tvl_usd = tvl;
-                    }
-                }
-
-                // Update initial_tvl_usd for newly created pools
-                for new_pool in &mut new_pool_records {
-                    if let Some(pool) = pools.get(&new_pool.pool_address) {
-                        if let Some(tvl) = pool.tvl_usd {
-                            new_pool.set_initial_tvl(tvl);
-                        }
-                    }
-                }
-
-                // Price all tokens using TVL-weighted average from pools where token is base
-                for token in tokens.values_mut() {
-                    if let Some(price) =
-                        price_resolver.calculate_token_price(&token.address, &pools)
-                    {
-                        token.price_usd = Some(price);
-                        token.price_updated_at = Some(Utc::now());
-                    }
-                }
-
-                // Phase 5 -> Flush pool states to PostgreSQL
-                // IMPORTANT: We must await these AND check for errors before updating checkpoint
-                // to prevent race conditions and data loss
-                // NOTE: Tokens are already saved in TokenFetcher when first discovered
-
-                let pools_to_flush: Vec<&Pool> = pools.values().collect();
-
-                // Batch flush tokens with updated prices
-                let tokens_to_flush: Vec<&crate::db::models::Token> = tokens.values().collect();
-
-                // Execute DB writes in parallel to reduce latency
-                let (pools_res, _) = tokio::join!(
-                    self.db.postgres.set_pools(&pools_to_flush),
-                    self.db.postgres.set_tokens(&tokens_to_flush)
-                );
-
-                // Check critical results
-                if let Err(e) = pools_res {
-                    warn!(
-                        "Chain {}: Failed to batch write pools: {:?}",
-                        self.chain_id, e
-                    );
-                }
-
-                // Phase 6 -> Send events to ClickHouse
-                // NOTE: This only queues the data. For full consistency, we should wait
-                // for ClickHouse confirmation, but that would require a different architecture
-                // (e.g., two-phase commit or acknowledgment channel)
-                let batch = BatchDataMessage {
-                    chain_id: self.chain_id,
-                    events,
-                    supply_events,
-                    new_pools: new_pool_records,
-                    pools: pools.values().cloned().collect(),
-                    tokens: tokens.values().cloned().collect(),
-                };
+                let ProcessedBatch {
+                    batch,
+                    latest_block_timestamp,
+                    oracle_updates,
+                } = self
+                    .process_batch(res, &mut batch_state, BatchMode::Sync)
+                    .await?;
 
                 // Tip detection: use timestamp-based approach for chain-agnostic detection
                 // If the latest block is within 60 seconds of current time, we're at the tip
                 // This works correctly regardless of chain block time (Ethereum ~12s, Arbitrum ~0.25s)
                 let current_timestamp = Utc::now().timestamp() as u64;
-                let latest_block_timestamp = block_timestamps.values().max().copied().unwrap_or(0);
                 let seconds_behind = current_timestamp.saturating_sub(latest_block_timestamp);
                 let is_at_tip = seconds_behind < 60;
 
@@ -1073,7 +288,6 @@ impl ChainWorker {
                 // On crash, we may re-process some blocks (causing duplicates in ClickHouse).
                 // This is acceptable: duplicates are better than data loss,
                 // and ClickHouse's ReplacingMergeTree can handle them.
-                let next_block = res.next_block;
                 last_synced_block = next_block;
                 let checkpoint = SyncCheckpoint::new(self.chain_id, next_block);
 
@@ -1094,6 +308,13 @@ impl ChainWorker {
                     .set_native_token_price(&batch_state.native_token_price)
                     .await;
 
+                // Save the oracle answers received in this batch (fire-and-forget, non-critical)
+                let oracle_prices: Vec<&OraclePrice> = oracle_updates
+                    .iter()
+                    .filter_map(|aggregator| batch_state.oracle_prices.get(aggregator))
+                    .collect();
+                let _ = self.db.postgres.set_oracle_prices(&oracle_prices).await;
+
                 // Log progress every PROGRESS_LOG_INTERVAL seconds to reduce noise
                 if last_progress_log.elapsed() >= PROGRESS_LOG_INTERVAL {
                     info!(
@@ -1121,4 +342,1224 @@ impl ChainWorker {
 
         Ok(())
     }
+
+    /// Latest block available on HyperSync for the chain.
+    pub async fn chain_height(&self) -> anyhow::Result<u64> {
+        self.client.get_height().await
+    }
+
+    /// Audits `[from_block, to_block)` in buckets of `bucket_size` blocks: source logs
+    /// from HyperSync against the rows indexed in ClickHouse (see `worker::audit`).
+    pub async fn audit_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+        bucket_size: u64,
+    ) -> anyhow::Result<Vec<RangeAudit>> {
+        anyhow::ensure!(bucket_size > 0, "The audit bucket size must be positive");
+        let mut expected: FxHashMap<u64, BucketCounts> = FxHashMap::default();
+
+        let query = self.log_query(from_block).to_block_excl(to_block);
+        let mut stream = self.client.stream(query, StreamConfig::default()).await?;
+
+        while let Some(res) = stream.recv().await {
+            let res = res.context("Stream error")?;
+            let parsed_logs = parser::parse_logs(
+                res.data.logs.into_iter().flatten(),
+                &FxHashMap::default(),
+                &self.chain_tokens,
+                0,
+            )
+            .parsed_logs;
+
+            audit::count_source_rows(
+                &self.db,
+                self.chain_id,
+                &parsed_logs,
+                from_block,
+                bucket_size,
+                &mut expected,
+            )
+            .await?;
+        }
+
+        let indexed =
+            audit::indexed_counts(&self.db, self.chain_id, from_block, to_block, bucket_size)
+                .await?;
+        let report = audit::build_report(from_block, to_block, bucket_size, &expected, &indexed);
+
+        for range in report.iter().filter(|range| !range.is_complete()) {
+            warn!(
+                "Chain {}: blocks {}..{} incomplete (events {}/{}, supply events {}/{})",
+                self.chain_id,
+                range.from_block,
+                range.to_block,
+                range.indexed_events,
+                range.expected_events,
+                range.indexed_supply_events,
+                range.expected_supply_events
+            );
+        }
+
+        Ok(report)
+    }
+
+    /// Re-indexes `[from_block, to_block)` and inserts the `events` and `supply_events`
+    /// rows missing from ClickHouse, with the trades, arbitrages and sandwiches of
+    /// their transactions.
+    ///
+    /// The sync checkpoint and the PostgreSQL pool and token state are left untouched,
+    /// so this can run next to the chain's worker. That state is the current one, not
+    /// the one at the time of the blocks, so the re-indexed rows are left unpriced
+    /// (`price_usd`, `volume_usd` and `fees_usd` at 0, like swaps of unpriced pools).
+    pub async fn reindex_range(
+        &self,
+        from_block: u64,
+        to_block: u64,
+    ) -> anyhow::Result<ReindexStats> {
+        let mut batch_state = self.initial_batch_state().await?;
+        let mut stats = ReindexStats::default();
+        let mut cursor = from_block;
+
+        let query = self.log_query(from_block).to_block_excl(to_block);
+        let mut stream = self.client.stream(query, StreamConfig::default()).await?;
+
+        while let Some(res) = stream.recv().await {
+            let res = res.context("Stream error")?;
+            let next_block = res.next_block.min(to_block);
+
+            let ProcessedBatch {
+                mut batch,
+                ..
+            } = self
+                .process_batch(res, &mut batch_state, BatchMode::Reindex)
+                .await?;
+
+            let (existing_events, existing_supply_events) = tokio::try_join!(
+                audit::existing_row_keys(
+                    &self.db,
+                    self.chain_id,
+                    IndexedTable::Events,
+                    cursor,
+                    next_block
+                ),
+                audit::existing_row_keys(
+                    &self.db,
+                    self.chain_id,
+                    IndexedTable::SupplyEvents,
+                    cursor,
+                    next_block
+                ),
+            )?;
+            cursor = next_block;
+
+            batch
+                .events
+                .retain(|ev| !existing_events.contains(&(ev.tx_hash.clone(), ev.log_index)));
+            batch
+                .supply_events
+                .retain(|ev| !existing_supply_events.contains(&(ev.tx_hash.clone(), ev.log_index)));
+            if batch.events.is_empty() && batch.supply_events.is_empty() {
+                continue;
+            }
+
+            // Derived rows are replacing tables: only those of re-indexed transactions
+            let tx_hashes: FxHashSet<&String> = batch
+                .events
+                .iter()
+                .map(|ev| &ev.tx_hash)
+                .chain(batch.supply_events.iter().map(|ev| &ev.tx_hash))
+                .collect();
+            let trades = std::mem::take(&mut batch.trades)
+                .into_iter()
+                .filter(|trade| tx_hashes.contains(&trade.tx_hash))
+                .collect();
+            let arbitrages = std::mem::take(&mut batch.arbitrages)
+                .into_iter()
+                .filter(|arb| tx_hashes.contains(&arb.tx_hash))
+                .collect();
+            let mev_sandwiches = std::mem::take(&mut batch.mev_sandwiches)
+                .into_iter()
+                .filter(|sandwich| tx_hashes.contains(&sandwich.victim_tx_hash))
+                .collect();
+            batch.trades = trades;
+            batch.arbitrages = arbitrages;
+            batch.mev_sandwiches = mev_sandwiches;
+
+            // Pool discoveries are kept at their first indexing, state is not published
+            batch.new_pools.clear();
+            batch.pools.clear();
+            batch.tokens.clear();
+
+            stats.events += batch.events.len();
+            stats.supply_events += batch.supply_events.len();
+            self.historical_sender
+                .send(IngestMessage::BatchData(batch))
+                .await?;
+        }
+
+        info!(
+            "Chain {}: re-indexed blocks {}..{} ({} events, {} supply events inserted)",
+            self.chain_id, from_block, to_block, stats.events, stats.supply_events
+        );
+
+        Ok(stats)
+    }
+
+    /// Seeds the cross-batch state from PostgreSQL.
+    async fn initial_batch_state(&self) -> anyhow::Result<BatchState> {
+        // Initialize batch state with native token price from DB or default
+        let native_token_price = self
+            .db
+            .postgres
+            .get_native_token_price(self.chain_id as i64)
+            .await?
+            .unwrap_or_else(|| NativeTokenPrice::new(self.chain_id as i64, 0.0));
+
+        // Seed reference pool states so the first batch can use pools it doesn't touch
+        let reference_pools = self
+            .db
+            .postgres
+            .get_pools(self.chain_id as i64, &self.chain_tokens.reference_pools)
+            .await?
+            .into_iter()
+            .map(|pool| (pool.address.clone(), pool))
+            .collect();
+
+        // Seed oracle answers so prices are available before the next round
+        let oracle_prices = self
+            .db
+            .postgres
+            .get_oracle_prices(self.chain_id as i64)
+            .await?
+            .into_iter()
+            .map(|price| (price.aggregator_address.clone(), price))
+            .collect();
+
+        // Seed the pricing graph with every priced pool on the chain
+        let pricing_graph = PricingGraph::from_pools(
+            self.chain_tokens.listed_tokens(),
+            &self
+                .db
+                .postgres
+                .get_priced_pools(self.chain_id as i64)
+                .await?,
+        );
+        info!(
+            "Chain {}: Loaded {} pools into the pricing graph",
+            self.chain_id,
+            pricing_graph.len()
+        );
+
+        Ok(BatchState {
+            native_token_price,
+            reference_pools,
+            oracle_prices,
+            pricing_graph,
+        })
+    }
+
+    /// Query for every log the worker indexes, from `from_block`.
+    fn log_query(&self, from_block: u64) -> Query {
+        let mut log_filters = AnyOf::new(self.filters.clone());
+        if let Some(price_feed_filter) = &self.price_feed_filter {
+            log_filters = log_filters.or(price_feed_filter.clone());
+        }
+
+        Query::new()
+            .from_block(from_block)
+            .where_logs(log_filters)
+            .select_block_fields([BlockField::Number, BlockField::Timestamp])
+            .select_log_fields([
+                LogField::BlockNumber,
+                LogField::TransactionHash,
+                LogField::LogIndex,
+                LogField::Address,
+                LogField::Data,
+                LogField::Topic0,
+                LogField::Topic1,
+                LogField::Topic2,
+                LogField::Topic3,
+            ])
+            .select_transaction_fields([TransactionField::Hash, TransactionField::From])
+    }
+
+    /// Runs phases 1-5 on one HyperSync response and returns the rows for ClickHouse.
+    ///
+    /// With `BatchMode::Reindex`, pool and token state is not written to PostgreSQL.
+    async fn process_batch(
+        &self,
+        res: QueryResponse,
+        batch_state: &mut BatchState,
+        mode: BatchMode,
+    ) -> anyhow::Result<ProcessedBatch> {
+        // Get block timestamps for the log batch
+        let block_timestamps: FxHashMap<u64, u64> = res
+            .data
+            .blocks
+            .iter()
+            .flatten()
+            .filter_map(|b| {
+                let n = b.number?;
+                let t = U256::from_be_slice(b.timestamp.as_ref()?).to::<u64>();
+                Some((n, t))
+            })
+            .collect();
+
+        // Transaction senders, joined to the matched logs by hypersync
+        let tx_senders: FxHashMap<String, String> = res
+            .data
+            .transactions
+            .iter()
+            .flatten()
+            .filter_map(|tx| {
+                Some((
+                    hex_encode(tx.hash.as_ref()?.as_ref()),
+                    hex_encode(tx.from.as_ref()?.as_ref()),
+                ))
+            })
+            .collect();
+
+        // Phase 1 -> Pre-parse all logs in a SINGLE PASS
+        // This eliminates repeated parsing in phases 3 and 4.
+        // We collect token addresses and parsed logs simultaneously.
+
+        // Estimate log count for capacity hints (avoid reallocations)
+        let log_count_estimate = res.data.logs.iter().flatten().count();
+
+        // Phase 1: Parse all logs using the parser module (single-pass)
+        // Returns parsed logs in sequential order + token/pool addresses
+        let parse_result = parser::parse_logs(
+            res.data.logs.into_iter().flatten(),
+            &block_timestamps,
+            &self.chain_tokens,
+            log_count_estimate,
+        );
+
+        let parsed_logs = parse_result.parsed_logs;
+        let mut token_addresses = parse_result.token_addresses;
+        let mut modified_pools_addresses = parse_result.modified_pools_addresses;
+
+        // Phase 1.5 -> Fetch existing pools that will be modified in this batch
+        // We need to do this BEFORE fetching tokens so we can include their tokens
+        modified_pools_addresses.sort();
+        modified_pools_addresses.dedup();
+
+        let mut updated_pools: FxHashMap<String, Pool> = FxHashMap::default();
+
+        // Batch fetch all modified pools from PostgreSQL
+        match self
+            .db
+            .postgres
+            .get_pools(self.chain_id as i64, &modified_pools_addresses)
+            .await
+        {
+            Ok(pools) => {
+                for pool in pools {
+                    // Add this pool's tokens to the token_addresses list
+                    token_addresses.push(pool.token0.clone());
+                    token_addresses.push(pool.token1.clone());
+                    updated_pools.insert(pool.address.clone(), pool);
+                }
+            },
+            Err(e) => {
+                warn!("Failed to fetch pools from DB: {:?}", e);
+            },
+        }
+
+        // Deduplicate token addresses before fetching
+        token_addresses.sort();
+        token_addresses.dedup();
+
+        // Phase 2 -> We fetch all the tokens required through the token_fetcher and create new ones.
+        let mut tokens = self.token_fetcher.get_tokens(&token_addresses).await?;
+
+        // Phase 3 -> Process Pool creation events from pre-parsed logs
+        // (no re-parsing needed - we use the ParsedLog enum)
+
+        // Create pools map for pools created during this batch
+        let mut new_pools: FxHashMap<String, Pool> = FxHashMap::default();
+        // NewPool records for ClickHouse (tracking new pool discoveries)
+        let mut new_pool_records: Vec<NewPool> = Vec::with_capacity(log_count_estimate / 50);
+
+        for parsed_log in &parsed_logs {
+            match parsed_log {
+                // V2 Mint
+                ParsedLog::V2PairCreated {
+                    event,
+                    log_address,
+                    block_number,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    // ANTI-SPOOFING: Validate pool address is not zero
+                    let event_pool_address = hex_encode(event.pair.as_slice());
+                    if event_pool_address == "0x0000000000000000000000000000000000000000" {
+                        continue;
+                    }
+
+                    // FACTORY FILTER: Only index pools from allowed factories
+                    if !self.factories.is_empty()
+                        && !self.factories.contains(&log_address.to_lowercase())
+                    {
+                        continue;
+                    }
+
+                    if let (Some(token0), Some(token1)) = (
+                        tokens.get(&hex_encode(event.token0.as_slice())),
+                        tokens.get(&hex_encode(event.token1.as_slice())),
+                    ) {
+                        let pool = Pool::from_v2_pool_created(
+                            self.chain_id,
+                            log_address.clone(),
+                            event.clone(),
+                            token0,
+                            token1,
+                            *block_number,
+                            tx_hash.clone(),
+                            *block_timestamp,
+                            &self.chain_tokens,
+                        );
+
+                        let new_pool = NewPool::from_pool_created(
+                            self.chain_id as u64,
+                            pool.address.clone(),
+                            *block_number,
+                            tx_hash.clone(),
+                            *block_timestamp,
+                            pool.token0.clone(),
+                            pool.token1.clone(),
+                            pool.token0_symbol.clone(),
+                            pool.token1_symbol.clone(),
+                            pool.protocol.clone().unwrap_or_default(),
+                            pool.protocol_version.clone().unwrap_or_default(),
+                            pool.fee.unwrap_or(0),
+                        );
+
+                        new_pool_records.push(new_pool);
+                        new_pools.insert(pool.address.clone(), pool);
+                    }
+                },
+                ParsedLog::V3PoolCreated {
+                    event,
+                    log_address,
+                    block_number,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    // ANTI-SPOOFING: Validate pool address is not zero
+                    let event_pool_address = hex_encode(event.pool.as_slice());
+                    if event_pool_address == "0x0000000000000000000000000000000000000000" {
+                        continue;
+                    }
+
+                    // FACTORY FILTER: Only index pools from allowed factories
+                    if !self.factories.is_empty()
+                        && !self.factories.contains(&log_address.to_lowercase())
+                    {
+                        continue;
+                    }
+
+                    if let (Some(token0), Some(token1)) = (
+                        tokens.get(&hex_encode(event.token0.as_slice())),
+                        tokens.get(&hex_encode(event.token1.as_slice())),
+                    ) {
+                        let pool = Pool::from_v3_pool_created(
+                            self.chain_id,
+                            log_address.clone(),
+                            event.clone(),
+                            token0,
+                            token1,
+                            *block_number,
+                            tx_hash.clone(),
+                            *block_timestamp,
+                            &self.chain_tokens,
+                        );
+
+                        let new_pool = NewPool::from_pool_created(
+                            self.chain_id as u64,
+                            pool.address.clone(),
+                            *block_number,
+                            tx_hash.clone(),
+                            *block_timestamp,
+                            pool.token0.clone(),
+                            pool.token1.clone(),
+                            pool.token0_symbol.clone(),
+                            pool.token1_symbol.clone(),
+                            pool.protocol.clone().unwrap_or_default(),
+                            pool.protocol_version.clone().unwrap_or_default(),
+                            pool.fee.unwrap_or(0),
+                        );
+
+                        new_pool_records.push(new_pool);
+                        new_pools.insert(pool.address.clone(), pool);
+                    }
+                },
+                ParsedLog::V4Initialize {
+                    event,
+                    log_address,
+                    block_number,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    // ANTI-SPOOFING: Validate pool ID by computing it from event fields
+                    let event_pool_id = hex_encode(event.id.as_slice());
+                    let computed_pool_id = compute_v4_pool_id(
+                        &hex_encode(event.currency0.as_slice()),
+                        &hex_encode(event.currency1.as_slice()),
+                        event.fee.to::<u32>(),
+                        event.tickSpacing.as_i32(),
+                        &event.hooks.to_string(),
+                    );
+
+                    if event_pool_id != computed_pool_id {
+                        warn!(
+                            "V4 Initialize: Pool ID mismatch! Event ID: {}, Computed: {}",
+                            event_pool_id, computed_pool_id
+                        );
+                        continue;
+                    }
+
+                    // FACTORY FILTER: Only index pools from allowed factories
+                    if !self.factories.is_empty()
+                        && !self.factories.contains(&log_address.to_lowercase())
+                    {
+                        continue;
+                    }
+
+                    // FACTORY FILTER: Only index pools from allowed factories
+                    if !self.factories.is_empty()
+                        && !self.factories.contains(&log_address.to_lowercase())
+                    {
+                        continue;
+                    }
+
+                    if let (Some(token0), Some(token1)) = (
+                        tokens.get(&hex_encode(event.currency0.as_slice())),
+                        tokens.get(&hex_encode(event.currency1.as_slice())),
+                    ) {
+                        let pool = Pool::from_v4_pool_created(
+                            self.chain_id,
+                            log_address.clone(),
+                            event.clone(),
+                            token0,
+                            token1,
+                            *block_number,
+                            tx_hash.clone(),
+                            *block_timestamp,
+                            &self.chain_tokens,
+                        );
+
+                        let new_pool = NewPool::from_pool_created(
+                            self.chain_id as u64,
+                            pool.address.clone(),
+                            *block_number,
+                            tx_hash.clone(),
+                            *block_timestamp,
+                            pool.token0.clone(),
+                            pool.token1.clone(),
+                            pool.token0_symbol.clone(),
+                            pool.token1_symbol.clone(),
+                            pool.protocol.clone().unwrap_or_default(),
+                            pool.protocol_version.clone().unwrap_or_default(),
+                            pool.fee.unwrap_or(0),
+                        );
+
+                        new_pool_records.push(new_pool);
+                        new_pools.insert(pool.address.clone(), pool);
+                    }
+                },
+                _ => {}, // Other event types handled in Phase 4
+            }
+        }
+
+        // Collect all pools in a single hashmap for easy access.
+        // new_pools takes precedence (comes second in chain) to handle pools created earlier in the same batch
+        let mut pools: FxHashMap<String, Pool> = updated_pools
+            .into_iter()
+            .chain(new_pools.into_iter())
+            .collect();
+
+        // Phase 4 -> Process all swap/liquidity/transfer events from pre-parsed logs
+        // IMPORTANT: parsed_logs maintains sequential order from original log stream
+        // This is critical for correct pool state updates and native token price tracking
+
+        // Initialize storage vectors with capacity hints
+        let mut events: Vec<Event> = Vec::with_capacity(log_count_estimate / 2);
+        let mut supply_events: Vec<SupplyEvent> = Vec::with_capacity(log_count_estimate / 10);
+        // Aggregators that published an answer in this batch
+        let mut oracle_updates: Vec<String> = Vec::new();
+
+        for parsed_log in parsed_logs {
+            match parsed_log {
+                // V2 Mint
+                ParsedLog::V2Mint {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v2_mint(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V3 Mint
+                ParsedLog::V3Mint {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v3_mint(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V2 Burn
+                ParsedLog::V2Burn {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v2_burn(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V3 Burn
+                ParsedLog::V3Burn {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v3_burn(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V2 Sync
+                ParsedLog::V2Sync {
+                    event,
+                    log_address,
+                    block_number,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        pool.update_v2_sync(&event, block_number, block_timestamp);
+                    }
+                },
+                // V3 Initialize
+                ParsedLog::V3Initialize {
+                    event,
+                    log_address,
+                    block_number,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        pool.update_v3_initialize(&event, block_number, block_timestamp);
+                    }
+                },
+                // V3 Collect
+                ParsedLog::V3Collect {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v3_collect(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V4 ModifyLiquidity
+                ParsedLog::V4ModifyLiquidity {
+                    event,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    let event_pool_id = hex_encode(event.id.as_slice());
+                    if let Some(pool) = pools.get_mut(&event_pool_id) {
+                        // ANTI-SPOOFING: Validate pool ID
+                        let computed_pool_id = compute_v4_pool_id_from_stored(
+                            &pool.token0,
+                            &pool.token1,
+                            pool.initial_fee.unwrap_or(0) as u32,
+                            pool.tick_spacing.unwrap_or(0),
+                            pool.hook_address
+                                .as_deref()
+                                .unwrap_or("0x0000000000000000000000000000000000000000"),
+                        );
+                        if event_pool_id != computed_pool_id {
+                            warn!(
+                                "V4 ModifyLiquidity: Pool ID mismatch! Event ID: {}, Computed: {}",
+                                event_pool_id, computed_pool_id
+                            );
+                            continue;
+                        }
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v4_modify_liquidity(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                                pool,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V2 Swap
+                ParsedLog::V2Swap {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v2_swap(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V3 Swap
+                ParsedLog::V3Swap {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(pool) = pools.get_mut(&log_address) {
+                        // ANTI-SPOOFING: Require initialization
+                        if pool.sqrt_price_x96.is_none() {
+                            continue;
+                        }
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v3_swap(
+                                self.chain_id,
+                                event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+                // V4 Swap
+                ParsedLog::V4Swap {
+                    event: swap_event,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    let event_pool_id = hex_encode(swap_event.id.as_slice());
+                    if let Some(pool) = pools.get_mut(&event_pool_id) {
+                        // ANTI-SPOOFING: Validate pool ID
+                        let computed_pool_id = compute_v4_pool_id_from_stored(
+                            &pool.token0,
+                            &pool.token1,
+                            pool.initial_fee.unwrap_or(0) as u32,
+                            pool.tick_spacing.unwrap_or(0),
+                            pool.hook_address
+                                .as_deref()
+                                .unwrap_or("0x0000000000000000000000000000000000000000"),
+                        );
+                        if event_pool_id != computed_pool_id {
+                            warn!(
+                                "V4 Swap: Pool ID mismatch! Event ID: {}, Computed: {}",
+                                event_pool_id, computed_pool_id
+                            );
+                            continue;
+                        }
+                        // ANTI-SPOOFING: Require initialization
+                        if pool.sqrt_price_x96.is_none() {
+                            continue;
+                        }
+                        pool.update_v4_fee(&swap_event);
+                        if let (Some(token0), Some(token1)) =
+                            (tokens.get(&pool.token0), tokens.get(&pool.token1))
+                        {
+                            let ev = Event::from_v4_swap(
+                                self.chain_id as u64,
+                                swap_event,
+                                token0,
+                                token1,
+                                block_number,
+                                tx_hash,
+                                log_index,
+                                pool.address.clone(),
+                                block_timestamp,
+                            );
+                            pool.update_from_event(&ev);
+                            events.push(ev);
+                        }
+                    }
+                },
+
+                // Supply: Transfer (Mint/Burn)
+                ParsedLog::SupplyTransfer {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                    is_mint,
+                } => {
+                    if let Some(token) = tokens.get(&log_address) {
+                        let event_type = if is_mint { "mint" } else { "burn" }.to_string();
+
+                        let event = SupplyEvent::new(
+                            self.chain_id as u64,
+                            block_number,
+                            block_timestamp,
+                            tx_hash,
+                            log_index,
+                            log_address,
+                            event_type,
+                            event.value,
+                            token.decimals as u8,
+                        );
+
+                        supply_events.push(event);
+                    }
+                },
+                // Supply: Deposit (Mint for Wrapped)
+                ParsedLog::SupplyDeposit {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(token) = tokens.get(&log_address) {
+                        let event = SupplyEvent::new(
+                            self.chain_id as u64,
+                            block_number,
+                            block_timestamp,
+                            tx_hash,
+                            log_index,
+                            log_address,
+                            "mint".to_string(),
+                            event.amount,
+                            token.decimals as u8,
+                        );
+
+                        supply_events.push(event);
+                    }
+                },
+                // Supply: Withdrawal (Burn for Wrapped)
+                ParsedLog::SupplyWithdrawal {
+                    event,
+                    log_address,
+                    block_number,
+                    log_index,
+                    tx_hash,
+                    block_timestamp,
+                } => {
+                    if let Some(token) = tokens.get(&log_address) {
+                        let event = SupplyEvent::new(
+                            self.chain_id as u64,
+                            block_number,
+                            block_timestamp,
+                            tx_hash,
+                            log_index,
+                            log_address,
+                            "burn".to_string(),
+                            event.amount,
+                            token.decimals as u8,
+                        );
+
+                        supply_events.push(event);
+                    }
+                },
+                // Chainlink answer from a configured aggregator
+                ParsedLog::OracleAnswer {
+                    aggregator,
+                    answer,
+                    round_id,
+                    block_number,
+                    block_timestamp,
+                } => {
+                    if let Some(feed) = self.chain_tokens.price_feed(&aggregator) {
+                        let oracle_price = OraclePrice {
+                            chain_id: self.chain_id as i64,
+                            aggregator_address: aggregator.clone(),
+                            token_address: feed.token_address.clone(),
+                            price: feed.answer_to_price(answer),
+                            round_id: round_id.to_string(),
+                            block_number,
+                            answered_at: block_timestamp,
+                            updated_at: Utc::now(),
+                        };
+                        batch_state
+                            .oracle_prices
+                            .insert(aggregator.clone(), oracle_price);
+                        oracle_updates.push(aggregator);
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        for event in &mut events {
+            if let Some(sender) = tx_senders.get(&event.tx_hash) {
+                event.tx_from = sender.clone();
+            }
+        }
+
+        // Phase 4.25 -> Update native token price from the reference pools touched in this batch
+        let mut reference_pools_touched = false;
+        for address in &self.chain_tokens.reference_pools {
+            if let Some(pool) = pools.get(address) {
+                batch_state
+                    .reference_pools
+                    .insert(address.clone(), pool.clone());
+                reference_pools_touched = true;
+            }
+        }
+        if reference_pools_touched {
+            let reference_pools: Vec<&Pool> = batch_state.reference_pools.values().collect();
+            self.pricing_strategy.update_native_price(
+                &mut batch_state.native_token_price,
+                &reference_pools,
+                &self.chain_tokens.wrapped_native_token,
+            );
+        }
+
+        // Oracle answers are authoritative over DEX-derived prices while fresh
+        let latest_block_timestamp = block_timestamps.values().max().copied().unwrap_or(0);
+        if let Some((aggregator, price)) =
+            self.fresh_native_oracle_price(&batch_state.oracle_prices, latest_block_timestamp)
+        {
+            batch_state
+                .native_token_price
+                .update_from_oracle(price, &aggregator);
+        }
+        let oracle_prices_usd = self.fresh_oracle_prices_usd(
+            &batch_state.oracle_prices,
+            latest_block_timestamp,
+            batch_state.native_token_price.price_usd,
+        );
+
+        // Refresh the pricing graph with the pool states of this batch
+        for pool in pools.values() {
+            batch_state.pricing_graph.upsert_pool(pool);
+        }
+
+        // Phase 4.5 -> Price all events and pools in USD
+        // This happens after all events are created and pool states are updated
+        //
+        // IMPORTANT: Create price resolver HERE, after all events processed,
+        // so it has the latest native token price from the reference pools.
+        let batch_tokens: FxHashSet<String> = tokens
+            .keys()
+            .cloned()
+            .chain(
+                pools
+                    .values()
+                    .flat_map(|pool| [pool.token0.to_lowercase(), pool.token1.to_lowercase()]),
+            )
+            .collect();
+        let mut price_resolver = PriceResolver::new(
+            self.chain_tokens.clone(),
+            self.pricing_strategy.clone(),
+            &batch_state.native_token_price,
+            oracle_prices_usd,
+            &batch_state.pricing_graph,
+            &batch_tokens,
+        );
+
+        // Price all events. Re-indexed blocks are behind the pool states and prices,
+        // which only move forward: their rows stay unpriced rather than take today's.
+        if mode == BatchMode::Sync {
+            for event in &mut events {
+                if let Some(pool) = pools.get(&event.pool_address) {
+                    price_resolver.price_event(event, pool);
+                }
+            }
+        }
+
+        // Price all pools (calculate price_usd and tvl_usd)
+        // Use 2-pass approach to avoid cloning the entire pools map
+        // 1. Calculate updates (immutable access to pools)
+        let pool_updates: Vec<_> = pools
+            .values()
+            .map(|pool| {
+                let (price, tvl, provenance) = price_resolver.calculate_pool_pricing(pool);
+                (pool.address.clone(), price, tvl, provenance)
+            })
+            .collect();
+
+        // 2. Apply updates (mutable access to pools)
+        for (address, price, tvl, provenance) in pool_updates {
+            if let Some(pool) = pools.get_mut(&address) {
+                pool.price_usd = price;
+                pool.tvl_usd = tvl;
+                pool.price_provenance = provenance;
+            }
+        }
+
+        // Update initial_tvl_usd for newly created pools
+        for new_pool in &mut new_pool_records {
+            if let Some(pool) = pools.get(&new_pool.pool_address) {
+                if let Some(tvl) = pool.tvl_usd {
+                    new_pool.set_initial_tvl(tvl);
+                }
+            }
+        }
+
+        // Price all tokens using TVL-weighted average from pools where token is base
+        for token in tokens.values_mut() {
+            if let Some((price, provenance)) =
+                price_resolver.calculate_token_price(&token.address, &pools)
+            {
+                token.price_usd = Some(price);
+                token.price_provenance = Some(provenance);
+                token.price_updated_at = Some(Utc::now());
+            }
+        }
+
+        // Phase 4.75 -> MEV analysis on the priced swaps
+        let mut token_price_usd = |token: &str| match mode {
+            BatchMode::Sync => price_resolver.get_token_price_usd(token),
+            BatchMode::Reindex => 0.0,
+        };
+        let mev_sandwiches =
+            mev::detect_sandwiches(self.chain_id, &mut events, &mut token_price_usd);
+        let arbitrages = mev::detect_arbitrages(self.chain_id, &mut events, &mut token_price_usd);
+
+        // Phase 4.8 -> Chain router hops into one trade per user trade
+        let trades = trades::build_trades(self.chain_id, &events, &mut token_price_usd);
+
+        // Phase 5 -> Flush pool states to PostgreSQL
+        // IMPORTANT: We must await these AND check for errors before updating checkpoint
+        // to prevent race conditions and data loss
+        // NOTE: Tokens are already saved in TokenFetcher when first discovered
+        // Re-indexed ranges are behind the stored state, which must not move back.
+        if mode == BatchMode::Sync {
+            let pools_to_flush: Vec<&Pool> = pools.values().collect();
+
+            // Batch flush tokens with updated prices
+            let tokens_to_flush: Vec<&crate::db::models::Token> = tokens.values().collect();
+
+            // Execute DB writes in parallel to reduce latency
+            let (pools_res, _) = tokio::join!(
+                self.db.postgres.set_pools(&pools_to_flush),
+                self.db.postgres.set_tokens(&tokens_to_flush)
+            );
+
+            // Check critical results
+            if let Err(e) = pools_res {
+                warn!(
+                    "Chain {}: Failed to batch write pools: {:?}",
+                    self.chain_id, e
+                );
+            }
+        }
+
+        // Phase 6 -> Rows for ClickHouse, sent by the caller
+        let batch = BatchDataMessage {
+            chain_id: self.chain_id,
+            events,
+            supply_events,
+            new_pools: new_pool_records,
+            mev_sandwiches,
+            arbitrages,
+            trades,
+            pools: pools.values().cloned().collect(),
+            tokens: tokens.values().cloned().collect(),
+        };
+
+        Ok(ProcessedBatch {
+            batch,
+            latest_block_timestamp,
+            oracle_updates,
+        })
+    }
+
+    /// Fresh USD-denominated oracle answer for the wrapped native token, with its aggregator.
+    fn fresh_native_oracle_price(
+        &self,
+        oracle_prices: &FxHashMap<String, OraclePrice>,
+        block_timestamp: u64,
+    ) -> Option<(String, f64)> {
+        self.chain_tokens
+            .price_feeds
+            .iter()
+            .filter(|feed| {
+                !feed.is_native_denominated()
+                    && self.chain_tokens.is_wrapped_native(&feed.token_address)
+            })
+            .find_map(|feed| {
+                let answer = oracle_prices.get(&feed.aggregator_address)?;
+                answer
+                    .is_fresh(block_timestamp, feed.heartbeat_secs)
+                    .then(|| (feed.aggregator_address.clone(), answer.price))
+            })
+    }
+
+    /// USD prices of all tokens with a fresh oracle answer.
+    ///
+    /// Native-denominated answers are converted with `native_price_usd`.
+    fn fresh_oracle_prices_usd(
+        &self,
+        oracle_prices: &FxHashMap<String, OraclePrice>,
+        block_timestamp: u64,
+        native_price_usd: f64,
+    ) -> FxHashMap<String, OracleQuote> {
+        let mut prices = FxHashMap::default();
+
+        for feed in &self.chain_tokens.price_feeds {
+            let Some(answer) = oracle_prices.get(&feed.aggregator_address) else {
+                continue;
+            };
+            if !answer.is_fresh(block_timestamp, feed.heartbeat_secs) {
+                continue;
+            }
+
+            let price_usd = if feed.is_native_denominated() {
+                answer.price * native_price_usd
+            } else {
+                answer.price
+            };
+
+            if price_usd > 0.0 && price_usd.is_finite() {
+                prices.insert(
+                    feed.token_address.clone(),
+                    OracleQuote {
+                        price_usd,
+                        aggregator_address: feed.aggregator_address.clone(),
+                    },
+                );
+            }
+        }
+
+        prices
+    }
 }